@@ -1,6 +1,8 @@
 use crate::{
     debug_log::debug_log, ghostty::GhosttyManager, ghostty_vt::GhosttyVtManager,
     native_terminal::NativeTerminalManager, runtime::WorkspaceRuntimeManager,
+    runtime_log::RuntimeLogManager, workspace_file::WorkspaceFileWatcherManager,
+    workspace_services::WorkspaceServiceManager,
 };
 use std::sync::{
     atomic::{AtomicBool, Ordering},
@@ -19,6 +21,9 @@ struct AppCleanupState {
     ghostty_vt_manager: GhosttyVtManager,
     native_terminal_manager: NativeTerminalManager,
     runtime_manager: WorkspaceRuntimeManager,
+    runtime_log_manager: RuntimeLogManager,
+    service_manager: WorkspaceServiceManager,
+    workspace_file_watcher: WorkspaceFileWatcherManager,
 }
 
 impl AppCleanupService {
@@ -27,6 +32,9 @@ impl AppCleanupService {
         ghostty_vt_manager: GhosttyVtManager,
         native_terminal_manager: NativeTerminalManager,
         runtime_manager: WorkspaceRuntimeManager,
+        runtime_log_manager: RuntimeLogManager,
+        service_manager: WorkspaceServiceManager,
+        workspace_file_watcher: WorkspaceFileWatcherManager,
     ) -> Self {
         Self {
             inner: Arc::new(AppCleanupState {
@@ -35,6 +43,9 @@ impl AppCleanupService {
                 ghostty_vt_manager,
                 native_terminal_manager,
                 runtime_manager,
+                runtime_log_manager,
+                service_manager,
+                workspace_file_watcher,
             }),
         }
     }
@@ -48,11 +59,12 @@ impl AppCleanupService {
         debug_log(
             "app",
             format!(
-                "cleanup start reason={reason} native_blocks={} ghostty_blocks={} vt_sessions={} runtimes={}",
+                "cleanup start reason={reason} native_blocks={} ghostty_blocks={} vt_sessions={} runtimes={} services={}",
                 self.inner.native_terminal_manager.block_count(),
                 self.inner.ghostty_manager.block_count(),
                 self.inner.ghostty_vt_manager.session_count(),
                 self.inner.runtime_manager.runtime_count(),
+                self.inner.service_manager.service_count(),
             ),
         );
 
@@ -68,15 +80,19 @@ impl AppCleanupService {
             debug_log("app", format!("ghostty vt cleanup failed: {error}"));
         }
 
+        self.inner.runtime_log_manager.stop_all();
+        self.inner.workspace_file_watcher.stop_all();
+        self.inner.service_manager.stop_all();
         self.inner.runtime_manager.stop_all();
         debug_log(
             "app",
             format!(
-                "cleanup complete reason={reason} native_blocks={} ghostty_blocks={} vt_sessions={} runtimes={}",
+                "cleanup complete reason={reason} native_blocks={} ghostty_blocks={} vt_sessions={} runtimes={} services={}",
                 self.inner.native_terminal_manager.block_count(),
                 self.inner.ghostty_manager.block_count(),
                 self.inner.ghostty_vt_manager.session_count(),
                 self.inner.runtime_manager.runtime_count(),
+                self.inner.service_manager.service_count(),
             ),
         );
     }
@@ -1,8 +1,18 @@
-use serde::Serialize;
+use crate::ghostty_vt_frame::encode_frame;
+use crate::shell_integration::ShellCommandRecord;
+use crate::terminal_links::open_link;
+use crate::terminal_recording::read_asciicast;
+use crate::workspace_env::{resolve_env_for, WorkspaceEnvSpec};
+use serde::{Deserialize, Serialize};
 use std::{
     collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
     sync::{Arc, Mutex, OnceLock},
+    time::{SystemTime, UNIX_EPOCH},
 };
+use tauri::http::{header, Method, Request, Response, StatusCode};
+use tauri::Manager;
 
 #[derive(Clone, Default)]
 pub struct GhosttyVtManager {
@@ -18,6 +28,11 @@ impl GhosttyVtManager {
     pub fn stop_all(&self) -> Result<(), String> {
         imp::ghostty_vt_destroy_all_sessions_in_map(&self.inner)
     }
+
+    /// Session ids paired with the pid of their live PTY child.
+    pub fn session_pids(&self) -> Vec<(String, u32)> {
+        imp::ghostty_vt_session_pids_in_map(&self.inner)
+    }
 }
 
 static REGISTERED_MANAGER: OnceLock<Arc<Mutex<HashMap<String, Arc<imp::SessionHandle>>>>> =
@@ -62,12 +77,18 @@ pub struct GhosttyVtCell {
     pub underline: bool,
     pub strikethrough: bool,
     pub invisible: bool,
+    /// The second cell of a wide character, or the padding left at the end
+    /// of a row when one wrapped; it has no text of its own.
+    #[serde(skip)]
+    pub spacer: bool,
 }
 
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct GhosttyVtRow {
     pub cells: Vec<GhosttyVtCell>,
+    /// The line was soft-wrapped and continues on the next row.
+    pub wrapped: bool,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -80,6 +101,47 @@ pub struct GhosttyVtCursor {
     pub shape: String,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum GhosttyVtLinkKind {
+    /// Set by the application with OSC 8.
+    Hyperlink,
+    Url,
+    /// A path, optionally with a line and column, resolved against the
+    /// directory the row was printed in.
+    File,
+}
+
+/// A link in viewport row `row`; `end_col` is exclusive.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GhosttyVtLink {
+    pub row: u16,
+    pub start_col: u16,
+    pub end_col: u16,
+    pub kind: GhosttyVtLinkKind,
+    /// The URL, or the absolute path for files.
+    pub target: String,
+    pub line: Option<u32>,
+    pub column: Option<u32>,
+}
+
+/// What a session is running, for labelling tabs and confirming before
+/// closing a block with a job in the foreground.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GhosttyVtSessionInfo {
+    /// The last OSC 0 or OSC 2 window title.
+    pub title: Option<String>,
+    /// Process group leader in the foreground of the PTY.
+    pub foreground_pid: Option<u32>,
+    pub foreground_name: Option<String>,
+    pub foreground_argv: Vec<String>,
+    /// A process other than the session's shell owns the terminal, or the
+    /// session was started with a command that is still running.
+    pub busy: bool,
+}
+
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct GhosttyVtSnapshot {
@@ -94,6 +156,118 @@ pub struct GhosttyVtSnapshot {
     pub cursor: GhosttyVtCursor,
     pub process_alive: bool,
     pub exit_status: Option<i32>,
+    pub info: GhosttyVtSessionInfo,
+    pub links: Vec<GhosttyVtLink>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GhosttyVtRowUpdate {
+    pub index: u16,
+    pub cells: Vec<GhosttyVtCell>,
+}
+
+/// Viewport rows changed since the sequence the client passed in. When
+/// `full` is set every row is included and the client should replace its
+/// copy, e.g. after a resize or when it passed a sequence it cannot have
+/// seen.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GhosttyVtDelta {
+    pub session_id: String,
+    pub seq: u64,
+    pub full: bool,
+    pub cols: u16,
+    pub rows: u16,
+    pub changed_rows: Vec<GhosttyVtRowUpdate>,
+    /// Links in the changed rows.
+    pub links: Vec<GhosttyVtLink>,
+    pub default_fg: GhosttyVtRgb,
+    pub default_bg: GhosttyVtRgb,
+    pub ansi_palette: Vec<GhosttyVtRgb>,
+    pub cursor: GhosttyVtCursor,
+    pub process_alive: bool,
+    pub exit_status: Option<i32>,
+}
+
+/// A match in the scrollback. `row` counts from the first line the session
+/// printed, so it stays valid while old lines are trimmed from the
+/// scrollback; columns are cell columns, `end_col` exclusive.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GhosttyVtSearchMatch {
+    pub row: u64,
+    pub start_col: u16,
+    pub end_col: u16,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GhosttyVtSearchResult {
+    pub matches: Vec<GhosttyVtSearchMatch>,
+    /// Row of the oldest line still in the scrollback.
+    pub first_row: u64,
+    pub total_rows: u64,
+    /// Set when the match limit was hit and later matches were dropped.
+    pub truncated: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GhosttyVtSearchPosition {
+    pub index: usize,
+    pub total: usize,
+    #[serde(rename = "match")]
+    pub search_match: GhosttyVtSearchMatch,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum GhosttyVtSearchDirection {
+    Next,
+    Previous,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum GhosttyVtExportFormat {
+    Text,
+    Ansi,
+    Html,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GhosttyVtPasteResult {
+    pub pasted: bool,
+    /// The application had bracketed paste (DEC mode 2004) enabled.
+    pub bracketed: bool,
+    /// Escape sequences and control characters removed from the text.
+    pub stripped_controls: usize,
+    pub bytes_written: usize,
+    /// Set instead of pasting when the text has several lines and the
+    /// shell at the prompt would run each as it arrives. Paste again with
+    /// `allow_multiline` to go ahead.
+    pub warning: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GhosttyVtOpenLinkResult {
+    pub opened: bool,
+    /// Set instead of opening a link that another app would handle. Open
+    /// it again with `allow_external` once the user confirmed.
+    pub warning: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GhosttyVtExport {
+    pub rows: u64,
+    /// The export itself, unless it was written to `path`.
+    pub content: Option<String>,
+    pub path: Option<String>,
 }
 
 #[tauri::command]
@@ -111,7 +285,13 @@ pub fn ghostty_vt_create_session(
     command: Option<String>,
     cols: Option<u16>,
     rows: Option<u16>,
+    env_spec: Option<WorkspaceEnvSpec>,
 ) -> Result<(), String> {
+    let default_root = workspace_root.as_deref().or(cwd.as_deref()).unwrap_or_default();
+    let env = resolve_env_for(env_spec, default_root)?;
+    let command = command
+        .map(|command| env.interpolate(&command))
+        .transpose()?;
     imp::ghostty_vt_create_session(
         &app_handle,
         manager.inner(),
@@ -121,6 +301,7 @@ pub fn ghostty_vt_create_session(
         command.as_deref(),
         cols,
         rows,
+        &env.pairs(),
     )
 }
 
@@ -152,6 +333,29 @@ pub fn ghostty_vt_send_text(
     imp::ghostty_vt_send_text(manager.inner(), &session_id, &text)
 }
 
+/// Pastes `text` the way a terminal does: control sequences are stripped,
+/// the text is bracketed when the application asked for it, and large
+/// pastes are written as fast as the PTY accepts them.
+#[tauri::command]
+pub async fn ghostty_vt_paste(
+    manager: tauri::State<'_, GhosttyVtManager>,
+    session_id: String,
+    text: String,
+    allow_multiline: Option<bool>,
+) -> Result<GhosttyVtPasteResult, String> {
+    let manager = manager.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        imp::ghostty_vt_paste(
+            &manager,
+            &session_id,
+            &text,
+            allow_multiline.unwrap_or(false),
+        )
+    })
+    .await
+    .map_err(|error| format!("Paste did not complete: {}", error))?
+}
+
 #[tauri::command]
 pub fn ghostty_vt_input_key(
     manager: tauri::State<'_, GhosttyVtManager>,
@@ -196,6 +400,336 @@ pub fn ghostty_vt_snapshot_session(
     imp::ghostty_vt_snapshot_session(manager.inner(), &session_id)
 }
 
+/// Returns the viewport rows that changed after `since_seq`. Omit
+/// `since_seq` to get a full frame and a sequence to continue from.
+#[tauri::command]
+pub fn ghostty_vt_snapshot_delta(
+    manager: tauri::State<'_, GhosttyVtManager>,
+    session_id: String,
+    since_seq: Option<u64>,
+) -> Result<GhosttyVtDelta, String> {
+    imp::ghostty_vt_snapshot_delta(manager.inner(), &session_id, since_seq)
+}
+
+/// Commands run in the session's shell, oldest first. Needs the shell
+/// integration injected at spawn (zsh, bash and fish).
+#[tauri::command]
+pub fn ghostty_vt_command_records(
+    manager: tauri::State<'_, GhosttyVtManager>,
+    session_id: String,
+) -> Result<Vec<ShellCommandRecord>, String> {
+    imp::ghostty_vt_command_records(manager.inner(), &session_id)
+}
+
+#[tauri::command]
+pub fn ghostty_vt_jump_to_prompt(
+    manager: tauri::State<'_, GhosttyVtManager>,
+    session_id: String,
+    direction: GhosttyVtSearchDirection,
+) -> Result<Option<ShellCommandRecord>, String> {
+    imp::ghostty_vt_jump_to_prompt(manager.inner(), &session_id, direction)
+}
+
+/// Refreshes and returns the session's foreground process and title.
+#[tauri::command]
+pub fn ghostty_vt_session_info(
+    manager: tauri::State<'_, GhosttyVtManager>,
+    session_id: String,
+) -> Result<GhosttyVtSessionInfo, String> {
+    imp::ghostty_vt_session_info(manager.inner(), &session_id)
+}
+
+/// Opens a link found in a session: web URLs in a browser block next to
+/// it, mail links with the mail app and files in the user's editor. Other
+/// URLs go to the system handler only with `allow_external`.
+#[tauri::command]
+pub fn ghostty_vt_open_link(
+    app_handle: tauri::AppHandle,
+    session_id: String,
+    target: String,
+    line: Option<u32>,
+    column: Option<u32>,
+    allow_external: Option<bool>,
+) -> Result<GhosttyVtOpenLinkResult, String> {
+    let warning = open_link(
+        &app_handle,
+        &session_id,
+        &target,
+        line,
+        column,
+        allow_external.unwrap_or(false),
+    )?;
+    Ok(GhosttyVtOpenLinkResult {
+        opened: warning.is_none(),
+        warning,
+    })
+}
+
+/// Session id from `ghostty-vt://session/<id>`, which Windows webviews
+/// request as `http://ghostty-vt.localhost/session/<id>`.
+fn frame_request_session_id(uri: &tauri::http::Uri) -> Option<String> {
+    let encoded = if uri.host() == Some("session") {
+        uri.path().strip_prefix('/')?
+    } else {
+        uri.path().strip_prefix("/session/")?
+    };
+    let mut decoded = Vec::with_capacity(encoded.len());
+    let mut bytes = encoded.bytes();
+    while let Some(byte) = bytes.next() {
+        if byte != b'%' {
+            decoded.push(byte);
+            continue;
+        }
+        let hex = [bytes.next()?, bytes.next()?];
+        decoded.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+    }
+    String::from_utf8(decoded).ok().filter(|id| !id.is_empty())
+}
+
+/// Only the canvas itself may read terminal frames.
+const FRAME_WEBVIEW_LABEL: &str = "main";
+
+/// The `scheme://host[:port]` origin the canvas webview is served from,
+/// which differs between dev builds and each platform's bundled app.
+fn webview_origin(app_handle: &tauri::AppHandle, label: &str) -> Option<String> {
+    let url = app_handle.get_webview_window(label)?.url().ok()?;
+    let host = url.host_str()?;
+    Some(match url.port() {
+        Some(port) => format!("{}://{}:{}", url.scheme(), host, port),
+        None => format!("{}://{}", url.scheme(), host),
+    })
+}
+
+fn frame_response(
+    status: StatusCode,
+    origin: Option<&str>,
+    content_type: &str,
+    body: Vec<u8>,
+) -> Response<Vec<u8>> {
+    let mut response = Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, content_type)
+        .header(header::CACHE_CONTROL, "no-store")
+        .header(header::VARY, "Origin");
+    if let Some(origin) = origin {
+        response = response.header(header::ACCESS_CONTROL_ALLOW_ORIGIN, origin);
+    }
+    response
+        .body(body)
+        .unwrap_or_else(|_| Response::new(Vec::new()))
+}
+
+/// Handler for the `ghostty-vt` URI scheme. Serves the current frame of a
+/// session in the binary layout described in `ghostty_vt_frame` to the
+/// canvas webview. Snapshots lock the session, so call it off the main
+/// thread.
+pub(crate) fn frame_protocol_response(
+    app_handle: &tauri::AppHandle,
+    webview_label: &str,
+    request: &Request<Vec<u8>>,
+) -> Response<Vec<u8>> {
+    let origin = webview_origin(app_handle, webview_label);
+    let error = |status: StatusCode, message: String| {
+        frame_response(
+            status,
+            origin.as_deref(),
+            "text/plain; charset=utf-8",
+            message.into_bytes(),
+        )
+    };
+    if webview_label != FRAME_WEBVIEW_LABEL {
+        return error(
+            StatusCode::FORBIDDEN,
+            format!("Webview {webview_label} may not read terminal frames"),
+        );
+    }
+    if request.method() != Method::GET {
+        return error(
+            StatusCode::METHOD_NOT_ALLOWED,
+            format!("{} is not supported", request.method()),
+        );
+    }
+    let Some(session_id) = frame_request_session_id(request.uri()) else {
+        return error(
+            StatusCode::BAD_REQUEST,
+            format!("Expected ghostty-vt://session/<id>, got {}", request.uri()),
+        );
+    };
+
+    let manager = app_handle.state::<GhosttyVtManager>();
+    match imp::ghostty_vt_snapshot_session(manager.inner(), &session_id) {
+        Ok(snapshot) => frame_response(
+            StatusCode::OK,
+            origin.as_deref(),
+            "application/octet-stream",
+            encode_frame(&snapshot),
+        ),
+        Err(message) => {
+            let status = if !imp::ghostty_vt_status().is_ok_and(|status| status.available) {
+                StatusCode::SERVICE_UNAVAILABLE
+            } else if manager
+                .inner
+                .lock()
+                .is_ok_and(|sessions| !sessions.contains_key(&session_id))
+            {
+                StatusCode::NOT_FOUND
+            } else {
+                StatusCode::INTERNAL_SERVER_ERROR
+            };
+            error(status, message)
+        }
+    }
+}
+
+/// Searches the whole scrollback of a session, oldest line first. The
+/// matches are kept on the session for `ghostty_vt_search_navigate`.
+#[tauri::command]
+pub async fn ghostty_vt_search(
+    manager: tauri::State<'_, GhosttyVtManager>,
+    session_id: String,
+    query: String,
+    regex: Option<bool>,
+    case_sensitive: Option<bool>,
+) -> Result<GhosttyVtSearchResult, String> {
+    // Collecting the scrollback pages through the whole buffer.
+    let manager = manager.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        imp::ghostty_vt_search(
+            &manager,
+            &session_id,
+            &query,
+            regex.unwrap_or(false),
+            case_sensitive.unwrap_or(false),
+        )
+    })
+    .await
+    .map_err(|error| format!("Search did not complete: {}", error))?
+}
+
+/// Moves to the next or previous match of the last search and scrolls the
+/// viewport to it. Returns `None` when the last search found nothing.
+#[tauri::command]
+pub fn ghostty_vt_search_navigate(
+    manager: tauri::State<'_, GhosttyVtManager>,
+    session_id: String,
+    direction: GhosttyVtSearchDirection,
+) -> Result<Option<GhosttyVtSearchPosition>, String> {
+    imp::ghostty_vt_search_navigate(manager.inner(), &session_id, direction)
+}
+
+/// Renders the whole scrollback as plain text, text with SGR escapes or a
+/// styled HTML fragment. When `path` is given the export is written there
+/// instead of being returned.
+#[tauri::command]
+pub async fn ghostty_vt_export_scrollback(
+    manager: tauri::State<'_, GhosttyVtManager>,
+    session_id: String,
+    format: GhosttyVtExportFormat,
+    path: Option<String>,
+) -> Result<GhosttyVtExport, String> {
+    // Rendering pages through the whole buffer and writing the file.
+    let manager = manager.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let (content, rows) = imp::ghostty_vt_export_scrollback(&manager, &session_id, format)?;
+        match path
+            .map(|path| path.trim().to_string())
+            .filter(|path| !path.is_empty())
+        {
+            Some(path) => {
+                fs::write(&path, content)
+                    .map_err(|error| format!("Failed to write scrollback to {path}: {error}"))?;
+                Ok(GhosttyVtExport {
+                    rows,
+                    content: None,
+                    path: Some(path),
+                })
+            }
+            None => Ok(GhosttyVtExport {
+                rows,
+                content: Some(content),
+                path: None,
+            }),
+        }
+    })
+    .await
+    .map_err(|error| format!("Scrollback export did not complete: {}", error))?
+}
+
+fn default_recording_path(app_handle: &tauri::AppHandle, session_id: &str) -> Result<PathBuf, String> {
+    let app_data_dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|error| format!("Failed to resolve app data dir: {error}"))?;
+    let name: String = session_id
+        .chars()
+        .map(|ch| if ch.is_ascii_alphanumeric() || ch == '-' || ch == '_' { ch } else { '-' })
+        .collect();
+    let started_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis())
+        .unwrap_or(0);
+    Ok(app_data_dir
+        .join("recordings")
+        .join(format!("{name}-{started_ms}.cast")))
+}
+
+/// Starts recording the session's output as an asciicast v2 file, by
+/// default under the app data dir. Returns the recording path.
+#[tauri::command]
+pub fn ghostty_vt_start_recording(
+    app_handle: tauri::AppHandle,
+    manager: tauri::State<'_, GhosttyVtManager>,
+    session_id: String,
+    path: Option<String>,
+) -> Result<String, String> {
+    let path = match path
+        .map(|path| path.trim().to_string())
+        .filter(|path| !path.is_empty())
+    {
+        Some(path) => PathBuf::from(path),
+        None => default_recording_path(&app_handle, &session_id)?,
+    };
+    imp::ghostty_vt_start_recording(manager.inner(), &session_id, &path)?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// Finishes the active recording and returns its path, if there was one.
+#[tauri::command]
+pub fn ghostty_vt_stop_recording(
+    manager: tauri::State<'_, GhosttyVtManager>,
+    session_id: String,
+) -> Result<Option<String>, String> {
+    Ok(imp::ghostty_vt_stop_recording(manager.inner(), &session_id)?
+        .map(|path| path.to_string_lossy().to_string()))
+}
+
+/// Creates a session without a PTY that plays back an asciicast file.
+/// `speed` multiplies playback speed and `max_idle_secs` caps pauses,
+/// falling back to the recording's `idle_time_limit`.
+#[tauri::command]
+pub fn ghostty_vt_create_replay_session(
+    app_handle: tauri::AppHandle,
+    manager: tauri::State<'_, GhosttyVtManager>,
+    session_id: String,
+    path: String,
+    speed: Option<f64>,
+    max_idle_secs: Option<f64>,
+) -> Result<(), String> {
+    let speed = speed.unwrap_or(1.0);
+    if !speed.is_finite() || speed <= 0.0 {
+        return Err(format!("Invalid replay speed {speed}"));
+    }
+    let recording = read_asciicast(Path::new(path.trim()))?;
+    imp::ghostty_vt_create_replay_session(
+        &app_handle,
+        manager.inner(),
+        &session_id,
+        recording,
+        speed,
+        max_idle_secs.filter(|limit| limit.is_finite() && *limit > 0.0),
+    )
+}
+
 #[tauri::command]
 pub fn ghostty_vt_destroy_session(
     manager: tauri::State<'_, GhosttyVtManager>,
@@ -222,6 +756,7 @@ pub(crate) fn create_registered_session(
         command,
         cols,
         rows,
+        &[],
     )
 }
 
@@ -355,6 +890,7 @@ mod imp {
     pub(super) struct SessionHandle;
 
     use super::*;
+    use crate::terminal_recording::AsciicastRecording;
 
     const UNAVAILABLE_MESSAGE: &str = "libghostty-vt prototype is not enabled for this build. Set OTTO_CANVAS_LIBGHOSTTY_VT_SOURCE_DIR to a Ghostty checkout (for example tmp/ghostty) or OTTO_CANVAS_LIBGHOSTTY_VT_LIB_DIR to a directory containing libghostty-vt.a before building apps/canvas/src-tauri.";
 
@@ -376,6 +912,7 @@ mod imp {
         command: Option<&str>,
         cols: Option<u16>,
         rows: Option<u16>,
+        env: &[(String, String)],
     ) -> Result<(), String> {
         let _ = (
             app_handle,
@@ -386,6 +923,7 @@ mod imp {
             command,
             cols,
             rows,
+            env,
         );
         Err(UNAVAILABLE_MESSAGE.to_string())
     }
@@ -418,6 +956,16 @@ mod imp {
         Err(UNAVAILABLE_MESSAGE.to_string())
     }
 
+    pub fn ghostty_vt_paste(
+        manager: &GhosttyVtManager,
+        session_id: &str,
+        text: &str,
+        allow_multiline: bool,
+    ) -> Result<GhosttyVtPasteResult, String> {
+        let _ = (manager, session_id, text, allow_multiline);
+        Err(UNAVAILABLE_MESSAGE.to_string())
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub fn ghostty_vt_input_key(
         manager: &GhosttyVtManager,
@@ -473,6 +1021,98 @@ mod imp {
         Err(UNAVAILABLE_MESSAGE.to_string())
     }
 
+    pub fn ghostty_vt_snapshot_delta(
+        manager: &GhosttyVtManager,
+        session_id: &str,
+        since_seq: Option<u64>,
+    ) -> Result<GhosttyVtDelta, String> {
+        let _ = (manager, session_id, since_seq);
+        Err(UNAVAILABLE_MESSAGE.to_string())
+    }
+
+    pub fn ghostty_vt_command_records(
+        manager: &GhosttyVtManager,
+        session_id: &str,
+    ) -> Result<Vec<ShellCommandRecord>, String> {
+        let _ = (manager, session_id);
+        Err(UNAVAILABLE_MESSAGE.to_string())
+    }
+
+    pub fn ghostty_vt_jump_to_prompt(
+        manager: &GhosttyVtManager,
+        session_id: &str,
+        direction: GhosttyVtSearchDirection,
+    ) -> Result<Option<ShellCommandRecord>, String> {
+        let _ = (manager, session_id, direction);
+        Err(UNAVAILABLE_MESSAGE.to_string())
+    }
+
+    pub fn ghostty_vt_session_info(
+        manager: &GhosttyVtManager,
+        session_id: &str,
+    ) -> Result<GhosttyVtSessionInfo, String> {
+        let _ = (manager, session_id);
+        Err(UNAVAILABLE_MESSAGE.to_string())
+    }
+
+    pub fn ghostty_vt_search(
+        manager: &GhosttyVtManager,
+        session_id: &str,
+        query: &str,
+        regex: bool,
+        case_sensitive: bool,
+    ) -> Result<GhosttyVtSearchResult, String> {
+        let _ = (manager, session_id, query, regex, case_sensitive);
+        Err(UNAVAILABLE_MESSAGE.to_string())
+    }
+
+    pub fn ghostty_vt_search_navigate(
+        manager: &GhosttyVtManager,
+        session_id: &str,
+        direction: GhosttyVtSearchDirection,
+    ) -> Result<Option<GhosttyVtSearchPosition>, String> {
+        let _ = (manager, session_id, direction);
+        Err(UNAVAILABLE_MESSAGE.to_string())
+    }
+
+    pub fn ghostty_vt_export_scrollback(
+        manager: &GhosttyVtManager,
+        session_id: &str,
+        format: GhosttyVtExportFormat,
+    ) -> Result<(String, u64), String> {
+        let _ = (manager, session_id, format);
+        Err(UNAVAILABLE_MESSAGE.to_string())
+    }
+
+    pub fn ghostty_vt_start_recording(
+        manager: &GhosttyVtManager,
+        session_id: &str,
+        path: &Path,
+    ) -> Result<(), String> {
+        let _ = (manager, session_id, path);
+        Err(UNAVAILABLE_MESSAGE.to_string())
+    }
+
+    pub fn ghostty_vt_stop_recording(
+        manager: &GhosttyVtManager,
+        session_id: &str,
+    ) -> Result<Option<PathBuf>, String> {
+        let _ = (manager, session_id);
+        Err(UNAVAILABLE_MESSAGE.to_string())
+    }
+
+    pub fn ghostty_vt_create_replay_session(
+        app_handle: &tauri::AppHandle,
+        manager: &GhosttyVtManager,
+        session_id: &str,
+        recording: AsciicastRecording,
+        speed: f64,
+        max_idle_secs: Option<f64>,
+    ) -> Result<(), String> {
+        let _ = (app_handle, manager, session_id, recording, speed, max_idle_secs);
+        Err(UNAVAILABLE_MESSAGE.to_string())
+    }
+
     pub fn ghostty_vt_destroy_session(
         manager: &GhosttyVtManager,
         session_id: &str,
@@ -481,16 +1121,29 @@ mod imp {
         Err(UNAVAILABLE_MESSAGE.to_string())
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn ghostty_vt_create_session_in_map(
         app_handle: &tauri::AppHandle,
         sessions: &Arc<Mutex<HashMap<String, Arc<SessionHandle>>>>,
         session_id: &str,
         cwd: Option<&str>,
+        workspace_root: Option<&str>,
         command: Option<&str>,
         cols: u16,
         rows: u16,
+        env: &[(String, String)],
     ) -> Result<(), String> {
-        let _ = (app_handle, sessions, session_id, cwd, command, cols, rows);
+        let _ = (
+            app_handle,
+            sessions,
+            session_id,
+            cwd,
+            workspace_root,
+            command,
+            cols,
+            rows,
+            env,
+        );
         Err(UNAVAILABLE_MESSAGE.to_string())
     }
 
@@ -547,6 +1200,13 @@ mod imp {
         Ok(())
     }
 
+    pub fn ghostty_vt_session_pids_in_map(
+        sessions: &Arc<Mutex<HashMap<String, Arc<SessionHandle>>>>,
+    ) -> Vec<(String, u32)> {
+        let _ = sessions;
+        Vec::new()
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub fn ghostty_vt_mouse_button_in_map(
         sessions: &Arc<Mutex<HashMap<String, Arc<SessionHandle>>>>,
@@ -600,25 +1260,36 @@ mod imp {
 #[cfg(otto_canvas_libghostty_vt)]
 mod imp {
     use super::{
-        GhosttyVtCell, GhosttyVtCursor, GhosttyVtManager, GhosttyVtRgb, GhosttyVtRow,
-        GhosttyVtSnapshot, GhosttyVtStatus,
+        GhosttyVtCell, GhosttyVtCursor, GhosttyVtDelta, GhosttyVtExportFormat, GhosttyVtLink,
+        GhosttyVtLinkKind, GhosttyVtManager, GhosttyVtPasteResult, GhosttyVtRgb, GhosttyVtRow,
+        GhosttyVtRowUpdate, GhosttyVtSearchDirection, GhosttyVtSearchMatch,
+        GhosttyVtSearchPosition, GhosttyVtSearchResult, GhosttyVtSessionInfo, GhosttyVtSnapshot,
+        GhosttyVtStatus,
     };
     use serde::Serialize;
     use std::{
         collections::HashMap,
         ffi::{c_char, c_int, c_void, CStr, CString},
         io, mem,
-        os::fd::RawFd,
+        os::fd::{AsRawFd, FromRawFd, OwnedFd, RawFd},
         path::{Path, PathBuf},
-        process::Command,
         ptr, slice,
         sync::{
             atomic::{AtomicBool, AtomicI32, Ordering},
-            Arc, Mutex,
+            mpsc, Arc, Mutex, OnceLock,
         },
         thread::{self, JoinHandle},
         time::{Duration, Instant},
     };
+    use crate::process_command::process_command;
+    use crate::shell_env::{resolve_login_shell_path, resolve_user_shell_path};
+    use crate::shell_integration::{
+        parse_shell_mark, prepare_shell_integration, CommandChange, CommandTracker, OscScanner,
+        ShellCommandRecord, ShellMark,
+    };
+    use crate::terminal_links::{find_text_links, parse_hyperlink, HyperlinkTracker};
+    use crate::terminal_paste::prepare_paste;
+    use crate::terminal_recording::{AsciicastEvent, AsciicastRecording, AsciicastWriter};
     use tauri::{AppHandle, Emitter};
 
     const GHOSTTY_SUCCESS: i32 = 0;
@@ -627,7 +1298,10 @@ mod imp {
     const GHOSTTY_TERMINAL_OPT_XTVERSION: i32 = 4;
     const GHOSTTY_TERMINAL_OPT_SIZE: i32 = 6;
     const GHOSTTY_TERMINAL_OPT_DEVICE_ATTRIBUTES: i32 = 8;
+    const GHOSTTY_SCROLL_VIEWPORT_TOP: i32 = 0;
+    const GHOSTTY_SCROLL_VIEWPORT_BOTTOM: i32 = 1;
     const GHOSTTY_SCROLL_VIEWPORT_DELTA: i32 = 2;
+    const GHOSTTY_RENDER_STATE_DATA_DIRTY: i32 = 3;
     const GHOSTTY_RENDER_STATE_DATA_ROW_ITERATOR: i32 = 4;
     const GHOSTTY_RENDER_STATE_DATA_CURSOR_VISUAL_STYLE: i32 = 10;
     const GHOSTTY_RENDER_STATE_DATA_CURSOR_VISIBLE: i32 = 11;
@@ -635,14 +1309,29 @@ mod imp {
     const GHOSTTY_RENDER_STATE_DATA_CURSOR_VIEWPORT_HAS_VALUE: i32 = 14;
     const GHOSTTY_RENDER_STATE_DATA_CURSOR_VIEWPORT_X: i32 = 15;
     const GHOSTTY_RENDER_STATE_DATA_CURSOR_VIEWPORT_Y: i32 = 16;
+    const GHOSTTY_RENDER_STATE_DIRTY_FALSE: i32 = 0;
+    const GHOSTTY_RENDER_STATE_DIRTY_FULL: i32 = 2;
+    const GHOSTTY_RENDER_STATE_OPTION_DIRTY: i32 = 0;
+    const GHOSTTY_RENDER_STATE_ROW_DATA_DIRTY: i32 = 1;
+    const GHOSTTY_RENDER_STATE_ROW_DATA_RAW: i32 = 2;
     const GHOSTTY_RENDER_STATE_ROW_DATA_CELLS: i32 = 3;
+    const GHOSTTY_RENDER_STATE_ROW_OPTION_DIRTY: i32 = 0;
+    const GHOSTTY_RENDER_STATE_ROW_CELLS_DATA_RAW: i32 = 1;
     const GHOSTTY_RENDER_STATE_ROW_CELLS_DATA_STYLE: i32 = 2;
     const GHOSTTY_RENDER_STATE_ROW_CELLS_DATA_GRAPHEMES_LEN: i32 = 3;
     const GHOSTTY_RENDER_STATE_ROW_CELLS_DATA_GRAPHEMES_BUF: i32 = 4;
     const GHOSTTY_RENDER_STATE_ROW_CELLS_DATA_BG_COLOR: i32 = 5;
     const GHOSTTY_RENDER_STATE_ROW_CELLS_DATA_FG_COLOR: i32 = 6;
+    const GHOSTTY_ROW_DATA_WRAP: i32 = 1;
+    const GHOSTTY_CELL_DATA_WIDE: i32 = 3;
+    const GHOSTTY_CELL_WIDE_NARROW: c_int = 0;
+    const GHOSTTY_CELL_WIDE_SPACER_TAIL: c_int = 2;
+    const GHOSTTY_CELL_WIDE_SPACER_HEAD: c_int = 3;
     const GHOSTTY_KEY_ACTION_PRESS: i32 = 1;
     const GHOSTTY_KEY_ACTION_REPEAT: i32 = 2;
+    const GHOSTTY_TERMINAL_DATA_CURSOR_X: i32 = 3;
+    const GHOSTTY_TERMINAL_DATA_CURSOR_Y: i32 = 4;
+    const GHOSTTY_TERMINAL_DATA_SCROLLBAR: i32 = 9;
     const GHOSTTY_TERMINAL_DATA_MOUSE_TRACKING: i32 = 11;
     const GHOSTTY_MOUSE_ENCODER_OPT_SIZE: i32 = 2;
     const GHOSTTY_MOUSE_ENCODER_OPT_ANY_BUTTON_PRESSED: i32 = 3;
@@ -666,12 +1355,51 @@ mod imp {
     const DEFAULT_CELL_WIDTH_PX: u32 = 8;
     const DEFAULT_CELL_HEIGHT_PX: u32 = 16;
     const XTVERSION: &[u8] = b"otto-canvas libghostty-vt";
+    const MAX_SEARCH_MATCHES: usize = 10_000;
+    const REPLAY_FRAME: Duration = Duration::from_millis(16);
+    /// Longest pause between replayed events, however slow the speed.
+    const REPLAY_MAX_DELAY: Duration = Duration::from_secs(60 * 60);
+    /// DEC private modes are passed to `ghostty_terminal_mode_get` as their
+    /// number; ANSI modes would set the high bit.
+    const GHOSTTY_MODE_SYNCHRONIZED_OUTPUT: u16 = 2026;
+    const FRAME_INTERVAL: Duration = Duration::from_millis(16);
+    const READ_CHUNK: usize = 64 * 1024;
+    /// Bytes read from one session per wake before the others get a turn.
+    const READ_BUDGET: usize = 256 * 1024;
+    /// Output read since the client last fetched a frame before the
+    /// session's PTY stops being polled. The child then blocks on write
+    /// once the kernel buffer fills.
+    const BACKPRESSURE_BYTES: usize = 4 * 1024 * 1024;
+    /// Longest pause for a session nobody fetches frames for, so output of
+    /// hidden terminals keeps moving.
+    const BACKPRESSURE_MAX_PAUSE: Duration = Duration::from_millis(250);
+    /// Same limit Ghostty applies before drawing a synchronized update that
+    /// never ended.
+    const SYNCHRONIZED_OUTPUT_TIMEOUT: Duration = Duration::from_secs(1);
+    const GHOSTTY_MODE_BRACKETED_PASTE: u16 = 2004;
+    /// Alternate screen modes; lines scrolled off it are not kept.
+    const GHOSTTY_MODES_ALTERNATE_SCREEN: [u16; 3] = [47, 1047, 1049];
+    /// Pastes are written in chunks so the terminal lock is not held while
+    /// the application drains its input.
+    const PASTE_CHUNK: usize = 4 * 1024;
+    /// A paste fails once the PTY has accepted nothing for this long.
+    const PASTE_STALL_TIMEOUT: Duration = Duration::from_secs(5);
+    /// How long a paste waits for the PTY to take input while holding the
+    /// terminal lock, which the reactor needs to read output.
+    const PASTE_POLL_TIMEOUT_MS: c_int = 10;
+    /// How often the reactor checks which process is in the foreground of
+    /// sessions that printed something since the last check.
+    const FOREGROUND_POLL_INTERVAL: Duration = Duration::from_millis(500);
+    /// How often it checks every session, for changes that print nothing.
+    const FOREGROUND_IDLE_POLL_INTERVAL: Duration = Duration::from_secs(10);
 
     type GhosttyResult = i32;
     type GhosttyTerminal = *mut c_void;
     type GhosttyRenderState = *mut c_void;
     type GhosttyRenderStateRowIterator = *mut c_void;
     type GhosttyRenderStateRowCells = *mut c_void;
+    type GhosttyRow = u64;
+    type GhosttyCell = u64;
     type GhosttyKeyEncoder = *mut c_void;
     type GhosttyKeyEvent = *mut c_void;
     type GhosttyMouseEncoder = *mut c_void;
@@ -744,6 +1472,14 @@ mod imp {
         value: GhosttyTerminalScrollViewportValue,
     }
 
+    #[repr(C)]
+    #[derive(Clone, Copy, Default)]
+    struct GhosttyTerminalScrollbar {
+        total: u64,
+        offset: u64,
+        len: u64,
+    }
+
     #[repr(C)]
     struct GhosttyMousePosition {
         x: f32,
@@ -823,6 +1559,11 @@ mod imp {
             out: *mut c_void,
         ) -> GhosttyResult;
         fn ghostty_terminal_vt_write(terminal: GhosttyTerminal, data: *const u8, len: usize);
+        fn ghostty_terminal_mode_get(
+            terminal: GhosttyTerminal,
+            mode: u16,
+            value: *mut bool,
+        ) -> GhosttyResult;
         fn ghostty_terminal_scroll_viewport(
             terminal: GhosttyTerminal,
             behavior: GhosttyTerminalScrollViewport,
@@ -841,10 +1582,15 @@ mod imp {
             data: c_int,
             out: *mut c_void,
         ) -> GhosttyResult;
-        fn ghostty_render_state_colors_get(
+        fn ghostty_render_state_set(
             state: GhosttyRenderState,
-            out_colors: *mut GhosttyRenderStateColors,
-        ) -> GhosttyResult;
+            option: c_int,
+            value: *const c_void,
+        ) -> GhosttyResult;
+        fn ghostty_render_state_colors_get(
+            state: GhosttyRenderState,
+            out_colors: *mut GhosttyRenderStateColors,
+        ) -> GhosttyResult;
         fn ghostty_render_state_row_iterator_new(
             allocator: *const GhosttyAllocator,
             out_iterator: *mut GhosttyRenderStateRowIterator,
@@ -856,6 +1602,11 @@ mod imp {
             data: c_int,
             out: *mut c_void,
         ) -> GhosttyResult;
+        fn ghostty_render_state_row_set(
+            iterator: GhosttyRenderStateRowIterator,
+            option: c_int,
+            value: *const c_void,
+        ) -> GhosttyResult;
         fn ghostty_render_state_row_cells_new(
             allocator: *const GhosttyAllocator,
             out_cells: *mut GhosttyRenderStateRowCells,
@@ -867,6 +1618,8 @@ mod imp {
             data: c_int,
             out: *mut c_void,
         ) -> GhosttyResult;
+        fn ghostty_row_get(row: GhosttyRow, data: c_int, out: *mut c_void) -> GhosttyResult;
+        fn ghostty_cell_get(cell: GhosttyCell, data: c_int, out: *mut c_void) -> GhosttyResult;
         fn ghostty_key_encoder_new(
             allocator: *const GhosttyAllocator,
             encoder: *mut GhosttyKeyEncoder,
@@ -956,6 +1709,7 @@ mod imp {
         command: Option<&str>,
         cols: Option<u16>,
         rows: Option<u16>,
+        env: &[(String, String)],
     ) -> Result<(), String> {
         ghostty_vt_create_session_in_map(
             app_handle,
@@ -966,6 +1720,7 @@ mod imp {
             command,
             cols.unwrap_or(DEFAULT_COLS).max(1),
             rows.unwrap_or(DEFAULT_ROWS).max(1),
+            env,
         )
     }
 
@@ -995,6 +1750,16 @@ mod imp {
         ghostty_vt_send_text_in_map(&manager.inner, session_id, text)
     }
 
+    pub fn ghostty_vt_paste(
+        manager: &GhosttyVtManager,
+        session_id: &str,
+        text: &str,
+        allow_multiline: bool,
+    ) -> Result<GhosttyVtPasteResult, String> {
+        let session = lookup_session(&manager.inner, session_id)?;
+        session.paste(text, allow_multiline)
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub fn ghostty_vt_input_key(
         manager: &GhosttyVtManager,
@@ -1054,6 +1819,126 @@ mod imp {
         ghostty_vt_snapshot_session_in_map(&manager.inner, session_id)
     }
 
+    pub fn ghostty_vt_snapshot_delta(
+        manager: &GhosttyVtManager,
+        session_id: &str,
+        since_seq: Option<u64>,
+    ) -> Result<GhosttyVtDelta, String> {
+        let session = lookup_session(&manager.inner, session_id)?;
+        session.snapshot_delta(since_seq)
+    }
+
+    pub fn ghostty_vt_command_records(
+        manager: &GhosttyVtManager,
+        session_id: &str,
+    ) -> Result<Vec<ShellCommandRecord>, String> {
+        let session = lookup_session(&manager.inner, session_id)?;
+        session.command_records()
+    }
+
+    pub fn ghostty_vt_jump_to_prompt(
+        manager: &GhosttyVtManager,
+        session_id: &str,
+        direction: GhosttyVtSearchDirection,
+    ) -> Result<Option<ShellCommandRecord>, String> {
+        let session = lookup_session(&manager.inner, session_id)?;
+        session.jump_to_prompt(direction)
+    }
+
+    pub fn ghostty_vt_session_info(
+        manager: &GhosttyVtManager,
+        session_id: &str,
+    ) -> Result<GhosttyVtSessionInfo, String> {
+        let session = lookup_session(&manager.inner, session_id)?;
+        session.refresh_info();
+        session.info()
+    }
+
+    pub fn ghostty_vt_search(
+        manager: &GhosttyVtManager,
+        session_id: &str,
+        query: &str,
+        regex: bool,
+        case_sensitive: bool,
+    ) -> Result<GhosttyVtSearchResult, String> {
+        let session = lookup_session(&manager.inner, session_id)?;
+        session.search(query, regex, case_sensitive)
+    }
+
+    pub fn ghostty_vt_search_navigate(
+        manager: &GhosttyVtManager,
+        session_id: &str,
+        direction: GhosttyVtSearchDirection,
+    ) -> Result<Option<GhosttyVtSearchPosition>, String> {
+        let session = lookup_session(&manager.inner, session_id)?;
+        session.search_navigate(direction)
+    }
+
+    pub fn ghostty_vt_export_scrollback(
+        manager: &GhosttyVtManager,
+        session_id: &str,
+        format: GhosttyVtExportFormat,
+    ) -> Result<(String, u64), String> {
+        let session = lookup_session(&manager.inner, session_id)?;
+        let scrollback = {
+            let mut terminal = session
+                .terminal
+                .lock()
+                .map_err(|_| "Failed to lock libghostty-vt terminal state".to_string())?;
+            collect_scrollback_rows(&mut terminal)?
+        };
+        let rows = scrollback.rows.len() as u64;
+        let content = match format {
+            GhosttyVtExportFormat::Text => export_text(&scrollback.rows),
+            GhosttyVtExportFormat::Ansi => export_ansi(&scrollback.rows),
+            GhosttyVtExportFormat::Html => export_html(&scrollback),
+        };
+        Ok((content, rows))
+    }
+
+    pub fn ghostty_vt_start_recording(
+        manager: &GhosttyVtManager,
+        session_id: &str,
+        path: &Path,
+    ) -> Result<(), String> {
+        let session = lookup_session(&manager.inner, session_id)?;
+        session.start_recording(path)
+    }
+
+    pub fn ghostty_vt_stop_recording(
+        manager: &GhosttyVtManager,
+        session_id: &str,
+    ) -> Result<Option<PathBuf>, String> {
+        let session = lookup_session(&manager.inner, session_id)?;
+        session.stop_recording()
+    }
+
+    pub fn ghostty_vt_create_replay_session(
+        app_handle: &AppHandle,
+        manager: &GhosttyVtManager,
+        session_id: &str,
+        recording: AsciicastRecording,
+        speed: f64,
+        max_idle_secs: Option<f64>,
+    ) -> Result<(), String> {
+        let mut sessions = manager
+            .inner
+            .lock()
+            .map_err(|_| "Failed to lock libghostty-vt session map".to_string())?;
+        if sessions.contains_key(session_id) {
+            return Err(format!("libghostty-vt session {session_id} already exists"));
+        }
+        let session = GhosttyVtSession::spawn_replay(
+            app_handle.clone(),
+            session_id,
+            recording,
+            speed,
+            max_idle_secs,
+        )?;
+        sessions.insert(session_id.to_string(), session);
+        Ok(())
+    }
+
     pub fn ghostty_vt_destroy_session(
         manager: &GhosttyVtManager,
         session_id: &str,
@@ -1070,6 +1955,7 @@ mod imp {
         command: Option<&str>,
         cols: u16,
         rows: u16,
+        env: &[(String, String)],
     ) -> Result<(), String> {
         let mut sessions = sessions
             .lock()
@@ -1086,6 +1972,7 @@ mod imp {
             command,
             cols,
             rows,
+            env,
         )?;
         sessions.insert(session_id.to_string(), session);
         Ok(())
@@ -1189,6 +2076,21 @@ mod imp {
         Ok(())
     }
 
+    pub fn ghostty_vt_session_pids_in_map(
+        sessions: &Arc<Mutex<HashMap<String, Arc<SessionHandle>>>>,
+    ) -> Vec<(String, u32)> {
+        let Ok(sessions) = sessions.lock() else {
+            return Vec::new();
+        };
+        sessions
+            .iter()
+            .filter(|(_, session)| {
+                session.child_pid > 0 && session.process_alive.load(Ordering::SeqCst)
+            })
+            .map(|(session_id, session)| (session_id.clone(), session.child_pid as u32))
+            .collect()
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub fn ghostty_vt_mouse_button_in_map(
         sessions: &Arc<Mutex<HashMap<String, Arc<SessionHandle>>>>,
@@ -1251,17 +2153,45 @@ mod imp {
 
     pub(super) type SessionHandle = GhosttyVtSession;
 
+    /// Sequence bookkeeping for `snapshot_delta`. `row_seqs[i]` is the
+    /// sequence at which viewport row `i` last changed; clients that are
+    /// older than `reset_seq` (the last geometry change) get a full frame.
+    #[derive(Default)]
+    struct DeltaState {
+        seq: u64,
+        reset_seq: u64,
+        cols: u16,
+        row_seqs: Vec<u64>,
+    }
+
     pub(super) struct GhosttyVtSession {
         app_handle: AppHandle,
         session_id: String,
         terminal: Mutex<TerminalState>,
         geometry: Arc<Mutex<SessionGeometry>>,
         scroll_remainder: Mutex<f64>,
+        search: Mutex<Option<SearchState>>,
+        delta: Mutex<DeltaState>,
+        info: Mutex<GhosttyVtSessionInfo>,
+        recording: Mutex<Option<AsciicastWriter>>,
         #[allow(dead_code)]
         callbacks: Box<CallbackContext>,
-        reader_thread: Mutex<Option<JoinHandle<()>>>,
+        worker_thread: Mutex<Option<JoinHandle<()>>>,
+        playback_cancelled: AtomicBool,
+        /// Set when a client fetches a frame; read by the reactor for
+        /// backpressure.
+        frame_consumed: AtomicBool,
+        /// The reactor has stopped polling this session's PTY.
+        throttled: AtomicBool,
         pty_fd: AtomicI32,
         child_pid: libc::pid_t,
+        /// Started with a command instead of an interactive shell.
+        runs_command: bool,
+        /// Where the child started, for resolving relative paths printed
+        /// before the shell reports its working directory.
+        initial_cwd: Option<PathBuf>,
+        /// Held for the duration of a paste so two never interleave.
+        paste: Mutex<()>,
         process_alive: AtomicBool,
         child_reaped: AtomicBool,
         exit_status: AtomicI32,
@@ -1273,12 +2203,39 @@ mod imp {
         session_id: String,
     }
 
+    #[derive(Clone, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct GhosttyVtCommandEvent {
+        session_id: String,
+        finished: bool,
+        record: ShellCommandRecord,
+    }
+
+    #[derive(Clone, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct GhosttyVtCwdEvent {
+        session_id: String,
+        cwd: String,
+    }
+
+    #[derive(Clone, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct GhosttyVtInfoEvent {
+        session_id: String,
+        info: GhosttyVtSessionInfo,
+    }
+
     #[derive(Clone, Serialize)]
     #[serde(rename_all = "camelCase")]
     struct GhosttyVtCloseEvent {
         block_id: String,
     }
 
+    struct SearchState {
+        matches: Vec<GhosttyVtSearchMatch>,
+        current: Option<usize>,
+    }
+
     struct TerminalState {
         handle: GhosttyTerminal,
         render_state: GhosttyRenderState,
@@ -1286,6 +2243,13 @@ mod imp {
         key_event: GhosttyKeyEvent,
         mouse_encoder: GhosttyMouseEncoder,
         mouse_event: GhosttyMouseEvent,
+        osc_scanner: OscScanner,
+        commands: CommandTracker,
+        hyperlinks: HyperlinkTracker,
+        /// Lines scrolled into the scrollback since the session started.
+        lines_pushed: u64,
+        /// See `row_origin`.
+        trimmed_rows: u64,
     }
 
     unsafe impl Send for TerminalState {}
@@ -1399,6 +2363,125 @@ mod imp {
         }
     }
 
+    fn create_terminal_state(
+        callbacks: &CallbackContext,
+        cols: u16,
+        rows: u16,
+    ) -> Result<TerminalState, String> {
+        // Built up in place so anything created before a failure is freed.
+        let mut state = TerminalState {
+            handle: ptr::null_mut(),
+            render_state: ptr::null_mut(),
+            key_encoder: ptr::null_mut(),
+            key_event: ptr::null_mut(),
+            mouse_encoder: ptr::null_mut(),
+            mouse_event: ptr::null_mut(),
+            osc_scanner: OscScanner::default(),
+            commands: CommandTracker::default(),
+            hyperlinks: HyperlinkTracker::default(),
+            lines_pushed: 0,
+            trimmed_rows: 0,
+        };
+        let options = GhosttyTerminalOptions {
+            cols,
+            rows,
+            max_scrollback: 5_000,
+        };
+        check_result(
+            unsafe { ghostty_terminal_new(ptr::null(), &mut state.handle, options) },
+            "ghostty_terminal_new",
+        )?;
+        let terminal = state.handle;
+
+        let userdata = (callbacks as *const CallbackContext)
+            .cast_mut()
+            .cast::<c_void>();
+        check_result(
+            unsafe {
+                ghostty_terminal_set(
+                    terminal,
+                    GHOSTTY_TERMINAL_OPT_USERDATA,
+                    userdata.cast::<c_void>(),
+                )
+            },
+            "ghostty_terminal_set(USERDATA)",
+        )?;
+        check_result(
+            unsafe {
+                ghostty_terminal_set(
+                    terminal,
+                    GHOSTTY_TERMINAL_OPT_WRITE_PTY,
+                    (write_pty_callback as *const ()).cast(),
+                )
+            },
+            "ghostty_terminal_set(WRITE_PTY)",
+        )?;
+        check_result(
+            unsafe {
+                ghostty_terminal_set(
+                    terminal,
+                    GHOSTTY_TERMINAL_OPT_SIZE,
+                    (size_callback as *const ()).cast(),
+                )
+            },
+            "ghostty_terminal_set(SIZE)",
+        )?;
+        check_result(
+            unsafe {
+                ghostty_terminal_set(
+                    terminal,
+                    GHOSTTY_TERMINAL_OPT_DEVICE_ATTRIBUTES,
+                    (device_attributes_callback as *const ()).cast(),
+                )
+            },
+            "ghostty_terminal_set(DEVICE_ATTRIBUTES)",
+        )?;
+        check_result(
+            unsafe {
+                ghostty_terminal_set(
+                    terminal,
+                    GHOSTTY_TERMINAL_OPT_XTVERSION,
+                    (xtversion_callback as *const ()).cast(),
+                )
+            },
+            "ghostty_terminal_set(XTVERSION)",
+        )?;
+        check_result(
+            unsafe {
+                ghostty_terminal_resize(
+                    terminal,
+                    cols,
+                    rows,
+                    DEFAULT_CELL_WIDTH_PX,
+                    DEFAULT_CELL_HEIGHT_PX,
+                )
+            },
+            "ghostty_terminal_resize",
+        )?;
+
+        check_result(
+            unsafe { ghostty_render_state_new(ptr::null(), &mut state.render_state) },
+            "ghostty_render_state_new",
+        )?;
+        check_result(
+            unsafe { ghostty_key_encoder_new(ptr::null(), &mut state.key_encoder) },
+            "ghostty_key_encoder_new",
+        )?;
+        check_result(
+            unsafe { ghostty_key_event_new(ptr::null(), &mut state.key_event) },
+            "ghostty_key_event_new",
+        )?;
+        check_result(
+            unsafe { ghostty_mouse_encoder_new(ptr::null(), &mut state.mouse_encoder) },
+            "ghostty_mouse_encoder_new",
+        )?;
+        check_result(
+            unsafe { ghostty_mouse_event_new(ptr::null(), &mut state.mouse_event) },
+            "ghostty_mouse_event_new",
+        )?;
+        Ok(state)
+    }
+
     impl GhosttyVtSession {
         fn spawn(
             app_handle: AppHandle,
@@ -1407,6 +2490,7 @@ mod imp {
             command: Option<&str>,
             cols: u16,
             rows: u16,
+            env: &[(String, String)],
         ) -> Result<Arc<Self>, String> {
             let geometry = Arc::new(Mutex::new(SessionGeometry {
                 cols,
@@ -1428,192 +2512,235 @@ mod imp {
                     cell_width_px: DEFAULT_CELL_WIDTH_PX,
                     cell_height_px: DEFAULT_CELL_HEIGHT_PX,
                 },
+                env,
             )?;
 
             let mut callbacks = callbacks;
             callbacks.pty_fd = pty_fd;
 
-            let mut terminal = ptr::null_mut();
-            let options = GhosttyTerminalOptions {
+            let terminal = create_terminal_state(&callbacks, cols, rows)?;
+            let mut session = Self::from_parts(
+                app_handle,
+                session_id,
+                terminal,
+                geometry,
+                callbacks,
+                pty_fd,
+                child_pid,
+                command.is_some(),
+            );
+            session.initial_cwd = cwd
+                .map(PathBuf::from)
+                .or_else(|| std::env::current_dir().ok());
+            let session = Arc::new(session);
+            if let Err(error) = pty_reactor().map(|reactor| reactor.register(Arc::clone(&session)))
+            {
+                let _ = session.stop();
+                return Err(error);
+            }
+
+            Ok(session)
+        }
+
+        /// A session with no PTY that plays an asciicast recording back into
+        /// its terminal. `speed` scales the recorded delays.
+        fn spawn_replay(
+            app_handle: AppHandle,
+            session_id: &str,
+            recording: AsciicastRecording,
+            speed: f64,
+            max_idle_secs: Option<f64>,
+        ) -> Result<Arc<Self>, String> {
+            let (cols, rows) = (recording.cols, recording.rows);
+            let geometry = Arc::new(Mutex::new(SessionGeometry {
                 cols,
                 rows,
-                max_scrollback: 5_000,
-            };
-            check_result(
-                unsafe { ghostty_terminal_new(ptr::null(), &mut terminal, options) },
-                "ghostty_terminal_new",
-            )?;
+                cell_width_px: DEFAULT_CELL_WIDTH_PX,
+                cell_height_px: DEFAULT_CELL_HEIGHT_PX,
+            }));
+            let callbacks = Box::new(CallbackContext {
+                pty_fd: -1,
+                geometry: Arc::clone(&geometry),
+            });
+            let terminal = create_terminal_state(&callbacks, cols, rows)?;
+            let session = Arc::new(Self::from_parts(
+                app_handle, session_id, terminal, geometry, callbacks, -1, 0, false,
+            ));
 
-            let userdata = (&*callbacks as *const CallbackContext)
-                .cast_mut()
-                .cast::<c_void>();
-            check_result(
-                unsafe {
-                    ghostty_terminal_set(
-                        terminal,
-                        GHOSTTY_TERMINAL_OPT_USERDATA,
-                        userdata.cast::<c_void>(),
-                    )
-                },
-                "ghostty_terminal_set(USERDATA)",
-            )?;
-            check_result(
-                unsafe {
-                    ghostty_terminal_set(
-                        terminal,
-                        GHOSTTY_TERMINAL_OPT_WRITE_PTY,
-                        (write_pty_callback as *const ()).cast(),
-                    )
-                },
-                "ghostty_terminal_set(WRITE_PTY)",
-            )?;
-            check_result(
-                unsafe {
-                    ghostty_terminal_set(
-                        terminal,
-                        GHOSTTY_TERMINAL_OPT_SIZE,
-                        (size_callback as *const ()).cast(),
-                    )
-                },
-                "ghostty_terminal_set(SIZE)",
-            )?;
-            check_result(
-                unsafe {
-                    ghostty_terminal_set(
-                        terminal,
-                        GHOSTTY_TERMINAL_OPT_DEVICE_ATTRIBUTES,
-                        (device_attributes_callback as *const ()).cast(),
-                    )
-                },
-                "ghostty_terminal_set(DEVICE_ATTRIBUTES)",
-            )?;
-            check_result(
-                unsafe {
-                    ghostty_terminal_set(
-                        terminal,
-                        GHOSTTY_TERMINAL_OPT_XTVERSION,
-                        (xtversion_callback as *const ()).cast(),
-                    )
-                },
-                "ghostty_terminal_set(XTVERSION)",
-            )?;
-            check_result(
-                unsafe {
-                    ghostty_terminal_resize(
-                        terminal,
-                        cols,
-                        rows,
-                        DEFAULT_CELL_WIDTH_PX,
-                        DEFAULT_CELL_HEIGHT_PX,
-                    )
-                },
-                "ghostty_terminal_resize",
-            )?;
+            let playback_session = Arc::clone(&session);
+            let playback_thread = thread::Builder::new()
+                .name(format!("ghostty-vt-replay-{session_id}"))
+                .spawn(move || playback_session.play(recording, speed, max_idle_secs))
+                .map_err(|error| format!("Failed to spawn libghostty-vt replay thread: {error}"))?;
 
-            let mut render_state = ptr::null_mut();
-            check_result(
-                unsafe { ghostty_render_state_new(ptr::null(), &mut render_state) },
-                "ghostty_render_state_new",
-            )?;
-            let mut key_encoder = ptr::null_mut();
-            check_result(
-                unsafe { ghostty_key_encoder_new(ptr::null(), &mut key_encoder) },
-                "ghostty_key_encoder_new",
-            )?;
-            let mut key_event = ptr::null_mut();
-            check_result(
-                unsafe { ghostty_key_event_new(ptr::null(), &mut key_event) },
-                "ghostty_key_event_new",
-            )?;
-            let mut mouse_encoder = ptr::null_mut();
-            check_result(
-                unsafe { ghostty_mouse_encoder_new(ptr::null(), &mut mouse_encoder) },
-                "ghostty_mouse_encoder_new",
-            )?;
-            let mut mouse_event = ptr::null_mut();
-            check_result(
-                unsafe { ghostty_mouse_event_new(ptr::null(), &mut mouse_event) },
-                "ghostty_mouse_event_new",
-            )?;
+            session
+                .worker_thread
+                .lock()
+                .map_err(|_| "Failed to store libghostty-vt replay thread".to_string())?
+                .replace(playback_thread);
 
-            let session = Self {
+            Ok(session)
+        }
+
+        #[allow(clippy::too_many_arguments)]
+        fn from_parts(
+            app_handle: AppHandle,
+            session_id: &str,
+            terminal: TerminalState,
+            geometry: Arc<Mutex<SessionGeometry>>,
+            callbacks: Box<CallbackContext>,
+            pty_fd: RawFd,
+            child_pid: libc::pid_t,
+            runs_command: bool,
+        ) -> Self {
+            Self {
                 app_handle,
                 session_id: session_id.to_string(),
-                terminal: Mutex::new(TerminalState {
-                    handle: terminal,
-                    render_state,
-                    key_encoder,
-                    key_event,
-                    mouse_encoder,
-                    mouse_event,
-                }),
+                terminal: Mutex::new(terminal),
                 geometry,
                 scroll_remainder: Mutex::new(0.0),
+                search: Mutex::new(None),
+                delta: Mutex::new(DeltaState::default()),
+                info: Mutex::new(GhosttyVtSessionInfo::default()),
+                recording: Mutex::new(None),
                 callbacks,
-                reader_thread: Mutex::new(None),
+                worker_thread: Mutex::new(None),
+                playback_cancelled: AtomicBool::new(false),
+                frame_consumed: AtomicBool::new(false),
+                throttled: AtomicBool::new(false),
                 pty_fd: AtomicI32::new(pty_fd),
                 child_pid,
+                runs_command,
+                initial_cwd: None,
+                paste: Mutex::new(()),
                 process_alive: AtomicBool::new(true),
-                child_reaped: AtomicBool::new(false),
+                // Replay sessions have no child to reap.
+                child_reaped: AtomicBool::new(child_pid <= 0),
                 exit_status: AtomicI32::new(EXIT_STATUS_RUNNING),
-            };
-
-            let session = Arc::new(session);
-            let reader_session = Arc::clone(&session);
-            let reader_thread = thread::Builder::new()
-                .name(format!("ghostty-vt-{session_id}"))
-                .spawn(move || reader_session.read_loop())
-                .map_err(|error| format!("Failed to spawn libghostty-vt reader thread: {error}"))?;
-
-            session
-                .reader_thread
-                .lock()
-                .map_err(|_| "Failed to store libghostty-vt reader thread".to_string())?
-                .replace(reader_thread);
-
-            Ok(session)
+            }
         }
 
-        fn read_loop(self: Arc<Self>) {
-            let mut buffer = [0_u8; 4096];
+        fn play(self: Arc<Self>, recording: AsciicastRecording, speed: f64, max_idle_secs: Option<f64>) {
+            let idle_limit = max_idle_secs.or(recording.idle_time_limit);
+            let mut previous = 0.0;
+            let mut last_update = Instant::now();
 
-            loop {
-                let fd = self.pty_fd.load(Ordering::SeqCst);
-                if fd < 0 {
-                    break;
+            for (seconds, event) in recording.events {
+                let mut gap = (seconds - previous).max(0.0);
+                previous = seconds;
+                if let Some(limit) = idle_limit {
+                    gap = gap.min(limit);
                 }
-
-                let read = unsafe { libc::read(fd, buffer.as_mut_ptr().cast(), buffer.len()) };
-                if read > 0 {
-                    let Ok(terminal) = self.terminal.lock() else {
-                        break;
-                    };
-                    unsafe {
-                        ghostty_terminal_vt_write(terminal.handle, buffer.as_ptr(), read as usize);
-                    }
+                let delay = Duration::try_from_secs_f64(gap / speed)
+                    .unwrap_or(REPLAY_MAX_DELAY)
+                    .min(REPLAY_MAX_DELAY);
+                // Coalesce bursts of events into one update per frame.
+                if delay >= REPLAY_FRAME || last_update.elapsed() >= REPLAY_FRAME {
                     self.emit_updated();
-                    continue;
+                    last_update = Instant::now();
+                }
+                if !self.wait_for_playback(delay) {
+                    return;
                 }
 
-                if read == 0 {
-                    break;
+                match event {
+                    AsciicastEvent::Output(data) => {
+                        let Ok(mut terminal) = self.terminal.lock() else {
+                            return;
+                        };
+                        let changes = write_terminal_output(&mut terminal, data.as_bytes());
+                        drop(terminal);
+                        self.apply_output_changes(changes);
+                    }
+                    AsciicastEvent::Resize { cols, rows } => {
+                        let (cell_width_px, cell_height_px) = match self.geometry.lock() {
+                            Ok(geometry) => (geometry.cell_width_px, geometry.cell_height_px),
+                            Err(_) => return,
+                        };
+                        let _ = self.resize(cols.max(1), rows.max(1), cell_width_px, cell_height_px);
+                    }
                 }
+            }
 
-                let error = io::Error::last_os_error();
-                match error.raw_os_error() {
-                    Some(code) if code == libc::EINTR => continue,
-                    Some(code) if code == libc::EAGAIN || code == libc::EWOULDBLOCK => {
-                        thread::sleep(std::time::Duration::from_millis(16));
-                        continue;
-                    }
-                    Some(code) if code == libc::EIO || code == libc::EBADF => break,
-                    _ => break,
+            self.process_alive.store(false, Ordering::SeqCst);
+            self.exit_status.store(0, Ordering::SeqCst);
+            self.emit_updated();
+        }
+
+        /// Sleeps for `delay` unless the session is stopped first.
+        fn wait_for_playback(&self, delay: Duration) -> bool {
+            let deadline = Instant::now() + delay;
+            loop {
+                if self.playback_cancelled.load(Ordering::SeqCst) {
+                    return false;
+                }
+                let now = Instant::now();
+                if now >= deadline {
+                    return true;
                 }
+                thread::sleep((deadline - now).min(Duration::from_millis(50)));
             }
+        }
 
+        fn start_recording(&self, path: &Path) -> Result<(), String> {
+            let geometry = *self
+                .geometry
+                .lock()
+                .map_err(|_| "Failed to lock libghostty-vt session geometry".to_string())?;
+            let mut recording = self
+                .recording
+                .lock()
+                .map_err(|_| "Failed to lock libghostty-vt recording".to_string())?;
+            if let Some(active) = recording.as_ref() {
+                return Err(format!(
+                    "libghostty-vt session {} is already recording to {}",
+                    self.session_id,
+                    active.path().display()
+                ));
+            }
+            recording.replace(AsciicastWriter::create(
+                path,
+                geometry.cols,
+                geometry.rows,
+                Some(&self.session_id),
+            )?);
+            Ok(())
+        }
+
+        fn stop_recording(&self) -> Result<Option<PathBuf>, String> {
+            let writer = self
+                .recording
+                .lock()
+                .map_err(|_| "Failed to lock libghostty-vt recording".to_string())?
+                .take();
+            writer.map(AsciicastWriter::finish).transpose()
+        }
+
+        /// Applies `write` to the active recording, dropping the recording if
+        /// it fails so a full disk does not fail every read.
+        fn record(&self, write: impl FnOnce(&mut AsciicastWriter) -> Result<(), String>) {
+            let Ok(mut recording) = self.recording.lock() else {
+                return;
+            };
+            let Some(writer) = recording.as_mut() else {
+                return;
+            };
+            if let Err(error) = write(writer) {
+                eprintln!("[canvas] stopped recording {}: {}", self.session_id, error);
+                recording.take();
+            }
+        }
+
+        /// Runs once the PTY reports EOF: reaps the child and tells the
+        /// canvas the block closed.
+        fn finish_output(&self) {
             self.process_alive.store(false, Ordering::SeqCst);
             self.close_pty();
+            self.refresh_info();
             self.reap_child(true);
+            if let Err(error) = self.stop_recording() {
+                eprintln!("[canvas] failed to finish recording {}: {}", self.session_id, error);
+            }
             let _ = self.app_handle.emit(
                 "ghostty-close-block",
                 GhosttyVtCloseEvent {
@@ -1623,6 +2750,144 @@ mod imp {
             self.emit_updated();
         }
 
+        fn apply_output_changes(&self, changes: OutputChanges) {
+            if let Some(title) = changes.title {
+                self.update_info(|info| info.title = Some(title).filter(|title| !title.is_empty()));
+            }
+            for change in changes.commands {
+                let _ = match change {
+                    CommandChange::Started(record) | CommandChange::Finished(record) => {
+                        self.app_handle.emit(
+                            "ghostty-vt-command",
+                            GhosttyVtCommandEvent {
+                                session_id: self.session_id.clone(),
+                                finished: record.finished_at_ms.is_some(),
+                                record,
+                            },
+                        )
+                    }
+                    CommandChange::Cwd(cwd) => self.app_handle.emit(
+                        "ghostty-vt-cwd-changed",
+                        GhosttyVtCwdEvent {
+                            session_id: self.session_id.clone(),
+                            cwd,
+                        },
+                    ),
+                };
+            }
+        }
+
+        fn info(&self) -> Result<GhosttyVtSessionInfo, String> {
+            self.info
+                .lock()
+                .map(|info| info.clone())
+                .map_err(|_| "Failed to lock libghostty-vt session info".to_string())
+        }
+
+        /// Applies `update` and emits `ghostty-vt-info-changed` if anything
+        /// changed.
+        fn update_info(&self, update: impl FnOnce(&mut GhosttyVtSessionInfo)) {
+            let info = {
+                let Ok(mut info) = self.info.lock() else {
+                    return;
+                };
+                let previous = info.clone();
+                update(&mut info);
+                if *info == previous {
+                    return;
+                }
+                info.clone()
+            };
+            let _ = self.app_handle.emit(
+                "ghostty-vt-info-changed",
+                GhosttyVtInfoEvent {
+                    session_id: self.session_id.clone(),
+                    info,
+                },
+            );
+        }
+
+        /// Looks up the foreground process group of the PTY. Nothing
+        /// notifies us when it changes, so the reactor calls this on a timer.
+        fn refresh_info(&self) {
+            let foreground = {
+                // Under the terminal lock so the descriptor is not closed
+                // and reused meanwhile.
+                let _terminal = self.terminal.lock();
+                let fd = self.pty_fd.load(Ordering::SeqCst);
+                let pgid = if fd >= 0 {
+                    unsafe { libc::tcgetpgrp(fd) }
+                } else {
+                    -1
+                };
+                (pgid > 0).then_some(pgid)
+            };
+            let command = foreground
+                .and_then(|pgid| process_command(pgid as u32))
+                .unwrap_or_default();
+            let busy = foreground.is_some_and(|pgid| self.runs_command || pgid != self.child_pid);
+            self.update_info(|info| {
+                info.foreground_pid = foreground.map(|pgid| pgid as u32);
+                info.foreground_name = command.name;
+                info.foreground_argv = command.argv;
+                info.busy = busy;
+            });
+        }
+
+        fn command_records(&self) -> Result<Vec<ShellCommandRecord>, String> {
+            let mut terminal = self
+                .terminal
+                .lock()
+                .map_err(|_| "Failed to lock libghostty-vt terminal state".to_string())?;
+            forget_scrolled_out(&mut terminal);
+            Ok(terminal.commands.records())
+        }
+
+        /// Scrolls the prompt after or before the top of the viewport to
+        /// the top. Returns its command record, or `None` past the ends.
+        fn jump_to_prompt(
+            &self,
+            direction: GhosttyVtSearchDirection,
+        ) -> Result<Option<ShellCommandRecord>, String> {
+            let record = {
+                let mut terminal = self
+                    .terminal
+                    .lock()
+                    .map_err(|_| "Failed to lock libghostty-vt terminal state".to_string())?;
+                let origin = forget_scrolled_out(&mut terminal);
+                let top = origin + terminal_scrollbar(terminal.handle)?.offset;
+                let records = terminal.commands.records();
+                let record = match direction {
+                    GhosttyVtSearchDirection::Next => {
+                        records.into_iter().find(|record| record.prompt_row > top)
+                    }
+                    GhosttyVtSearchDirection::Previous => records
+                        .into_iter()
+                        .rev()
+                        .find(|record| record.prompt_row < top),
+                };
+                let Some(record) = record else {
+                    return Ok(None);
+                };
+                scroll_terminal(terminal.handle, GHOSTTY_SCROLL_VIEWPORT_TOP, 0);
+                scroll_terminal(
+                    terminal.handle,
+                    GHOSTTY_SCROLL_VIEWPORT_DELTA,
+                    record.prompt_row.saturating_sub(origin) as i64,
+                );
+                record
+            };
+            self.emit_updated();
+            Ok(Some(record))
+        }
+
+        fn mark_frame_consumed(&self) {
+            self.frame_consumed.store(true, Ordering::SeqCst);
+            if self.throttled.load(Ordering::SeqCst) {
+                wake_reactor();
+            }
+        }
+
         fn emit_updated(&self) {
             crate::native_terminal::request_redraw(&self.app_handle, &self.session_id);
             let _ = self.app_handle.emit(
@@ -1650,6 +2915,125 @@ mod imp {
             Ok(())
         }
 
+        fn paste(&self, text: &str, allow_multiline: bool) -> Result<GhosttyVtPasteResult, String> {
+            let _paste = self
+                .paste
+                .lock()
+                .map_err(|_| "Failed to lock libghostty-vt paste".to_string())?;
+            let bracketed = {
+                let terminal = self
+                    .terminal
+                    .lock()
+                    .map_err(|_| "Failed to lock libghostty-vt terminal state".to_string())?;
+                terminal_mode(terminal.handle, GHOSTTY_MODE_BRACKETED_PASTE)
+            };
+            let prepared = prepare_paste(text, bracketed);
+            let mut result = GhosttyVtPasteResult {
+                pasted: false,
+                bracketed,
+                stripped_controls: prepared.stripped_controls,
+                bytes_written: 0,
+                warning: None,
+            };
+            if prepared.bytes.is_empty() {
+                return Ok(result);
+            }
+
+            if prepared.multiline && !bracketed && !allow_multiline {
+                self.refresh_info();
+                if !self.info()?.busy {
+                    let lines = prepared.lines;
+                    result.warning = Some(format!(
+                        "The shell does not support bracketed paste, so each of the {lines} lines would run as soon as it is pasted."
+                    ));
+                    return Ok(result);
+                }
+            }
+
+            result.bytes_written = self.write_paced(&prepared.bytes)?;
+            result.pasted = true;
+            Ok(result)
+        }
+
+        /// Writes all of `bytes`, waiting while the PTY's input buffer is
+        /// full where `send_text` would drop the rest.
+        fn write_paced(&self, bytes: &[u8]) -> Result<usize, String> {
+            // Writes and waits go through a duplicate of the PTY descriptor so
+            // the terminal lock, which the reactor needs for every read, is
+            // only held while the duplicate is taken. The duplicate stays
+            // valid even if the session closes its own descriptor meanwhile.
+            let fd = {
+                let _terminal = self
+                    .terminal
+                    .lock()
+                    .map_err(|_| "Failed to lock libghostty-vt terminal state".to_string())?;
+                let fd = self.pty_fd.load(Ordering::SeqCst);
+                if fd < 0 {
+                    return Err(format!(
+                        "libghostty-vt session {} is closed",
+                        self.session_id
+                    ));
+                }
+                let duplicate = unsafe { libc::dup(fd) };
+                if duplicate < 0 {
+                    return Err(format!(
+                        "Failed to duplicate PTY for paste: {}",
+                        io::Error::last_os_error()
+                    ));
+                }
+                unsafe { OwnedFd::from_raw_fd(duplicate) }
+            };
+
+            let mut written = 0;
+            let mut stalled_since: Option<Instant> = None;
+            while written < bytes.len() {
+                if self.pty_fd.load(Ordering::SeqCst) < 0 {
+                    return Err(format!(
+                        "libghostty-vt session {} closed after {} of {} pasted bytes",
+                        self.session_id,
+                        written,
+                        bytes.len()
+                    ));
+                }
+                let chunk = &bytes[written..bytes.len().min(written + PASTE_CHUNK)];
+                let result = unsafe {
+                    libc::write(fd.as_raw_fd(), chunk.as_ptr().cast::<c_void>(), chunk.len())
+                };
+                if result > 0 {
+                    written += result as usize;
+                    stalled_since = None;
+                    continue;
+                }
+
+                // A write that took nothing is treated like a full buffer.
+                if result < 0 {
+                    let error = io::Error::last_os_error();
+                    match error.raw_os_error() {
+                        Some(code) if code == libc::EINTR => continue,
+                        Some(code) if code == libc::EAGAIN || code == libc::EWOULDBLOCK => {}
+                        _ => return Err(format!("Failed to write paste to PTY: {error}")),
+                    }
+                }
+                if stalled_since.get_or_insert_with(Instant::now).elapsed() >= PASTE_STALL_TIMEOUT {
+                    return Err(format!(
+                        "libghostty-vt session {} stopped reading after {} of {} pasted bytes",
+                        self.session_id,
+                        written,
+                        bytes.len()
+                    ));
+                }
+                let mut poll_fd = libc::pollfd {
+                    fd: fd.as_raw_fd(),
+                    events: libc::POLLOUT,
+                    revents: 0,
+                };
+                unsafe {
+                    libc::poll(&mut poll_fd, 1, PASTE_POLL_TIMEOUT_MS);
+                }
+            }
+            Ok(written)
+        }
+
         #[allow(clippy::too_many_arguments)]
         fn input_key(
             &self,
@@ -1755,21 +3139,128 @@ mod imp {
                 .terminal
                 .lock()
                 .map_err(|_| "Failed to lock libghostty-vt terminal state".to_string())?;
-            unsafe {
-                ghostty_terminal_scroll_viewport(
-                    terminal.handle,
-                    GhosttyTerminalScrollViewport {
-                        tag: GHOSTTY_SCROLL_VIEWPORT_DELTA,
-                        value: GhosttyTerminalScrollViewportValue {
-                            delta: delta as isize,
-                        },
-                    },
-                );
-            }
+            scroll_terminal(terminal.handle, GHOSTTY_SCROLL_VIEWPORT_DELTA, delta);
             self.emit_updated();
             Ok(())
         }
 
+        fn search(
+            &self,
+            query: &str,
+            regex: bool,
+            case_sensitive: bool,
+        ) -> Result<GhosttyVtSearchResult, String> {
+            let pattern = if regex {
+                query.to_string()
+            } else {
+                regex::escape(query)
+            };
+            let matcher = regex::RegexBuilder::new(&pattern)
+                .case_insensitive(!case_sensitive)
+                .build()
+                .map_err(|error| format!("Invalid search pattern: {error}"))?;
+
+            let (rows, first_row) = {
+                let mut terminal = self
+                    .terminal
+                    .lock()
+                    .map_err(|_| "Failed to lock libghostty-vt terminal state".to_string())?;
+                (collect_scrollback_rows(&mut terminal)?.rows, row_origin(&mut terminal))
+            };
+            let (matches, truncated) = if query.is_empty() {
+                (Vec::new(), false)
+            } else {
+                search_rows(&rows, first_row, &matcher)
+            };
+
+            *self
+                .search
+                .lock()
+                .map_err(|_| "Failed to lock libghostty-vt search state".to_string())? =
+                Some(SearchState {
+                    matches: matches.clone(),
+                    current: None,
+                });
+
+            Ok(GhosttyVtSearchResult {
+                matches,
+                first_row,
+                total_rows: rows.len() as u64,
+                truncated,
+            })
+        }
+
+        fn search_navigate(
+            &self,
+            direction: GhosttyVtSearchDirection,
+        ) -> Result<Option<GhosttyVtSearchPosition>, String> {
+            let first_row = {
+                let mut terminal = self
+                    .terminal
+                    .lock()
+                    .map_err(|_| "Failed to lock libghostty-vt terminal state".to_string())?;
+                row_origin(&mut terminal)
+            };
+            let mut state = self
+                .search
+                .lock()
+                .map_err(|_| "Failed to lock libghostty-vt search state".to_string())?;
+            let Some(search) = state.as_mut() else {
+                return Ok(None);
+            };
+            // Matches are oldest first, so those trimmed from the scrollback
+            // since the search lead the list.
+            let trimmed = search
+                .matches
+                .partition_point(|search_match| search_match.row < first_row);
+            if trimmed > 0 {
+                search.matches.drain(..trimmed);
+                search.current = search
+                    .current
+                    .and_then(|current| current.checked_sub(trimmed));
+            }
+            if search.matches.is_empty() {
+                return Ok(None);
+            }
+
+            // Without a current match, previous starts from the newest output
+            // and next from the oldest.
+            let total = search.matches.len();
+            let index = match (search.current, direction) {
+                (None, GhosttyVtSearchDirection::Next) => 0,
+                (None, GhosttyVtSearchDirection::Previous) => total - 1,
+                (Some(current), GhosttyVtSearchDirection::Next) => (current + 1) % total,
+                (Some(current), GhosttyVtSearchDirection::Previous) => {
+                    (current + total - 1) % total
+                }
+            };
+            search.current = Some(index);
+            let search_match = search.matches[index].clone();
+            drop(state);
+
+            let rows = self
+                .geometry
+                .lock()
+                .map_err(|_| "Failed to lock libghostty-vt session geometry".to_string())?
+                .rows;
+            {
+                let terminal = self
+                    .terminal
+                    .lock()
+                    .map_err(|_| "Failed to lock libghostty-vt terminal state".to_string())?;
+                let top = (search_match.row - first_row).saturating_sub(u64::from(rows / 2));
+                scroll_terminal(terminal.handle, GHOSTTY_SCROLL_VIEWPORT_TOP, 0);
+                scroll_terminal(terminal.handle, GHOSTTY_SCROLL_VIEWPORT_DELTA, top as i64);
+            }
+            self.emit_updated();
+
+            Ok(Some(GhosttyVtSearchPosition {
+                index,
+                total,
+                search_match,
+            }))
+        }
+
         fn encode_mouse_event(
             &self,
             action: i32,
@@ -2109,6 +3600,8 @@ mod imp {
                 },
                 "ghostty_terminal_resize",
             )?;
+            drop(terminal);
+            self.record(|writer| writer.resize(cols, rows));
 
             let fd = self.pty_fd.load(Ordering::SeqCst);
             if fd >= 0 {
@@ -2138,6 +3631,16 @@ mod imp {
 
             let render_snapshot = render_terminal_snapshot(&mut terminal)?;
             let screen_text = plain_text_from_rows(&render_snapshot.rows_data);
+            let links = viewport_links(
+                &mut terminal,
+                render_snapshot
+                    .row_indexes
+                    .iter()
+                    .copied()
+                    .zip(&render_snapshot.rows_data),
+                self.initial_cwd.as_deref(),
+            );
+            self.mark_frame_consumed();
 
             Ok(GhosttyVtSnapshot {
                 session_id: self.session_id.clone(),
@@ -2151,29 +3654,118 @@ mod imp {
                 cursor: render_snapshot.cursor,
                 process_alive: self.process_alive.load(Ordering::SeqCst),
                 exit_status: self.exit_status(),
+                info: self.info()?,
+                links,
+            })
+        }
+
+        fn snapshot_delta(&self, since_seq: Option<u64>) -> Result<GhosttyVtDelta, String> {
+            let geometry = *self
+                .geometry
+                .lock()
+                .map_err(|_| "Failed to lock libghostty-vt session geometry".to_string())?;
+            let mut terminal = self
+                .terminal
+                .lock()
+                .map_err(|_| "Failed to lock libghostty-vt terminal state".to_string())?;
+            let mut delta = self
+                .delta
+                .lock()
+                .map_err(|_| "Failed to lock libghostty-vt delta state".to_string())?;
+
+            let row_count = usize::from(geometry.rows);
+            if delta.cols != geometry.cols || delta.row_seqs.len() != row_count {
+                delta.seq += 1;
+                delta.reset_seq = delta.seq;
+                delta.cols = geometry.cols;
+                delta.row_seqs = vec![delta.seq; row_count];
+            }
+
+            let full = match since_seq {
+                Some(since) => since < delta.reset_seq || since > delta.seq,
+                None => true,
+            };
+            let since = since_seq.unwrap_or(0);
+            let next_seq = delta.seq + 1;
+            let mut changed = false;
+            let row_seqs = &mut delta.row_seqs;
+            let render_snapshot = render_terminal_rows(
+                &mut terminal,
+                Some(&mut |index, dirty| {
+                    let Some(row_seq) = row_seqs.get_mut(index) else {
+                        return true;
+                    };
+                    if dirty {
+                        *row_seq = next_seq;
+                        changed = true;
+                    }
+                    full || *row_seq > since
+                }),
+            )?;
+            if changed {
+                delta.seq = next_seq;
+            }
+            let links = viewport_links(
+                &mut terminal,
+                render_snapshot
+                    .row_indexes
+                    .iter()
+                    .copied()
+                    .zip(&render_snapshot.rows_data),
+                self.initial_cwd.as_deref(),
+            );
+            self.mark_frame_consumed();
+
+            let changed_rows = render_snapshot
+                .row_indexes
+                .into_iter()
+                .zip(render_snapshot.rows_data)
+                .map(|(index, row)| GhosttyVtRowUpdate {
+                    index: u16::try_from(index).unwrap_or(u16::MAX),
+                    cells: row.cells,
+                })
+                .collect();
+
+            Ok(GhosttyVtDelta {
+                session_id: self.session_id.clone(),
+                seq: delta.seq,
+                full,
+                cols: geometry.cols,
+                rows: geometry.rows,
+                changed_rows,
+                links,
+                default_fg: render_snapshot.default_fg,
+                default_bg: render_snapshot.default_bg,
+                ansi_palette: render_snapshot.ansi_palette,
+                cursor: render_snapshot.cursor,
+                process_alive: self.process_alive.load(Ordering::SeqCst),
+                exit_status: self.exit_status(),
             })
         }
 
         fn stop(&self) -> Result<(), String> {
+            self.playback_cancelled.store(true, Ordering::SeqCst);
             self.terminate_process_group();
             self.close_pty();
+            wake_reactor();
 
             if let Some(handle) = self
-                .reader_thread
+                .worker_thread
                 .lock()
-                .map_err(|_| "Failed to lock libghostty-vt reader thread".to_string())?
+                .map_err(|_| "Failed to lock libghostty-vt worker thread".to_string())?
                 .take()
             {
                 let _ = handle.join();
             }
 
             self.reap_child(true);
+            let finished = self.stop_recording();
             self.emit_updated();
-            Ok(())
+            finished.map(|_| ())
         }
 
         fn terminate_process_group(&self) {
-            if !self.process_alive.swap(false, Ordering::SeqCst) {
+            if !self.process_alive.swap(false, Ordering::SeqCst) || self.child_pid <= 0 {
                 return;
             }
 
@@ -2196,63 +3788,627 @@ mod imp {
                         libc::kill(-self.child_pid, libc::SIGKILL);
                         libc::kill(self.child_pid, libc::SIGKILL);
                     }
-                    return;
+                    return;
+                }
+
+                thread::sleep(Duration::from_millis(50));
+            }
+        }
+
+        fn close_pty(&self) {
+            // Closed under the terminal lock, which the reactor holds while
+            // reading, so it never reads a descriptor number that was reused.
+            let _terminal = self.terminal.lock();
+            let fd = self.pty_fd.swap(-1, Ordering::SeqCst);
+            if fd >= 0 {
+                unsafe {
+                    libc::close(fd);
+                }
+            }
+        }
+
+        fn exit_status(&self) -> Option<i32> {
+            let exit_status = self.exit_status.load(Ordering::SeqCst);
+            (exit_status != EXIT_STATUS_RUNNING).then_some(exit_status)
+        }
+
+        fn record_child_exit(&self, status: c_int) {
+            let exit_status = if libc::WIFEXITED(status) {
+                libc::WEXITSTATUS(status)
+            } else if libc::WIFSIGNALED(status) {
+                128 + libc::WTERMSIG(status)
+            } else {
+                0
+            };
+            self.exit_status.store(exit_status, Ordering::SeqCst);
+            self.child_reaped.store(true, Ordering::SeqCst);
+        }
+
+        fn reap_child(&self, blocking: bool) {
+            if self.child_reaped.load(Ordering::SeqCst) {
+                return;
+            }
+
+            let mut status = 0;
+            let wait_flags = if blocking { 0 } else { libc::WNOHANG };
+            let wait_result = unsafe { libc::waitpid(self.child_pid, &mut status, wait_flags) };
+            if wait_result != self.child_pid {
+                return;
+            }
+
+            self.record_child_exit(status);
+        }
+    }
+
+    impl Drop for GhosttyVtSession {
+        fn drop(&mut self) {
+            self.close_pty();
+        }
+    }
+
+    fn terminal_mode(handle: GhosttyTerminal, mode: u16) -> bool {
+        let mut enabled = false;
+        let result = unsafe { ghostty_terminal_mode_get(handle, mode, &mut enabled) };
+        result == GHOSTTY_SUCCESS && enabled
+    }
+
+    fn terminal_get_u16(handle: GhosttyTerminal, data: i32) -> u16 {
+        let mut value = 0_u16;
+        let _ = unsafe { ghostty_terminal_get(handle, data, (&mut value as *mut u16).cast()) };
+        value
+    }
+
+    /// Cursor row counted from the first line the session printed (see
+    /// `row_origin`), and its column.
+    fn cursor_screen_position(terminal: &mut TerminalState) -> (u64, u16) {
+        let y = terminal_get_u16(terminal.handle, GHOSTTY_TERMINAL_DATA_CURSOR_Y);
+        let x = terminal_get_u16(terminal.handle, GHOSTTY_TERMINAL_DATA_CURSOR_X);
+        let active_top = terminal_scrollbar(terminal.handle)
+            .map(|scrollbar| scrollbar.total.saturating_sub(scrollbar.len))
+            .unwrap_or(0);
+        (row_origin(terminal) + active_top + u64::from(y), x)
+    }
+
+    fn unix_time_ms() -> u64 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis() as u64)
+            .unwrap_or(0)
+    }
+
+    /// Shell integration changes and the latest window title seen in a
+    /// chunk of output.
+    #[derive(Default)]
+    struct OutputChanges {
+        commands: Vec<CommandChange>,
+        title: Option<String>,
+    }
+
+    /// Title set by OSC 0 (icon name and title) or OSC 2 (title).
+    fn parse_window_title(payload: &str) -> Option<&str> {
+        payload.strip_prefix("0;").or_else(|| payload.strip_prefix("2;"))
+    }
+
+    /// Feeds PTY output to the terminal. Writing stops after each shell
+    /// integration mark and hyperlink escape so it is recorded at the
+    /// cursor position it was printed at.
+    fn write_terminal_output(terminal: &mut TerminalState, bytes: &[u8]) -> OutputChanges {
+        let mut changes = OutputChanges::default();
+        let mut written = 0;
+        for (end, payload) in terminal.osc_scanner.feed(bytes) {
+            if let Some(title) = parse_window_title(&payload) {
+                changes.title = Some(title.to_string());
+                continue;
+            }
+            let hyperlink = parse_hyperlink(&payload);
+            let mark = parse_shell_mark(&payload);
+            if hyperlink.is_none() && mark.is_none() {
+                continue;
+            }
+            feed_terminal(terminal, &bytes[written..end]);
+            written = end;
+            let (row, col) = cursor_screen_position(terminal);
+            if let Some(hyperlink) = hyperlink {
+                terminal.hyperlinks.apply(hyperlink, row, col);
+            }
+            if let Some(mark) = mark {
+                if mark == ShellMark::PromptStart {
+                    terminal.hyperlinks.forget_from(row);
+                }
+                if let Some(change) = terminal.commands.apply(mark, row, col, unix_time_ms()) {
+                    changes.commands.push(change);
+                }
+            }
+        }
+        feed_terminal(terminal, &bytes[written..]);
+        changes
+    }
+
+    /// Writes to the terminal a line at a time to count the lines that
+    /// scroll into the scrollback: a line feed that leaves the cursor on the
+    /// bottom row of the primary screen pushes the top row up.
+    /// Writes a chunk of output in one call and counts the rows it scrolled
+    /// into the scrollback from how much the scrollbar total grew. Once the
+    /// scrollback is full, dropping its oldest page shrinks the total
+    /// instead; the chunk's line feeds stand in for the count then.
+    fn feed_terminal(terminal: &mut TerminalState, bytes: &[u8]) {
+        let primary_screen = |terminal: &TerminalState| {
+            !GHOSTTY_MODES_ALTERNATE_SCREEN
+                .iter()
+                .any(|mode| terminal_mode(terminal.handle, *mode))
+        };
+        let before = terminal_scrollbar(terminal.handle)
+            .ok()
+            .filter(|_| primary_screen(terminal));
+        unsafe { ghostty_terminal_vt_write(terminal.handle, bytes.as_ptr(), bytes.len()) };
+        let Some(before) = before.filter(|_| primary_screen(terminal)) else {
+            return;
+        };
+        let Ok(after) = terminal_scrollbar(terminal.handle) else {
+            return;
+        };
+        terminal.lines_pushed += if after.total >= before.total {
+            after.total - before.total
+        } else {
+            bytes.iter().filter(|byte| **byte == b'\n').count() as u64
+        };
+    }
+
+    /// Lines trimmed off the top of the scrollback so far. Rows handed to
+    /// clients count from the first line the session printed, i.e. add this
+    /// to an index into the retained lines, so they stay valid as
+    /// `max_scrollback` trims.
+    fn row_origin(terminal: &mut TerminalState) -> u64 {
+        let retained = terminal_scrollbar(terminal.handle)
+            .map(|scrollbar| scrollbar.total.saturating_sub(scrollbar.len))
+            .unwrap_or(0);
+        terminal.trimmed_rows = terminal
+            .trimmed_rows
+            .max(terminal.lines_pushed.saturating_sub(retained));
+        terminal.trimmed_rows
+    }
+
+    /// Drops the command records and hyperlinks that were trimmed off the
+    /// scrollback, returning the current `row_origin`.
+    fn forget_scrolled_out(terminal: &mut TerminalState) -> u64 {
+        let origin = row_origin(terminal);
+        terminal.commands.forget_before(origin);
+        terminal.hyperlinks.forget_before(origin);
+        origin
+    }
+
+    /// One thread that polls the PTYs of all sessions, feeds their output
+    /// into the terminals and emits `ghostty-vt-updated` at most once per
+    /// frame per session.
+    struct PtyReactor {
+        incoming: Arc<Mutex<Vec<Arc<GhosttyVtSession>>>>,
+        wake_fd: RawFd,
+    }
+
+    static PTY_REACTOR: OnceLock<Result<PtyReactor, String>> = OnceLock::new();
+
+    fn pty_reactor() -> Result<&'static PtyReactor, String> {
+        PTY_REACTOR
+            .get_or_init(PtyReactor::start)
+            .as_ref()
+            .map_err(Clone::clone)
+    }
+
+    /// Lets the reactor notice a closed PTY or a consumed frame without
+    /// starting it for sessions that never registered.
+    fn wake_reactor() {
+        if let Some(Ok(reactor)) = PTY_REACTOR.get() {
+            reactor.wake();
+        }
+    }
+
+    fn set_nonblocking_cloexec(fd: RawFd) -> Result<(), String> {
+        let flags = unsafe { libc::fcntl(fd, libc::F_GETFL) };
+        if flags < 0
+            || unsafe { libc::fcntl(fd, libc::F_SETFL, flags | libc::O_NONBLOCK) } < 0
+            || unsafe { libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC) } < 0
+        {
+            return Err(io::Error::last_os_error().to_string());
+        }
+        Ok(())
+    }
+
+    impl PtyReactor {
+        fn start() -> Result<Self, String> {
+            let mut fds = [-1; 2];
+            if unsafe { libc::pipe(fds.as_mut_ptr()) } < 0 {
+                return Err(format!(
+                    "Failed to create libghostty-vt reactor pipe: {}",
+                    io::Error::last_os_error()
+                ));
+            }
+            let [wake_read, wake_write] = fds;
+            let close_pipe = || unsafe {
+                libc::close(wake_read);
+                libc::close(wake_write);
+            };
+            if let Err(error) =
+                set_nonblocking_cloexec(wake_read).and_then(|_| set_nonblocking_cloexec(wake_write))
+            {
+                close_pipe();
+                return Err(format!("Failed to configure libghostty-vt reactor pipe: {error}"));
+            }
+
+            let incoming = Arc::new(Mutex::new(Vec::new()));
+            let reactor_incoming = Arc::clone(&incoming);
+            if let Err(error) = thread::Builder::new()
+                .name("ghostty-vt-reactor".to_string())
+                .spawn(move || run_reactor(reactor_incoming, wake_read))
+            {
+                close_pipe();
+                return Err(format!("Failed to spawn libghostty-vt reactor thread: {error}"));
+            }
+
+            Ok(Self {
+                incoming,
+                wake_fd: wake_write,
+            })
+        }
+
+        fn register(&self, session: Arc<GhosttyVtSession>) {
+            if let Ok(mut incoming) = self.incoming.lock() {
+                incoming.push(session);
+            }
+            self.wake();
+        }
+
+        fn wake(&self) {
+            // A full pipe already has a wakeup pending.
+            let byte = 1_u8;
+            unsafe {
+                libc::write(self.wake_fd, (&byte as *const u8).cast(), 1);
+            }
+        }
+    }
+
+    struct ReactorEntry {
+        session: Arc<GhosttyVtSession>,
+        /// The descriptor registered; once the session closes its PTY the
+        /// entry is dropped even if the number is reused.
+        fd: RawFd,
+        pending_update: bool,
+        last_update: Option<Instant>,
+        synchronized_since: Option<Instant>,
+        unrendered_bytes: usize,
+        paused_since: Option<Instant>,
+        /// Output arrived since the foreground process was last looked up.
+        output_since_refresh: bool,
+    }
+
+    impl ReactorEntry {
+        fn new(session: Arc<GhosttyVtSession>) -> Self {
+            Self {
+                fd: session.pty_fd.load(Ordering::SeqCst),
+                session,
+                pending_update: false,
+                last_update: None,
+                synchronized_since: None,
+                unrendered_bytes: 0,
+                paused_since: None,
+                output_since_refresh: true,
+            }
+        }
+
+        fn attached(&self) -> bool {
+            self.fd >= 0 && self.session.pty_fd.load(Ordering::SeqCst) == self.fd
+        }
+
+        /// Emits a pending update if the frame interval has passed and the
+        /// application is not inside a synchronized update. Returns how
+        /// long to wait otherwise.
+        fn flush(&mut self, now: Instant) -> Option<Duration> {
+            if !self.pending_update {
+                return None;
+            }
+            if let Some(since) = self.synchronized_since {
+                let deadline = since + SYNCHRONIZED_OUTPUT_TIMEOUT;
+                if now < deadline {
+                    return Some(deadline - now);
+                }
+            }
+            if let Some(next) = self.last_update.map(|last| last + FRAME_INTERVAL) {
+                if now < next {
+                    return Some(next - now);
+                }
+            }
+            self.pending_update = false;
+            self.last_update = Some(now);
+            self.session.emit_updated();
+            None
+        }
+
+        /// Returns how long the PTY stays out of the poll set, or `None` if
+        /// it should be polled.
+        fn throttle(&mut self, now: Instant) -> Option<Duration> {
+            if self.session.frame_consumed.swap(false, Ordering::SeqCst) {
+                self.unrendered_bytes = 0;
+            }
+            if self.unrendered_bytes >= BACKPRESSURE_BYTES {
+                let resume = *self.paused_since.get_or_insert(now) + BACKPRESSURE_MAX_PAUSE;
+                if now < resume {
+                    self.session.throttled.store(true, Ordering::SeqCst);
+                    return Some(resume - now);
+                }
+                self.unrendered_bytes = 0;
+            }
+            self.paused_since = None;
+            self.session.throttled.store(false, Ordering::SeqCst);
+            None
+        }
+
+        /// Reads up to `READ_BUDGET` bytes. Returns false once the PTY hit
+        /// EOF or failed.
+        fn read_available(&mut self, buffer: &mut [u8]) -> bool {
+            let mut budget = READ_BUDGET;
+            while budget > 0 {
+                let Ok(mut terminal) = self.session.terminal.lock() else {
+                    return false;
+                };
+                if !self.attached() {
+                    return true;
+                }
+                let len = buffer.len().min(budget);
+                let read = unsafe { libc::read(self.fd, buffer.as_mut_ptr().cast(), len) };
+                if read > 0 {
+                    let read = read as usize;
+                    let changes = write_terminal_output(&mut terminal, &buffer[..read]);
+                    let synchronized =
+                        terminal_mode(terminal.handle, GHOSTTY_MODE_SYNCHRONIZED_OUTPUT);
+                    drop(terminal);
+
+                    self.session.record(|writer| writer.output(&buffer[..read]));
+                    self.session.apply_output_changes(changes);
+                    budget -= read;
+                    self.unrendered_bytes += read;
+                    self.pending_update = true;
+                    self.output_since_refresh = true;
+                    self.synchronized_since = if synchronized {
+                        self.synchronized_since.or_else(|| Some(Instant::now()))
+                    } else {
+                        None
+                    };
+                    continue;
+                }
+                drop(terminal);
+
+                if read == 0 {
+                    return false;
+                }
+                match io::Error::last_os_error().raw_os_error() {
+                    Some(code) if code == libc::EINTR => continue,
+                    Some(code) if code == libc::EAGAIN || code == libc::EWOULDBLOCK => {
+                        return true
+                    }
+                    _ => return false,
+                }
+            }
+            true
+        }
+    }
+
+    /// Reaping may block, so it runs off the reactor thread.
+    fn finish_session(session: Arc<GhosttyVtSession>) {
+        let exit_session = Arc::clone(&session);
+        match thread::Builder::new()
+            .name(format!("ghostty-vt-exit-{}", session.session_id))
+            .spawn(move || exit_session.finish_output())
+        {
+            Ok(handle) => {
+                if let Ok(mut worker_thread) = session.worker_thread.lock() {
+                    worker_thread.replace(handle);
+                }
+            }
+            Err(error) => {
+                eprintln!(
+                    "[canvas] failed to spawn exit thread for {}: {}",
+                    session.session_id, error
+                );
+                session.finish_output();
+            }
+        }
+    }
+
+    /// Waits until one of `poll_fds` is readable, setting `revents` on those
+    /// that are. Returns what `poll` would.
+    #[cfg(not(target_os = "macos"))]
+    fn wait_readable(poll_fds: &mut [libc::pollfd], timeout: Option<Duration>) -> c_int {
+        unsafe {
+            libc::poll(
+                poll_fds.as_mut_ptr(),
+                poll_fds.len() as libc::nfds_t,
+                timeout_ms(timeout),
+            )
+        }
+    }
+
+    /// macOS `poll` has reported PTY descriptors as invalid (`POLLNVAL`), so
+    /// this uses `select` there like tmux does, as long as every descriptor
+    /// fits in an `fd_set`.
+    #[cfg(target_os = "macos")]
+    fn wait_readable(poll_fds: &mut [libc::pollfd], timeout: Option<Duration>) -> c_int {
+        if poll_fds
+            .iter()
+            .any(|poll_fd| poll_fd.fd < 0 || poll_fd.fd as usize >= libc::FD_SETSIZE)
+        {
+            return unsafe {
+                libc::poll(
+                    poll_fds.as_mut_ptr(),
+                    poll_fds.len() as libc::nfds_t,
+                    timeout_ms(timeout),
+                )
+            };
+        }
+        let mut read_fds: libc::fd_set = unsafe { mem::zeroed() };
+        unsafe { libc::FD_ZERO(&mut read_fds) };
+        for poll_fd in poll_fds.iter() {
+            unsafe { libc::FD_SET(poll_fd.fd, &mut read_fds) };
+        }
+        let max_fd = poll_fds.iter().map(|poll_fd| poll_fd.fd).max().unwrap_or(-1);
+        let mut wait = timeout.map(|timeout| libc::timeval {
+            tv_sec: timeout.as_secs().min(libc::time_t::MAX as u64) as libc::time_t,
+            tv_usec: timeout.subsec_micros() as libc::suseconds_t,
+        });
+        let ready = unsafe {
+            libc::select(
+                max_fd + 1,
+                &mut read_fds,
+                ptr::null_mut(),
+                ptr::null_mut(),
+                wait.as_mut().map_or(ptr::null_mut(), |wait| wait as *mut libc::timeval),
+            )
+        };
+        if ready > 0 {
+            for poll_fd in poll_fds.iter_mut() {
+                if unsafe { libc::FD_ISSET(poll_fd.fd, &read_fds) } {
+                    poll_fd.revents = libc::POLLIN;
+                }
+            }
+        }
+        ready
+    }
+
+    /// Rounds up so a sub-millisecond wait does not spin.
+    fn timeout_ms(timeout: Option<Duration>) -> c_int {
+        timeout.map_or(-1, |timeout| {
+            timeout.as_micros().div_ceil(1000).min(i32::MAX as u128) as c_int
+        })
+    }
+
+    /// Looks up foreground processes for the reactor, which must not wait on
+    /// /proc or sysctl. Returns `None` if the thread could not be started.
+    fn spawn_info_worker() -> Option<mpsc::SyncSender<Vec<Arc<GhosttyVtSession>>>> {
+        let (sender, receiver) = mpsc::sync_channel::<Vec<Arc<GhosttyVtSession>>>(1);
+        let spawned = thread::Builder::new()
+            .name("ghostty-vt-info".to_string())
+            .spawn(move || {
+                for sessions in receiver {
+                    for session in sessions {
+                        session.refresh_info();
+                    }
+                }
+            });
+        match spawned {
+            Ok(_) => Some(sender),
+            Err(error) => {
+                eprintln!("[canvas] failed to spawn libghostty-vt info thread: {error}");
+                None
+            }
+        }
+    }
+
+    fn run_reactor(incoming: Arc<Mutex<Vec<Arc<GhosttyVtSession>>>>, wake_fd: RawFd) {
+        let mut entries: Vec<ReactorEntry> = Vec::new();
+        let mut buffer = vec![0_u8; READ_CHUNK];
+        let mut poll_fds: Vec<libc::pollfd> = Vec::new();
+        let mut polled: Vec<usize> = Vec::new();
+        let mut last_foreground_poll = Instant::now();
+        let mut last_idle_foreground_poll = Instant::now();
+        let info_worker = spawn_info_worker();
+
+        loop {
+            if let Ok(mut incoming) = incoming.lock() {
+                entries.extend(incoming.drain(..).map(ReactorEntry::new));
+            }
+            entries.retain(ReactorEntry::attached);
+
+            let now = Instant::now();
+            let mut timeout: Option<Duration> = None;
+            if !entries.is_empty() {
+                // Looking up the foreground process costs syscalls and
+                // /proc reads, so idle sessions are only checked rarely.
+                let refresh_all = now >= last_idle_foreground_poll + FOREGROUND_IDLE_POLL_INTERVAL;
+                if refresh_all || now >= last_foreground_poll + FOREGROUND_POLL_INTERVAL {
+                    let stale: Vec<usize> = (0..entries.len())
+                        .filter(|index| refresh_all || entries[*index].output_since_refresh)
+                        .collect();
+                    let sessions: Vec<Arc<GhosttyVtSession>> = stale
+                        .iter()
+                        .map(|index| Arc::clone(&entries[*index].session))
+                        .collect();
+                    // A busy worker gets these sessions on a later round.
+                    let handed_off = match &info_worker {
+                        Some(worker) => sessions.is_empty() || worker.try_send(sessions).is_ok(),
+                        None => {
+                            sessions.iter().for_each(|session| session.refresh_info());
+                            true
+                        }
+                    };
+                    if handed_off {
+                        for index in stale {
+                            entries[index].output_since_refresh = false;
+                        }
+                    }
+                    last_foreground_poll = now;
+                    if refresh_all {
+                        last_idle_foreground_poll = now;
+                    }
                 }
-
-                thread::sleep(Duration::from_millis(50));
+                let mut next_poll = last_idle_foreground_poll + FOREGROUND_IDLE_POLL_INTERVAL;
+                if entries.iter().any(|entry| entry.output_since_refresh) {
+                    next_poll = next_poll.min(last_foreground_poll + FOREGROUND_POLL_INTERVAL);
+                }
+                timeout = Some(next_poll.saturating_duration_since(now));
             }
-        }
-
-        fn close_pty(&self) {
-            let fd = self.pty_fd.swap(-1, Ordering::SeqCst);
-            if fd >= 0 {
-                unsafe {
-                    libc::close(fd);
+            poll_fds.clear();
+            polled.clear();
+            poll_fds.push(libc::pollfd {
+                fd: wake_fd,
+                events: libc::POLLIN,
+                revents: 0,
+            });
+            for (index, entry) in entries.iter_mut().enumerate() {
+                let flush_wait = entry.flush(now);
+                let throttle_wait = entry.throttle(now);
+                for wait in [flush_wait, throttle_wait].into_iter().flatten() {
+                    timeout = Some(timeout.map_or(wait, |timeout| timeout.min(wait)));
+                }
+                if throttle_wait.is_none() {
+                    poll_fds.push(libc::pollfd {
+                        fd: entry.fd,
+                        events: libc::POLLIN,
+                        revents: 0,
+                    });
+                    polled.push(index);
                 }
             }
-        }
-
-        fn exit_status(&self) -> Option<i32> {
-            let exit_status = self.exit_status.load(Ordering::SeqCst);
-            (exit_status != EXIT_STATUS_RUNNING).then_some(exit_status)
-        }
-
-        fn record_child_exit(&self, status: c_int) {
-            let exit_status = if libc::WIFEXITED(status) {
-                libc::WEXITSTATUS(status)
-            } else if libc::WIFSIGNALED(status) {
-                128 + libc::WTERMSIG(status)
-            } else {
-                0
-            };
-            self.exit_status.store(exit_status, Ordering::SeqCst);
-            self.child_reaped.store(true, Ordering::SeqCst);
-        }
 
-        fn reap_child(&self, blocking: bool) {
-            if self.child_reaped.load(Ordering::SeqCst) {
-                return;
+            let ready = wait_readable(&mut poll_fds, timeout);
+            if ready < 0 {
+                let error = io::Error::last_os_error();
+                if error.raw_os_error() != Some(libc::EINTR) {
+                    eprintln!("[canvas] libghostty-vt reactor poll failed: {error}");
+                    thread::sleep(FRAME_INTERVAL);
+                }
+                continue;
             }
 
-            let mut status = 0;
-            let wait_flags = if blocking { 0 } else { libc::WNOHANG };
-            let wait_result = unsafe { libc::waitpid(self.child_pid, &mut status, wait_flags) };
-            if wait_result != self.child_pid {
-                return;
+            if poll_fds[0].revents != 0 {
+                let mut drain = [0_u8; 64];
+                while unsafe { libc::read(wake_fd, drain.as_mut_ptr().cast(), drain.len()) } > 0 {}
             }
 
-            self.record_child_exit(status);
-        }
-    }
-
-    impl Drop for GhosttyVtSession {
-        fn drop(&mut self) {
-            self.close_pty();
+            let mut closed = Vec::new();
+            for (poll_fd, &index) in poll_fds[1..].iter().zip(&polled) {
+                if poll_fd.revents != 0 && !entries[index].read_available(&mut buffer) {
+                    closed.push(index);
+                }
+            }
+            for index in closed.into_iter().rev() {
+                let entry = entries.swap_remove(index);
+                finish_session(entry.session);
+            }
         }
     }
 
     struct RenderSnapshotInternal {
         rows_data: Vec<GhosttyVtRow>,
+        /// Viewport index of each entry in `rows_data`.
+        row_indexes: Vec<usize>,
         default_fg: GhosttyVtRgb,
         default_bg: GhosttyVtRgb,
         ansi_palette: Vec<GhosttyVtRgb>,
@@ -2261,6 +4417,17 @@ mod imp {
 
     fn render_terminal_snapshot(
         terminal: &mut TerminalState,
+    ) -> Result<RenderSnapshotInternal, String> {
+        render_terminal_rows(terminal, None)
+    }
+
+    /// Updates the render state and reads the viewport. With `select`, each
+    /// row's dirty flag is passed to it to decide whether the row is read,
+    /// and the dirty flags are cleared afterwards; without it every row is
+    /// read and the flags are left for the next `select` caller.
+    fn render_terminal_rows(
+        terminal: &mut TerminalState,
+        mut select: Option<&mut dyn FnMut(usize, bool) -> bool>,
     ) -> Result<RenderSnapshotInternal, String> {
         let render_state = terminal.render_state;
         let mut row_iterator = ptr::null_mut();
@@ -2359,8 +4526,64 @@ mod imp {
                 };
             }
 
+            let mut dirty = GHOSTTY_RENDER_STATE_DIRTY_FULL;
+            if select.is_some() {
+                let _ = unsafe {
+                    ghostty_render_state_get(
+                        render_state,
+                        GHOSTTY_RENDER_STATE_DATA_DIRTY,
+                        (&mut dirty as *mut i32).cast(),
+                    )
+                };
+            }
+
             let mut rows_data = Vec::new();
+            let mut row_indexes = Vec::new();
+            let mut row_index = 0;
             while unsafe { ghostty_render_state_row_iterator_next(row_iterator) } {
+                let index = row_index;
+                row_index += 1;
+                if let Some(select) = select.as_mut() {
+                    let mut row_dirty = false;
+                    let _ = unsafe {
+                        ghostty_render_state_row_get(
+                            row_iterator,
+                            GHOSTTY_RENDER_STATE_ROW_DATA_DIRTY,
+                            (&mut row_dirty as *mut bool).cast(),
+                        )
+                    };
+                    let clean = false;
+                    let _ = unsafe {
+                        ghostty_render_state_row_set(
+                            row_iterator,
+                            GHOSTTY_RENDER_STATE_ROW_OPTION_DIRTY,
+                            (&clean as *const bool).cast(),
+                        )
+                    };
+                    if !select(index, row_dirty || dirty == GHOSTTY_RENDER_STATE_DIRTY_FULL) {
+                        continue;
+                    }
+                }
+
+                let mut raw_row: GhosttyRow = 0;
+                let mut wrapped = false;
+                if unsafe {
+                    ghostty_render_state_row_get(
+                        row_iterator,
+                        GHOSTTY_RENDER_STATE_ROW_DATA_RAW,
+                        (&mut raw_row as *mut GhosttyRow).cast(),
+                    )
+                } == GHOSTTY_SUCCESS
+                {
+                    let _ = unsafe {
+                        ghostty_row_get(
+                            raw_row,
+                            GHOSTTY_ROW_DATA_WRAP,
+                            (&mut wrapped as *mut bool).cast(),
+                        )
+                    };
+                }
+
                 check_result(
                     unsafe {
                         ghostty_render_state_row_get(
@@ -2426,6 +4649,25 @@ mod imp {
                         )
                     };
 
+                    let mut raw_cell: GhosttyCell = 0;
+                    let mut wide = GHOSTTY_CELL_WIDE_NARROW;
+                    if unsafe {
+                        ghostty_render_state_row_cells_get(
+                            row_cells,
+                            GHOSTTY_RENDER_STATE_ROW_CELLS_DATA_RAW,
+                            (&mut raw_cell as *mut GhosttyCell).cast(),
+                        )
+                    } == GHOSTTY_SUCCESS
+                    {
+                        let _ = unsafe {
+                            ghostty_cell_get(
+                                raw_cell,
+                                GHOSTTY_CELL_DATA_WIDE,
+                                (&mut wide as *mut c_int).cast(),
+                            )
+                        };
+                    }
+
                     cells.push(GhosttyVtCell {
                         text,
                         fg,
@@ -2436,14 +4678,31 @@ mod imp {
                         underline: style.underline != 0,
                         strikethrough: style.strikethrough,
                         invisible: style.invisible,
+                        spacer: matches!(
+                            wide,
+                            GHOSTTY_CELL_WIDE_SPACER_TAIL | GHOSTTY_CELL_WIDE_SPACER_HEAD
+                        ),
                     });
                 }
 
-                rows_data.push(GhosttyVtRow { cells });
+                rows_data.push(GhosttyVtRow { cells, wrapped });
+                row_indexes.push(index);
+            }
+
+            if select.is_some() {
+                let clean = GHOSTTY_RENDER_STATE_DIRTY_FALSE;
+                let _ = unsafe {
+                    ghostty_render_state_set(
+                        render_state,
+                        GHOSTTY_RENDER_STATE_OPTION_DIRTY,
+                        (&clean as *const i32).cast(),
+                    )
+                };
             }
 
             Ok(RenderSnapshotInternal {
                 rows_data,
+                row_indexes,
                 default_fg: serialize_rgb(colors.foreground),
                 default_bg: serialize_rgb(colors.background),
                 ansi_palette: colors
@@ -2471,6 +4730,410 @@ mod imp {
         result
     }
 
+    fn scroll_terminal(handle: GhosttyTerminal, tag: i32, delta: i64) {
+        unsafe {
+            ghostty_terminal_scroll_viewport(
+                handle,
+                GhosttyTerminalScrollViewport {
+                    tag,
+                    value: GhosttyTerminalScrollViewportValue {
+                        delta: delta as isize,
+                    },
+                },
+            );
+        }
+    }
+
+    fn terminal_scrollbar(handle: GhosttyTerminal) -> Result<GhosttyTerminalScrollbar, String> {
+        let mut scrollbar = GhosttyTerminalScrollbar::default();
+        check_result(
+            unsafe {
+                ghostty_terminal_get(
+                    handle,
+                    GHOSTTY_TERMINAL_DATA_SCROLLBAR,
+                    (&mut scrollbar as *mut GhosttyTerminalScrollbar).cast(),
+                )
+            },
+            "ghostty_terminal_get(SCROLLBAR)",
+        )?;
+        Ok(scrollbar)
+    }
+
+    struct Scrollback {
+        rows: Vec<GhosttyVtRow>,
+        default_fg: GhosttyVtRgb,
+        default_bg: GhosttyVtRgb,
+    }
+
+    /// Every row of the scrollback and active screen, oldest first. The
+    /// render state only covers the viewport, so this pages the viewport
+    /// from the top and puts it back afterwards.
+    fn collect_scrollback_rows(terminal: &mut TerminalState) -> Result<Scrollback, String> {
+        let original = terminal_scrollbar(terminal.handle)?;
+        let mut scrollback = Scrollback {
+            rows: Vec::new(),
+            default_fg: GhosttyVtRgb {
+                r: 255,
+                g: 255,
+                b: 255,
+            },
+            default_bg: GhosttyVtRgb { r: 0, g: 0, b: 0 },
+        };
+
+        scroll_terminal(terminal.handle, GHOSTTY_SCROLL_VIEWPORT_TOP, 0);
+        let result = (|| loop {
+            let scrollbar = terminal_scrollbar(terminal.handle)?;
+            let page = render_terminal_snapshot(terminal)?;
+            scrollback.default_fg = page.default_fg;
+            scrollback.default_bg = page.default_bg;
+            let page_len = page.rows_data.len() as u64;
+            // The last page is clamped to the bottom and can overlap.
+            let seen = (scrollback.rows.len() as u64).saturating_sub(scrollbar.offset) as usize;
+            scrollback.rows.extend(page.rows_data.into_iter().skip(seen));
+            if page_len == 0 || scrollbar.offset + scrollbar.len >= scrollbar.total {
+                return Ok(());
+            }
+            scroll_terminal(
+                terminal.handle,
+                GHOSTTY_SCROLL_VIEWPORT_DELTA,
+                scrollbar.len.max(1) as i64,
+            );
+        })();
+
+        if original.offset + original.len >= original.total {
+            scroll_terminal(terminal.handle, GHOSTTY_SCROLL_VIEWPORT_BOTTOM, 0);
+        } else {
+            scroll_terminal(terminal.handle, GHOSTTY_SCROLL_VIEWPORT_TOP, 0);
+            scroll_terminal(
+                terminal.handle,
+                GHOSTTY_SCROLL_VIEWPORT_DELTA,
+                original.offset as i64,
+            );
+        }
+        result.map(|_| scrollback)
+    }
+
+    /// The text of a row with blank cells as spaces, plus the cell column
+    /// at every byte offset (one past the end maps to the row width).
+    fn row_text_with_columns(row: &GhosttyVtRow) -> (String, Vec<u16>) {
+        let mut text = String::new();
+        let mut columns = Vec::new();
+        for (column, cell_text, _) in visible_cells(row) {
+            text.push_str(cell_text);
+            columns.resize(text.len(), column as u16);
+        }
+        columns.push(row.cells.len() as u16);
+        (text, columns)
+    }
+
+    /// OSC 8 hyperlinks and links detected in the text of viewport rows,
+    /// given with their viewport index.
+    fn viewport_links<'a>(
+        terminal: &mut TerminalState,
+        rows: impl IntoIterator<Item = (usize, &'a GhosttyVtRow)>,
+        initial_cwd: Option<&Path>,
+    ) -> Vec<GhosttyVtLink> {
+        let top = forget_scrolled_out(terminal)
+            + terminal_scrollbar(terminal.handle)
+                .map(|scrollbar| scrollbar.offset)
+                .unwrap_or(0);
+        let mut links = Vec::new();
+        for (index, row) in rows {
+            let viewport_row = u16::try_from(index).unwrap_or(u16::MAX);
+            let screen_row = top + index as u64;
+            let cols = u16::try_from(row.cells.len()).unwrap_or(u16::MAX);
+            let hyperlinks = terminal.hyperlinks.row_spans(screen_row, cols);
+            for (start_col, end_col, uri) in &hyperlinks {
+                links.push(GhosttyVtLink {
+                    row: viewport_row,
+                    start_col: *start_col,
+                    end_col: *end_col,
+                    kind: GhosttyVtLinkKind::Hyperlink,
+                    target: uri.to_string(),
+                    line: None,
+                    column: None,
+                });
+            }
+
+            let cwd = terminal
+                .commands
+                .cwd_at(screen_row)
+                .map(Path::new)
+                .or(initial_cwd);
+            let (text, columns) = row_text_with_columns(row);
+            for link in find_text_links(&text, cwd) {
+                let (start_col, end_col) = (columns[link.start], columns[link.end]);
+                if hyperlinks
+                    .iter()
+                    .any(|(start, end, _)| start_col < *end && *start < end_col)
+                {
+                    continue;
+                }
+                links.push(GhosttyVtLink {
+                    row: viewport_row,
+                    start_col,
+                    end_col,
+                    kind: link.kind,
+                    target: link.target,
+                    line: link.line,
+                    column: link.column,
+                });
+            }
+        }
+        links
+    }
+
+    /// The cells of a row that hold a character, with their column and text:
+    /// wide-character spacers are skipped and blank or invisible cells read
+    /// as a space.
+    fn visible_cells(
+        row: &GhosttyVtRow,
+    ) -> impl Iterator<Item = (usize, &str, &GhosttyVtCell)> + '_ {
+        row.cells
+            .iter()
+            .enumerate()
+            .filter(|(_, cell)| !cell.spacer)
+            .map(|(column, cell)| {
+                let text = if cell.text.is_empty() || cell.invisible {
+                    " "
+                } else {
+                    cell.text.as_str()
+                };
+                (column, text, cell)
+            })
+    }
+
+    #[derive(Clone, Copy, Default, PartialEq, Eq)]
+    struct CellStyle {
+        fg: Option<GhosttyVtRgb>,
+        bg: Option<GhosttyVtRgb>,
+        bold: bool,
+        italic: bool,
+        dim: bool,
+        underline: bool,
+        strikethrough: bool,
+    }
+
+    /// Cells of a row as text and style, without the blank, unstyled tail of
+    /// a row that was not soft-wrapped.
+    fn styled_cells(row: &GhosttyVtRow) -> Vec<(&str, CellStyle)> {
+        let mut cells = Vec::with_capacity(row.cells.len());
+        for (_, text, cell) in visible_cells(row) {
+            cells.push((
+                text,
+                CellStyle {
+                    fg: cell.fg,
+                    bg: cell.bg,
+                    bold: cell.bold,
+                    italic: cell.italic,
+                    dim: cell.dim,
+                    underline: cell.underline,
+                    strikethrough: cell.strikethrough,
+                },
+            ));
+        }
+        while !row.wrapped
+            && cells
+                .last()
+                .is_some_and(|(text, style)| *text == " " && style.bg.is_none())
+        {
+            cells.pop();
+        }
+        cells
+    }
+
+    /// Joins the rendered `lines` of `rows`, continuing soft-wrapped rows on
+    /// the same line and dropping the empty rows below the last output.
+    fn join_rows(rows: &[GhosttyVtRow], mut lines: Vec<String>) -> String {
+        while lines.last().is_some_and(|line| line.is_empty()) {
+            lines.pop();
+        }
+        let mut output = String::new();
+        for (row, line) in rows.iter().zip(&lines) {
+            output.push_str(line);
+            if !row.wrapped {
+                output.push('\n');
+            }
+        }
+        if !output.is_empty() && !output.ends_with('\n') {
+            output.push('\n');
+        }
+        output
+    }
+
+    fn export_text(rows: &[GhosttyVtRow]) -> String {
+        join_rows(
+            rows,
+            rows.iter()
+                .map(|row| {
+                    let line: String = styled_cells(row).into_iter().map(|(text, _)| text).collect();
+                    if row.wrapped {
+                        line
+                    } else {
+                        line.trim_end().to_string()
+                    }
+                })
+                .collect(),
+        )
+    }
+
+    fn sgr_sequence(style: &CellStyle) -> String {
+        let mut codes = vec!["0".to_string()];
+        for (enabled, code) in [
+            (style.bold, "1"),
+            (style.dim, "2"),
+            (style.italic, "3"),
+            (style.underline, "4"),
+            (style.strikethrough, "9"),
+        ] {
+            if enabled {
+                codes.push(code.to_string());
+            }
+        }
+        if let Some(fg) = style.fg {
+            codes.push(format!("38;2;{};{};{}", fg.r, fg.g, fg.b));
+        }
+        if let Some(bg) = style.bg {
+            codes.push(format!("48;2;{};{};{}", bg.r, bg.g, bg.b));
+        }
+        format!("\x1b[{}m", codes.join(";"))
+    }
+
+    fn export_ansi(rows: &[GhosttyVtRow]) -> String {
+        join_rows(
+            rows,
+            rows.iter()
+                .map(|row| {
+                    let mut line = String::new();
+                    let mut current = CellStyle::default();
+                    for (text, style) in styled_cells(row) {
+                        if style != current {
+                            line.push_str(&sgr_sequence(&style));
+                            current = style;
+                        }
+                        line.push_str(text);
+                    }
+                    if current != CellStyle::default() {
+                        line.push_str("\x1b[0m");
+                    }
+                    line
+                })
+                .collect(),
+        )
+    }
+
+    fn css_color(color: GhosttyVtRgb) -> String {
+        format!("#{:02x}{:02x}{:02x}", color.r, color.g, color.b)
+    }
+
+    fn escape_html(text: &str, output: &mut String) {
+        for ch in text.chars() {
+            match ch {
+                '&' => output.push_str("&amp;"),
+                '<' => output.push_str("&lt;"),
+                '>' => output.push_str("&gt;"),
+                '"' => output.push_str("&quot;"),
+                _ => output.push(ch),
+            }
+        }
+    }
+
+    fn css_declarations(style: &CellStyle) -> String {
+        let mut declarations = Vec::new();
+        if let Some(fg) = style.fg {
+            declarations.push(format!("color:{}", css_color(fg)));
+        }
+        if let Some(bg) = style.bg {
+            declarations.push(format!("background-color:{}", css_color(bg)));
+        }
+        if style.bold {
+            declarations.push("font-weight:bold".to_string());
+        }
+        if style.italic {
+            declarations.push("font-style:italic".to_string());
+        }
+        if style.dim {
+            declarations.push("opacity:0.6".to_string());
+        }
+        let decorations: Vec<&str> = [
+            (style.underline, "underline"),
+            (style.strikethrough, "line-through"),
+        ]
+        .into_iter()
+        .filter_map(|(enabled, decoration)| enabled.then_some(decoration))
+        .collect();
+        if !decorations.is_empty() {
+            declarations.push(format!("text-decoration:{}", decorations.join(" ")));
+        }
+        declarations.join(";")
+    }
+
+    fn export_html(scrollback: &Scrollback) -> String {
+        let body = join_rows(
+            &scrollback.rows,
+            scrollback
+                .rows
+                .iter()
+                .map(|row| {
+                    let mut line = String::new();
+                    let mut runs: Vec<(CellStyle, String)> = Vec::new();
+                    for (text, style) in styled_cells(row) {
+                        match runs.last_mut() {
+                            Some((run_style, run_text)) if *run_style == style => {
+                                run_text.push_str(text)
+                            }
+                            _ => runs.push((style, text.to_string())),
+                        }
+                    }
+                    for (style, text) in runs {
+                        if style == CellStyle::default() {
+                            escape_html(&text, &mut line);
+                        } else {
+                            line.push_str("<span style=\"");
+                            line.push_str(&css_declarations(&style));
+                            line.push_str("\">");
+                            escape_html(&text, &mut line);
+                            line.push_str("</span>");
+                        }
+                    }
+                    line
+                })
+                .collect(),
+        );
+        format!(
+            "<pre style=\"background-color:{};color:{};font-family:ui-monospace,Menlo,monospace;padding:8px\">{}</pre>\n",
+            css_color(scrollback.default_bg),
+            css_color(scrollback.default_fg),
+            body
+        )
+    }
+
+    fn search_rows(
+        rows: &[GhosttyVtRow],
+        first_row: u64,
+        matcher: &regex::Regex,
+    ) -> (Vec<GhosttyVtSearchMatch>, bool) {
+        let mut matches = Vec::new();
+        for (row_index, row) in rows.iter().enumerate() {
+            let (text, columns) = row_text_with_columns(row);
+            for found in matcher.find_iter(&text) {
+                if found.is_empty() {
+                    continue;
+                }
+                if matches.len() == MAX_SEARCH_MATCHES {
+                    return (matches, true);
+                }
+                matches.push(GhosttyVtSearchMatch {
+                    row: first_row + row_index as u64,
+                    start_col: columns[found.start()],
+                    end_col: columns[found.end()],
+                    text: found.as_str().to_string(),
+                });
+            }
+        }
+        (matches, false)
+    }
+
     fn plain_text_from_rows(rows: &[GhosttyVtRow]) -> String {
         let mut output = String::new();
         for (row_index, row) in rows.iter().enumerate() {
@@ -2730,9 +5393,28 @@ mod imp {
         cwd: Option<&str>,
         command: Option<&str>,
         geometry: SessionGeometry,
+        env: &[(String, String)],
     ) -> Result<(RawFd, libc::pid_t), String> {
         let shell_path = resolve_user_shell_path();
         let login_shell_path = resolve_login_shell_path(&shell_path);
+        let shell_name = Path::new(&shell_path)
+            .file_name()
+            .and_then(|value| value.to_str())
+            .unwrap_or("sh");
+        // Prepared before forking; commands run non-interactively and get
+        // no prompt hooks.
+        let mut child_env = env.to_vec();
+        let mut bash_rcfile = None;
+        if let Some(launch) = command
+            .is_none()
+            .then(|| prepare_shell_integration(shell_name, env))
+            .flatten()
+        {
+            child_env.extend(launch.env);
+            bash_rcfile = launch
+                .bash_rcfile
+                .and_then(|path| CString::new(path.to_string_lossy().as_bytes()).ok());
+        }
         let mut pty_fd = -1;
         let mut winsize = libc::winsize {
             ws_row: geometry.rows,
@@ -2748,7 +5430,14 @@ mod imp {
         }
 
         if child_pid == 0 {
-            run_shell_child(cwd, command, &shell_path, login_shell_path.as_deref());
+            run_shell_child(
+                cwd,
+                command,
+                &shell_path,
+                login_shell_path.as_deref(),
+                &child_env,
+                bash_rcfile.as_deref(),
+            );
         }
 
         let flags = unsafe { libc::fcntl(pty_fd, libc::F_GETFL) };
@@ -2779,6 +5468,8 @@ mod imp {
         command: Option<&str>,
         shell_path: &str,
         login_shell_path: Option<&str>,
+        env: &[(String, String)],
+        bash_rcfile: Option<&CStr>,
     ) -> ! {
         if let Some(cwd) = cwd {
             if let Ok(cwd) = CString::new(cwd) {
@@ -2789,6 +5480,9 @@ mod imp {
         }
 
         configure_shell_environment(login_shell_path);
+        for (key, value) in env {
+            set_env(key, value);
+        }
 
         let shell = CString::new(shell_path)
             .unwrap_or_else(|_| CString::new("/bin/sh").expect("static shell path is valid"));
@@ -2812,6 +5506,20 @@ mod imp {
                     ptr::null::<c_char>(),
                 );
             }
+        } else if let Some(rcfile) = bash_rcfile {
+            // The rc file sources the login files itself.
+            let shell_name = CString::new(shell_name)
+                .unwrap_or_else(|_| CString::new("bash").expect("static shell name is valid"));
+            let arg1 = CString::new("--rcfile").expect("static shell arg is valid");
+            unsafe {
+                libc::execl(
+                    shell.as_ptr(),
+                    shell_name.as_ptr(),
+                    arg1.as_ptr(),
+                    rcfile.as_ptr(),
+                    ptr::null::<c_char>(),
+                );
+            }
         } else {
             unsafe {
                 libc::execl(
@@ -2883,60 +5591,6 @@ mod imp {
         }
     }
 
-    fn resolve_login_shell_path(shell_path: &str) -> Option<String> {
-        resolve_shell_path(shell_path, &["-i", "-l", "-c"])
-            .or_else(|| resolve_shell_path(shell_path, &["-l", "-c"]))
-    }
-
-    fn resolve_shell_path(shell_path: &str, shell_args: &[&str]) -> Option<String> {
-        const START_MARKER: &str = "__OTTO_CANVAS_PATH_START__";
-        const END_MARKER: &str = "__OTTO_CANVAS_PATH_END__";
-
-        let output = Command::new(shell_path)
-            .args(shell_args)
-            .arg(format!(
-                "printf '%s%s%s' '{START_MARKER}' \"$PATH\" '{END_MARKER}'"
-            ))
-            .output()
-            .ok()?;
-        if !output.status.success() {
-            return None;
-        }
-        let stdout = String::from_utf8(output.stdout).ok()?;
-        let start = stdout.rfind(START_MARKER)? + START_MARKER.len();
-        let end = stdout[start..].find(END_MARKER)? + start;
-        let path = stdout[start..end].trim();
-        if path.is_empty() {
-            return None;
-        }
-        Some(path.to_string())
-    }
-
-    fn resolve_user_shell_path() -> String {
-        if let Some(shell_path) = std::env::var("SHELL")
-            .ok()
-            .filter(|value| !value.is_empty())
-        {
-            return shell_path;
-        }
-
-        unsafe {
-            let passwd = libc::getpwuid(libc::geteuid());
-            if !passwd.is_null() {
-                let shell_ptr = (*passwd).pw_shell;
-                if !shell_ptr.is_null() {
-                    if let Ok(shell_path) = CStr::from_ptr(shell_ptr).to_str() {
-                        if !shell_path.is_empty() {
-                            return shell_path.to_string();
-                        }
-                    }
-                }
-            }
-        }
-
-        "/bin/sh".to_string()
-    }
-
     fn set_env(key: &str, value: &str) {
         let Ok(key) = CString::new(key) else {
             return;
@@ -0,0 +1,254 @@
+//! Binary encoding of a `GhosttyVtSnapshot`, served over the `ghostty-vt`
+//! URI scheme so the webview can decode large frames with typed arrays
+//! instead of parsing one JSON object per cell.
+//!
+//! All integers are little-endian and every section starts on a 4-byte
+//! boundary.
+//!
+//! ```text
+//! header (32 bytes)
+//!    0  [u8; 4]  magic "GVTF"
+//!    4  u16      format version (1)
+//!    6  u16      flags: 1 process alive, 2 exit status valid,
+//!                4 cursor visible, 8 cursor blinking, 16 cursor position valid
+//!    8  u16      cols
+//!   10  u16      rows
+//!   12  i32      exit status
+//!   16  u16      cursor x
+//!   18  u16      cursor y
+//!   20  u8       cursor shape: 0 block, 1 bar, 2 underline, 3 hollow block
+//!   21  u8       reserved
+//!   22  u16      palette length P
+//!   24  u32      span count S
+//!   28  u32      text length T in bytes
+//! palette        P x u32 0x00RRGGBB; 0 is the default foreground, 1 the
+//!                default background, 2..18 the ANSI colors, then every other
+//!                color used by a span
+//! row table      (rows + 1) x u32; spans of row r are [row[r], row[r + 1])
+//! spans          S x 3 u32:
+//!                  word 0  fg palette index | bg palette index << 16,
+//!                          0xFFFF when the cell uses the default color
+//!                  word 1  cell count | style flags << 16: 1 bold, 2 italic,
+//!                          4 dim, 8 underline, 16 strikethrough, 32 invisible
+//!                  word 2  offset of the span's text in UTF-16 code units
+//! cell lengths   one u8 per cell in span order: UTF-16 length of the cell's
+//!                text, 0 for blank cells and wide-character spacers;
+//!                padded to 4 bytes
+//! text           T bytes of UTF-8; decode once with TextDecoder and slice
+//!                with the span offsets and cell lengths
+//! ```
+
+use crate::ghostty_vt::{GhosttyVtCell, GhosttyVtRgb, GhosttyVtSnapshot};
+use std::collections::HashMap;
+
+const MAGIC: &[u8; 4] = b"GVTF";
+const FORMAT_VERSION: u16 = 1;
+const HEADER_LEN: usize = 32;
+
+const FLAG_PROCESS_ALIVE: u16 = 1;
+const FLAG_EXITED: u16 = 2;
+const FLAG_CURSOR_VISIBLE: u16 = 4;
+const FLAG_CURSOR_BLINKING: u16 = 8;
+const FLAG_CURSOR_POSITION: u16 = 16;
+
+const DEFAULT_COLOR: u16 = 0xFFFF;
+
+const STYLE_BOLD: u16 = 1;
+const STYLE_ITALIC: u16 = 2;
+const STYLE_DIM: u16 = 4;
+const STYLE_UNDERLINE: u16 = 8;
+const STYLE_STRIKETHROUGH: u16 = 16;
+const STYLE_INVISIBLE: u16 = 32;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct SpanStyle {
+    fg: u16,
+    bg: u16,
+    flags: u16,
+}
+
+struct Span {
+    style: SpanStyle,
+    cells: u16,
+    text_offset: u32,
+}
+
+fn pack_rgb(color: GhosttyVtRgb) -> u32 {
+    (u32::from(color.r) << 16) | (u32::from(color.g) << 8) | u32::from(color.b)
+}
+
+fn cursor_shape_code(shape: &str) -> u8 {
+    match shape {
+        "bar" => 1,
+        "underline" => 2,
+        "hollow-block" => 3,
+        _ => 0,
+    }
+}
+
+struct Palette {
+    colors: Vec<u32>,
+    indexes: HashMap<u32, u16>,
+}
+
+impl Palette {
+    fn new(snapshot: &GhosttyVtSnapshot) -> Self {
+        let mut palette = Self {
+            colors: Vec::new(),
+            indexes: HashMap::new(),
+        };
+        palette.push(pack_rgb(snapshot.default_fg));
+        palette.push(pack_rgb(snapshot.default_bg));
+        for color in &snapshot.ansi_palette {
+            palette.push(pack_rgb(*color));
+        }
+        palette
+    }
+
+    fn push(&mut self, color: u32) -> u16 {
+        let index = self.colors.len() as u16;
+        self.colors.push(color);
+        self.indexes.entry(color).or_insert(index);
+        index
+    }
+
+    fn index(&mut self, color: Option<GhosttyVtRgb>) -> u16 {
+        let Some(color) = color.map(pack_rgb) else {
+            return DEFAULT_COLOR;
+        };
+        match self.indexes.get(&color) {
+            Some(index) => *index,
+            // Past 0xFFFE distinct colors the rest fall back to the default.
+            None if self.colors.len() < usize::from(DEFAULT_COLOR) => self.push(color),
+            None => DEFAULT_COLOR,
+        }
+    }
+}
+
+fn cell_style(palette: &mut Palette, cell: &GhosttyVtCell) -> SpanStyle {
+    let mut flags = 0;
+    for (set, flag) in [
+        (cell.bold, STYLE_BOLD),
+        (cell.italic, STYLE_ITALIC),
+        (cell.dim, STYLE_DIM),
+        (cell.underline, STYLE_UNDERLINE),
+        (cell.strikethrough, STYLE_STRIKETHROUGH),
+        (cell.invisible, STYLE_INVISIBLE),
+    ] {
+        if set {
+            flags |= flag;
+        }
+    }
+    SpanStyle {
+        fg: palette.index(cell.fg),
+        bg: palette.index(cell.bg),
+        flags,
+    }
+}
+
+/// Longest prefix of `text` that fits the u8 UTF-16 length of a cell.
+fn cell_text(text: &str) -> (&str, u8) {
+    let mut units = 0;
+    for (index, ch) in text.char_indices() {
+        if units + ch.len_utf16() > usize::from(u8::MAX) {
+            return (&text[..index], units as u8);
+        }
+        units += ch.len_utf16();
+    }
+    (text, units as u8)
+}
+
+fn pad_to_word(buffer: &mut Vec<u8>) {
+    buffer.resize(buffer.len().next_multiple_of(4), 0);
+}
+
+pub fn encode_frame(snapshot: &GhosttyVtSnapshot) -> Vec<u8> {
+    let mut palette = Palette::new(snapshot);
+    let mut row_starts = Vec::with_capacity(snapshot.rows_data.len() + 1);
+    let mut spans: Vec<Span> = Vec::new();
+    let mut cell_lengths = Vec::new();
+    let mut text = String::new();
+    let mut text_units: u32 = 0;
+
+    for row in &snapshot.rows_data {
+        row_starts.push(spans.len() as u32);
+        let row_start = spans.len();
+        for cell in &row.cells {
+            let style = cell_style(&mut palette, cell);
+            let (shown, units) = cell_text(&cell.text);
+            let in_row = spans.len() > row_start;
+            match spans.last_mut() {
+                Some(span) if in_row && span.style == style && span.cells < u16::MAX => {
+                    span.cells += 1;
+                }
+                _ => spans.push(Span {
+                    style,
+                    cells: 1,
+                    text_offset: text_units,
+                }),
+            }
+            cell_lengths.push(units);
+            text.push_str(shown);
+            text_units += u32::from(units);
+        }
+    }
+    row_starts.push(spans.len() as u32);
+
+    let mut flags = 0;
+    if snapshot.process_alive {
+        flags |= FLAG_PROCESS_ALIVE;
+    }
+    if snapshot.exit_status.is_some() {
+        flags |= FLAG_EXITED;
+    }
+    if snapshot.cursor.visible {
+        flags |= FLAG_CURSOR_VISIBLE;
+    }
+    if snapshot.cursor.blinking {
+        flags |= FLAG_CURSOR_BLINKING;
+    }
+    let cursor_position = snapshot.cursor.x.zip(snapshot.cursor.y);
+    if cursor_position.is_some() {
+        flags |= FLAG_CURSOR_POSITION;
+    }
+    let (cursor_x, cursor_y) = cursor_position.unwrap_or_default();
+
+    let len = HEADER_LEN
+        + palette.colors.len() * 4
+        + row_starts.len() * 4
+        + spans.len() * 12
+        + cell_lengths.len().next_multiple_of(4)
+        + text.len();
+    let mut buffer = Vec::with_capacity(len);
+    buffer.extend_from_slice(MAGIC);
+    buffer.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+    buffer.extend_from_slice(&flags.to_le_bytes());
+    buffer.extend_from_slice(&snapshot.cols.to_le_bytes());
+    buffer.extend_from_slice(&((row_starts.len() - 1) as u16).to_le_bytes());
+    buffer.extend_from_slice(&snapshot.exit_status.unwrap_or(0).to_le_bytes());
+    buffer.extend_from_slice(&cursor_x.to_le_bytes());
+    buffer.extend_from_slice(&cursor_y.to_le_bytes());
+    buffer.push(cursor_shape_code(&snapshot.cursor.shape));
+    buffer.push(0);
+    buffer.extend_from_slice(&(palette.colors.len() as u16).to_le_bytes());
+    buffer.extend_from_slice(&(spans.len() as u32).to_le_bytes());
+    buffer.extend_from_slice(&(text.len() as u32).to_le_bytes());
+
+    for color in &palette.colors {
+        buffer.extend_from_slice(&color.to_le_bytes());
+    }
+    for start in &row_starts {
+        buffer.extend_from_slice(&start.to_le_bytes());
+    }
+    for span in &spans {
+        let colors = u32::from(span.style.fg) | (u32::from(span.style.bg) << 16);
+        let cells = u32::from(span.cells) | (u32::from(span.style.flags) << 16);
+        buffer.extend_from_slice(&colors.to_le_bytes());
+        buffer.extend_from_slice(&cells.to_le_bytes());
+        buffer.extend_from_slice(&span.text_offset.to_le_bytes());
+    }
+    buffer.extend_from_slice(&cell_lengths);
+    pad_to_word(&mut buffer);
+    buffer.extend_from_slice(text.as_bytes());
+    buffer
+}
@@ -0,0 +1,330 @@
+use git2::{
+    BranchType, Repository, StatusOptions, WorktreeAddOptions, WorktreeLockStatus,
+    WorktreePruneOptions,
+};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GitWorktreeInfo {
+    /// `None` for the main working tree.
+    pub name: Option<String>,
+    pub path: String,
+    pub branch: Option<String>,
+    pub is_main: bool,
+    pub locked: bool,
+    pub prunable: bool,
+    pub dirty: bool,
+    pub ahead: usize,
+    pub behind: usize,
+}
+
+fn expand_home(path: &str) -> Result<PathBuf, String> {
+    match path.strip_prefix("~/") {
+        Some(rest) => {
+            let home = dirs::home_dir().ok_or_else(|| "No home directory".to_string())?;
+            Ok(home.join(rest))
+        }
+        None => Ok(PathBuf::from(path)),
+    }
+}
+
+/// Worktrees default to a sibling `<repo>.worktrees` directory so they never
+/// show up as untracked files in the main checkout.
+fn default_worktree_root(repo: &Repository) -> Result<PathBuf, String> {
+    let workdir = repo
+        .workdir()
+        .ok_or_else(|| "Repository has no working directory".to_string())?;
+    let workdir = workdir.canonicalize().unwrap_or_else(|_| workdir.to_path_buf());
+    let name = workdir
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("repo");
+    let parent = workdir.parent().unwrap_or(&workdir);
+    Ok(parent.join(format!("{}.worktrees", name)))
+}
+
+fn worktree_name_for_branch(branch: &str) -> String {
+    branch
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_' | '.') {
+                ch
+            } else {
+                '-'
+            }
+        })
+        .collect::<String>()
+        .trim_matches(|ch| ch == '-' || ch == '.')
+        .to_string()
+}
+
+fn is_dirty(repo: &Repository) -> Result<bool, git2::Error> {
+    let mut options = StatusOptions::new();
+    options.include_untracked(true).include_ignored(false);
+    Ok(!repo.statuses(Some(&mut options))?.is_empty())
+}
+
+fn calculate_ahead_behind(repo: &Repository) -> Result<(usize, usize), git2::Error> {
+    let head = repo.head()?;
+    let local_oid = head
+        .target()
+        .ok_or_else(|| git2::Error::from_str("No local HEAD target"))?;
+
+    let upstream_oid = if head.is_branch() {
+        let branch = git2::Branch::wrap(head);
+        match branch.upstream() {
+            Ok(upstream) => upstream.get().target(),
+            Err(_) => {
+                let name = branch.name()?.unwrap_or("main");
+                repo.find_reference(&format!("refs/remotes/origin/{}", name))
+                    .ok()
+                    .and_then(|reference| reference.target())
+            }
+        }
+    } else {
+        None
+    };
+
+    match upstream_oid {
+        Some(upstream_oid) => repo.graph_ahead_behind(local_oid, upstream_oid),
+        None => Ok((0, 0)),
+    }
+}
+
+fn describe_worktree(
+    path: &Path,
+    name: Option<String>,
+    locked: bool,
+    prunable: bool,
+) -> GitWorktreeInfo {
+    let mut info = GitWorktreeInfo {
+        name,
+        path: path.to_string_lossy().to_string(),
+        branch: None,
+        is_main: false,
+        locked,
+        prunable,
+        dirty: false,
+        ahead: 0,
+        behind: 0,
+    };
+    if prunable {
+        return info;
+    }
+    if let Ok(repo) = Repository::open(path) {
+        info.branch = repo
+            .head()
+            .ok()
+            .filter(|head| head.is_branch())
+            .and_then(|head| head.shorthand().map(str::to_string));
+        info.dirty = is_dirty(&repo).unwrap_or(false);
+        (info.ahead, info.behind) = calculate_ahead_behind(&repo).unwrap_or((0, 0));
+    }
+    info
+}
+
+fn list_worktrees(repo: &Repository) -> Result<Vec<GitWorktreeInfo>, String> {
+    let mut worktrees = Vec::new();
+    if let Some(workdir) = repo.workdir() {
+        let mut main = describe_worktree(workdir, None, false, false);
+        main.is_main = true;
+        worktrees.push(main);
+    }
+
+    let names = repo.worktrees().map_err(|e| e.to_string())?;
+    for name in names.iter().flatten() {
+        let worktree = repo.find_worktree(name).map_err(|e| e.to_string())?;
+        let locked = matches!(worktree.is_locked(), Ok(WorktreeLockStatus::Locked(_)));
+        let prunable = worktree.validate().is_err();
+        worktrees.push(describe_worktree(
+            worktree.path(),
+            Some(name.to_string()),
+            locked,
+            prunable,
+        ));
+    }
+    Ok(worktrees)
+}
+
+/// Opens the main repository even when `path` points into a linked worktree.
+fn open_main_repository(path: &str) -> Result<Repository, String> {
+    let repo = Repository::open(expand_home(path)?)
+        .map_err(|e| format!("Not a git repository: {}", e))?;
+    if repo.is_worktree() {
+        // `.git/worktrees/<name>/commondir` points back at the shared git dir.
+        let common_dir = fs::read_to_string(repo.path().join("commondir"))
+            .map_err(|e| format!("Failed to resolve main repository: {}", e))?;
+        return Repository::open(repo.path().join(common_dir.trim())).map_err(|e| e.to_string());
+    }
+    Ok(repo)
+}
+
+/// Creates a linked worktree. With `create_branch` a new branch is cut from
+/// `base` (or HEAD); otherwise `branch` must already exist locally or as
+/// `origin/<branch>`, in which case a tracking branch is created.
+#[tauri::command]
+pub async fn git_worktree_create(
+    repo_path: String,
+    branch: String,
+    create_branch: bool,
+    base: Option<String>,
+    root: Option<String>,
+) -> Result<GitWorktreeInfo, String> {
+    let repo = open_main_repository(&repo_path)?;
+    let branch = branch.trim();
+    if branch.is_empty() {
+        return Err("Branch name is required".to_string());
+    }
+
+    let name = worktree_name_for_branch(branch);
+    if name.is_empty() {
+        return Err(format!("Cannot derive a worktree name from branch {}", branch));
+    }
+    if repo.find_worktree(&name).is_ok() {
+        return Err(format!("Worktree {} already exists", name));
+    }
+
+    let root = match root {
+        Some(root) if !root.trim().is_empty() => expand_home(root.trim())?,
+        _ => default_worktree_root(&repo)?,
+    };
+    let path = root.join(&name);
+    if path.exists() {
+        return Err(format!("Worktree path {} already exists", path.display()));
+    }
+    fs::create_dir_all(&root)
+        .map_err(|e| format!("Failed to create worktree root {}: {}", root.display(), e))?;
+
+    // Branches created here are deleted again if the worktree cannot be added.
+    let (local, created) = if create_branch {
+        if repo.find_branch(branch, BranchType::Local).is_ok() {
+            return Err(format!("Branch {} already exists", branch));
+        }
+        let base_commit = match base.as_deref().filter(|base| !base.trim().is_empty()) {
+            Some(base) => repo
+                .revparse_single(base)
+                .and_then(|object| object.peel_to_commit())
+                .map_err(|e| format!("Unknown base {}: {}", base, e))?,
+            None => repo
+                .head()
+                .and_then(|head| head.peel_to_commit())
+                .map_err(|e| e.to_string())?,
+        };
+        let local = repo
+            .branch(branch, &base_commit, false)
+            .map_err(|e| format!("Failed to create branch {}: {}", branch, e))?;
+        (local, true)
+    } else {
+        match repo.find_branch(branch, BranchType::Local) {
+            Ok(local) => (local, false),
+            Err(_) => {
+                let remote = repo
+                    .find_branch(&format!("origin/{}", branch), BranchType::Remote)
+                    .map_err(|_| format!("Branch {} was not found", branch))?;
+                let commit = remote.get().peel_to_commit().map_err(|e| e.to_string())?;
+                let mut local = repo
+                    .branch(branch, &commit, false)
+                    .map_err(|e| format!("Failed to create branch {}: {}", branch, e))?;
+                if let Err(error) = local.set_upstream(Some(&format!("origin/{}", branch))) {
+                    let _ = local.delete();
+                    return Err(error.to_string());
+                }
+                (local, true)
+            }
+        }
+    };
+    if local.is_head() {
+        return Err(format!("Branch {} is checked out in the main worktree", branch));
+    }
+
+    let mut reference = local.into_reference();
+    let added = {
+        let mut options = WorktreeAddOptions::new();
+        options.reference(Some(&reference));
+        repo.worktree(&name, &path, Some(&options))
+    };
+    let worktree = match added {
+        Ok(worktree) => worktree,
+        Err(error) => {
+            if created {
+                let _ = reference.delete();
+            }
+            return Err(format!("Failed to create worktree: {}", error));
+        }
+    };
+
+    Ok(describe_worktree(
+        worktree.path(),
+        Some(name),
+        false,
+        false,
+    ))
+}
+
+#[tauri::command]
+pub async fn git_worktree_list(repo_path: String) -> Result<Vec<GitWorktreeInfo>, String> {
+    let repo = open_main_repository(&repo_path)?;
+    list_worktrees(&repo)
+}
+
+/// Removes a linked worktree and its checkout. Dirty worktrees are refused
+/// unless `force` is set; locked worktrees are always refused. The branch is
+/// kept.
+#[tauri::command]
+pub async fn git_worktree_remove(
+    repo_path: String,
+    name: String,
+    force: bool,
+) -> Result<(), String> {
+    let repo = open_main_repository(&repo_path)?;
+    let worktree = repo
+        .find_worktree(&name)
+        .map_err(|_| format!("Worktree {} was not found", name))?;
+
+    if let Ok(WorktreeLockStatus::Locked(reason)) = worktree.is_locked() {
+        return Err(match reason {
+            Some(reason) if !reason.is_empty() => {
+                format!("Worktree {} is locked: {}", name, reason)
+            }
+            _ => format!("Worktree {} is locked", name),
+        });
+    }
+
+    if worktree.validate().is_ok() && !force {
+        let worktree_repo = Repository::open_from_worktree(&worktree).map_err(|e| e.to_string())?;
+        if is_dirty(&worktree_repo).map_err(|e| e.to_string())? {
+            return Err(format!("Worktree {} has uncommitted changes", name));
+        }
+    }
+
+    let mut options = WorktreePruneOptions::new();
+    options.valid(true).working_tree(true);
+    worktree
+        .prune(Some(&mut options))
+        .map_err(|e| format!("Failed to remove worktree {}: {}", name, e))
+}
+
+/// Prunes administrative data for worktrees whose checkout no longer exists
+/// and returns their names.
+#[tauri::command]
+pub async fn git_worktree_prune(repo_path: String) -> Result<Vec<String>, String> {
+    let repo = open_main_repository(&repo_path)?;
+    let names = repo.worktrees().map_err(|e| e.to_string())?;
+    let mut pruned = Vec::new();
+    for name in names.iter().flatten() {
+        let Ok(worktree) = repo.find_worktree(name) else {
+            continue;
+        };
+        if !worktree.is_prunable(None).unwrap_or(false) {
+            continue;
+        }
+        worktree
+            .prune(None)
+            .map_err(|e| format!("Failed to prune worktree {}: {}", name, e))?;
+        pruned.push(name.to_string());
+    }
+    Ok(pruned)
+}
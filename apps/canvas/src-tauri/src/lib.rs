@@ -3,9 +3,25 @@ mod cleanup;
 mod debug_log;
 mod ghostty;
 mod ghostty_vt;
+mod ghostty_vt_frame;
+mod git;
 mod native_terminal;
+mod process_command;
+mod process_metrics;
 mod runtime;
+mod runtime_log;
+mod shell_env;
+mod shell_integration;
+mod terminal_links;
+mod terminal_paste;
+mod terminal_recording;
+mod workspace_automation;
+mod workspace_env;
 mod workspace_file;
+mod workspace_history;
+mod workspace_layers;
+pub mod workspace_schema;
+mod workspace_services;
 
 use browser::{
     browser_create_block, browser_destroy_block, browser_navigate_block, browser_reload_block,
@@ -19,21 +35,46 @@ use ghostty::{
     ghostty_update_block, GhosttyManager,
 };
 use ghostty_vt::{
-    ghostty_vt_create_session, ghostty_vt_destroy_session, ghostty_vt_input_key,
-    ghostty_vt_resize_session, ghostty_vt_scroll_viewport, ghostty_vt_send_text,
-    ghostty_vt_snapshot_session, ghostty_vt_status, GhosttyVtManager,
+    ghostty_vt_command_records, ghostty_vt_create_replay_session, ghostty_vt_create_session,
+    ghostty_vt_destroy_session, ghostty_vt_export_scrollback, ghostty_vt_input_key,
+    ghostty_vt_jump_to_prompt, ghostty_vt_open_link, ghostty_vt_paste, ghostty_vt_resize_session,
+    ghostty_vt_scroll_viewport, ghostty_vt_search, ghostty_vt_search_navigate,
+    ghostty_vt_send_text, ghostty_vt_session_info, ghostty_vt_snapshot_delta,
+    ghostty_vt_snapshot_session, ghostty_vt_start_recording, ghostty_vt_status,
+    ghostty_vt_stop_recording, GhosttyVtManager,
 };
+use git::{git_worktree_create, git_worktree_list, git_worktree_prune, git_worktree_remove};
 use native_terminal::{
     native_terminal_create_block, native_terminal_destroy_block, native_terminal_status,
     native_terminal_update_block, NativeTerminalManager,
 };
+use process_metrics::{canvas_process_metrics, ProcessMetricsManager};
 use runtime::{
     workspace_get_runtime, workspace_list_runtimes, workspace_read_runtime_log,
     workspace_start_runtime, workspace_stop_all_runtimes, workspace_stop_runtime,
     WorkspaceRuntimeManager,
 };
+use runtime_log::{
+    workspace_list_runtime_logs, workspace_read_runtime_log_generation,
+    workspace_read_runtime_log_page, workspace_subscribe_runtime_log,
+    workspace_unsubscribe_runtime_log, RuntimeLogManager,
+};
 use tauri::Manager;
-use workspace_file::{workspace_file_exists, workspace_file_read, workspace_file_write};
+use workspace_automation::workspace_run_ensure_steps;
+use workspace_env::{workspace_interpolate, workspace_resolve_env};
+use workspace_services::{
+    workspace_list_services, workspace_restart_service, workspace_start_service,
+    workspace_start_services_on_open, workspace_stop_service, WorkspaceServiceManager,
+};
+use workspace_file::{
+    workspace_file_exists, workspace_file_read, workspace_file_read_versioned,
+    workspace_file_unwatch, workspace_file_validate, workspace_file_watch, workspace_file_write,
+    WorkspaceFileWatcherManager,
+};
+use workspace_history::{
+    workspace_file_history_diff, workspace_file_history_list, workspace_file_history_restore,
+};
+use workspace_layers::{workspace_file_base_layer, workspace_file_read_merged};
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -44,11 +85,18 @@ pub fn run() {
     let ghostty_vt_manager_for_setup = ghostty_vt_manager.clone();
     let native_terminal_manager = NativeTerminalManager::default();
     let runtime_manager = WorkspaceRuntimeManager::default();
+    let runtime_log_manager = RuntimeLogManager::default();
+    let process_metrics_manager = ProcessMetricsManager::default();
+    let service_manager = WorkspaceServiceManager::default();
+    let workspace_file_watcher = WorkspaceFileWatcherManager::default();
     let cleanup_service = AppCleanupService::new(
         ghostty_manager.clone(),
         ghostty_vt_manager.clone(),
         native_terminal_manager.clone(),
         runtime_manager.clone(),
+        runtime_log_manager.clone(),
+        service_manager.clone(),
+        workspace_file_watcher.clone(),
     );
     let cleanup_service_for_events = cleanup_service.clone();
 
@@ -58,11 +106,16 @@ pub fn run() {
         .manage(ghostty_vt_manager)
         .manage(native_terminal_manager)
         .manage(runtime_manager)
+        .manage(runtime_log_manager)
+        .manage(process_metrics_manager)
+        .manage(service_manager)
+        .manage(workspace_file_watcher)
         .invoke_handler(tauri::generate_handler![
             ghostty_status,
             ghostty_vt_status,
             native_terminal_status,
             canvas_debug_log,
+            canvas_process_metrics,
             canvas_set_pending_shortcut_mode,
             ghostty_create_block,
             ghostty_update_block,
@@ -73,9 +126,21 @@ pub fn run() {
             ghostty_vt_create_session,
             ghostty_vt_resize_session,
             ghostty_vt_send_text,
+            ghostty_vt_paste,
             ghostty_vt_input_key,
             ghostty_vt_scroll_viewport,
             ghostty_vt_snapshot_session,
+            ghostty_vt_snapshot_delta,
+            ghostty_vt_search,
+            ghostty_vt_search_navigate,
+            ghostty_vt_export_scrollback,
+            ghostty_vt_start_recording,
+            ghostty_vt_stop_recording,
+            ghostty_vt_create_replay_session,
+            ghostty_vt_command_records,
+            ghostty_vt_jump_to_prompt,
+            ghostty_vt_session_info,
+            ghostty_vt_open_link,
             ghostty_vt_destroy_session,
             native_terminal_create_block,
             native_terminal_update_block,
@@ -90,11 +155,48 @@ pub fn run() {
             workspace_stop_runtime,
             workspace_stop_all_runtimes,
             workspace_read_runtime_log,
+            workspace_read_runtime_log_page,
+            workspace_subscribe_runtime_log,
+            workspace_unsubscribe_runtime_log,
+            workspace_list_runtime_logs,
+            workspace_read_runtime_log_generation,
             workspace_list_runtimes,
             workspace_file_exists,
             workspace_file_read,
+            workspace_file_read_versioned,
+            workspace_file_base_layer,
+            workspace_file_read_merged,
             workspace_file_write,
+            workspace_file_validate,
+            workspace_file_watch,
+            workspace_file_unwatch,
+            workspace_file_history_list,
+            workspace_file_history_diff,
+            workspace_file_history_restore,
+            workspace_run_ensure_steps,
+            workspace_resolve_env,
+            workspace_interpolate,
+            workspace_start_service,
+            workspace_start_services_on_open,
+            workspace_stop_service,
+            workspace_restart_service,
+            workspace_list_services,
+            git_worktree_create,
+            git_worktree_list,
+            git_worktree_remove,
+            git_worktree_prune,
         ])
+        .register_asynchronous_uri_scheme_protocol("ghostty-vt", |ctx, request, responder| {
+            let app_handle = ctx.app_handle().clone();
+            let webview_label = ctx.webview_label().to_string();
+            tauri::async_runtime::spawn_blocking(move || {
+                responder.respond(ghostty_vt::frame_protocol_response(
+                    &app_handle,
+                    &webview_label,
+                    &request,
+                ))
+            });
+        })
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
@@ -106,6 +208,9 @@ pub fn run() {
             ghostty::register_app_handle(app.handle().clone());
             ghostty::register_manager(&ghostty_manager_for_setup);
             ghostty_vt::register_manager(&ghostty_vt_manager_for_setup);
+            app.state::<WorkspaceRuntimeManager>().restore(app.handle());
+            app.state::<WorkspaceServiceManager>().restore(app.handle());
+            process_metrics::start_sampler(app.handle().clone());
 
             let window = app.get_webview_window("main").unwrap();
             debug_log("app", format!("main window ready label={}", window.label()));
@@ -0,0 +1,111 @@
+// Only used for the foreground process of libghostty-vt sessions.
+#![cfg_attr(not(otto_canvas_libghostty_vt), allow(dead_code))]
+
+/// Executable name and arguments of a running process.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProcessCommand {
+    pub name: Option<String>,
+    pub argv: Vec<String>,
+}
+
+/// Splits NUL-terminated strings. Empty strings are kept so the count
+/// matches argc.
+fn split_nul_separated(bytes: &[u8]) -> Vec<String> {
+    if bytes.is_empty() {
+        return Vec::new();
+    }
+    bytes
+        .strip_suffix(&[0])
+        .unwrap_or(bytes)
+        .split(|byte| *byte == 0)
+        .map(|arg| String::from_utf8_lossy(arg).into_owned())
+        .collect()
+}
+
+pub fn process_command(pid: u32) -> Option<ProcessCommand> {
+    imp::process_command(pid)
+}
+
+#[cfg(target_os = "linux")]
+mod imp {
+    use super::{split_nul_separated, ProcessCommand};
+    use std::fs;
+
+    pub(super) fn process_command(pid: u32) -> Option<ProcessCommand> {
+        let name = fs::read_to_string(format!("/proc/{pid}/comm")).ok()?;
+        // Kernel threads and zombies have an empty command line.
+        let argv = fs::read(format!("/proc/{pid}/cmdline"))
+            .map(|bytes| split_nul_separated(&bytes))
+            .unwrap_or_default();
+        Some(ProcessCommand {
+            name: Some(name.trim_end().to_string()).filter(|name| !name.is_empty()),
+            argv,
+        })
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod imp {
+    use super::{split_nul_separated, ProcessCommand};
+    use std::{mem, path::Path, ptr};
+
+    /// Reads `KERN_PROCARGS2`: argc, the executable path, NUL padding, then
+    /// argv and the environment as NUL-separated strings.
+    pub(super) fn process_command(pid: u32) -> Option<ProcessCommand> {
+        let mut mib = [libc::CTL_KERN, libc::KERN_PROCARGS2, pid as libc::c_int];
+        let mut size: libc::size_t = 0;
+        let result = unsafe {
+            libc::sysctl(
+                mib.as_mut_ptr(),
+                mib.len() as libc::c_uint,
+                ptr::null_mut(),
+                &mut size,
+                ptr::null_mut(),
+                0,
+            )
+        };
+        if result != 0 || size <= mem::size_of::<libc::c_int>() {
+            return None;
+        }
+        let mut buffer = vec![0_u8; size];
+        let result = unsafe {
+            libc::sysctl(
+                mib.as_mut_ptr(),
+                mib.len() as libc::c_uint,
+                buffer.as_mut_ptr().cast(),
+                &mut size,
+                ptr::null_mut(),
+                0,
+            )
+        };
+        if result != 0 {
+            return None;
+        }
+        buffer.truncate(size);
+
+        let (argc, rest) = buffer.split_at_checked(mem::size_of::<libc::c_int>())?;
+        let argc = libc::c_int::from_ne_bytes(argc.try_into().ok()?).max(0) as usize;
+        let exec_end = rest.iter().position(|byte| *byte == 0)?;
+        let exec_path = String::from_utf8_lossy(&rest[..exec_end]).into_owned();
+        // The executable path is padded with NULs up to argv[0].
+        let args_start = rest[exec_end..]
+            .iter()
+            .position(|byte| *byte != 0)
+            .map_or(rest.len(), |padding| exec_end + padding);
+        let mut argv = split_nul_separated(&rest[args_start..]);
+        argv.truncate(argc);
+        let name = Path::new(&exec_path)
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned());
+        Some(ProcessCommand { name, argv })
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+mod imp {
+    use super::ProcessCommand;
+
+    pub(super) fn process_command(_pid: u32) -> Option<ProcessCommand> {
+        None
+    }
+}
@@ -0,0 +1,344 @@
+use crate::{debug_log::debug_log, ghostty_vt::GhosttyVtManager, runtime::WorkspaceRuntimeManager};
+use serde::Serialize;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    thread::sleep,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use tauri::{AppHandle, Emitter, Manager, Wry};
+
+const METRICS_EVENT: &str = "canvas-process-metrics";
+const METRICS_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase",
+    tag = "kind"
+)]
+pub enum ProcessOwner {
+    Runtime {
+        workspace_id: String,
+        environment_id: String,
+    },
+    Terminal {
+        session_id: String,
+    },
+}
+
+/// Totals for a root process and all of its descendants.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessTreeMetrics {
+    #[serde(flatten)]
+    pub owner: ProcessOwner,
+    pub pid: u32,
+    pub cpu_percent: f64,
+    pub rss_bytes: u64,
+    pub threads: u64,
+    pub children: u32,
+    pub read_bytes: u64,
+    pub write_bytes: u64,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CanvasProcessMetrics {
+    pub supported: bool,
+    pub sampled_at_ms: u64,
+    pub processes: Vec<ProcessTreeMetrics>,
+}
+
+/// CPU percentages are deltas, so the previous tick count of every root is
+/// kept between samples.
+#[derive(Clone, Default)]
+pub struct ProcessMetricsManager {
+    previous: Arc<Mutex<HashMap<u32, (u64, Instant)>>>,
+}
+
+impl ProcessMetricsManager {
+    pub fn sample(
+        &self,
+        runtime_manager: &WorkspaceRuntimeManager,
+        ghostty_vt_manager: &GhosttyVtManager,
+    ) -> CanvasProcessMetrics {
+        let mut roots: Vec<(ProcessOwner, u32)> = runtime_manager
+            .runtime_pids()
+            .into_iter()
+            .map(|(workspace_id, environment_id, pid)| {
+                (
+                    ProcessOwner::Runtime {
+                        workspace_id,
+                        environment_id,
+                    },
+                    pid,
+                )
+            })
+            .collect();
+        roots.extend(
+            ghostty_vt_manager
+                .session_pids()
+                .into_iter()
+                .map(|(session_id, pid)| (ProcessOwner::Terminal { session_id }, pid)),
+        );
+
+        let sampled_at_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis() as u64)
+            .unwrap_or(0);
+        // Building the table reads all of /proc; skip it while nothing is
+        // tracked.
+        if roots.is_empty() {
+            if let Ok(mut previous) = self.previous.lock() {
+                previous.clear();
+            }
+            return CanvasProcessMetrics {
+                supported: imp::SUPPORTED,
+                sampled_at_ms,
+                processes: Vec::new(),
+            };
+        }
+        let Some(table) = imp::process_table() else {
+            return CanvasProcessMetrics {
+                supported: false,
+                sampled_at_ms,
+                processes: Vec::new(),
+            };
+        };
+
+        let now = Instant::now();
+        let mut previous = match self.previous.lock() {
+            Ok(previous) => previous,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        let mut next = HashMap::new();
+        let processes = roots
+            .into_iter()
+            .filter_map(|(owner, pid)| {
+                let tree = imp::process_tree(&table, pid)?;
+                let cpu_percent = match previous.get(&pid) {
+                    Some((ticks, at)) => {
+                        let elapsed = now.duration_since(*at).as_secs_f64();
+                        let delta = tree.cpu_ticks.saturating_sub(*ticks) as f64;
+                        if elapsed > 0.0 {
+                            delta / imp::clock_ticks_per_second() / elapsed * 100.0
+                        } else {
+                            0.0
+                        }
+                    }
+                    None => 0.0,
+                };
+                next.insert(pid, (tree.cpu_ticks, now));
+                Some(ProcessTreeMetrics {
+                    owner,
+                    pid,
+                    cpu_percent,
+                    rss_bytes: tree.rss_bytes,
+                    threads: tree.threads,
+                    children: tree.children,
+                    read_bytes: tree.read_bytes,
+                    write_bytes: tree.write_bytes,
+                })
+            })
+            .collect();
+        *previous = next;
+
+        CanvasProcessMetrics {
+            supported: true,
+            sampled_at_ms,
+            processes,
+        }
+    }
+}
+
+fn sample_app(app_handle: &AppHandle<Wry>) -> CanvasProcessMetrics {
+    app_handle.state::<ProcessMetricsManager>().sample(
+        &app_handle.state::<WorkspaceRuntimeManager>(),
+        &app_handle.state::<GhosttyVtManager>(),
+    )
+}
+
+/// Emits `canvas-process-metrics` on a fixed interval for the lifetime of the
+/// app.
+pub(crate) fn start_sampler(app_handle: AppHandle<Wry>) {
+    let spawned = std::thread::Builder::new()
+        .name("otto-process-metrics".to_string())
+        .spawn(move || loop {
+            let metrics = sample_app(&app_handle);
+            if !metrics.supported {
+                return;
+            }
+            let _ = app_handle.emit(METRICS_EVENT, metrics);
+            sleep(METRICS_INTERVAL);
+        });
+    if let Err(error) = spawned {
+        debug_log("metrics", format!("failed to spawn sampler: {error}"));
+    }
+}
+
+#[tauri::command]
+pub async fn canvas_process_metrics(
+    app_handle: AppHandle<Wry>,
+) -> Result<CanvasProcessMetrics, String> {
+    // Sampling reads /proc for every tracked tree.
+    tauri::async_runtime::spawn_blocking(move || sample_app(&app_handle))
+        .await
+        .map_err(|error| format!("Process metrics did not complete: {}", error))
+}
+
+#[derive(Default)]
+struct TreeTotals {
+    cpu_ticks: u64,
+    rss_bytes: u64,
+    threads: u64,
+    children: u32,
+    read_bytes: u64,
+    write_bytes: u64,
+}
+
+#[cfg(target_os = "linux")]
+mod imp {
+    use super::TreeTotals;
+    use std::{collections::HashMap, fs};
+
+    /// Parent pid and cumulative user+system ticks from `/proc/<pid>/stat`,
+    /// including those of children the process has reaped so the ticks of
+    /// exited descendants are not lost from the tree's total.
+    struct ProcStat {
+        ppid: u32,
+        cpu_ticks: u64,
+    }
+
+    pub(super) const SUPPORTED: bool = true;
+
+    pub(super) struct ProcessTable {
+        stats: HashMap<u32, ProcStat>,
+        children: HashMap<u32, Vec<u32>>,
+    }
+
+    pub(super) fn clock_ticks_per_second() -> f64 {
+        let ticks = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+        if ticks > 0 {
+            ticks as f64
+        } else {
+            100.0
+        }
+    }
+
+    fn read_stat(pid: u32) -> Option<ProcStat> {
+        let content = fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+        // The command name may contain spaces or parens; fields resume after
+        // the last ')'.
+        let rest = &content[content.rfind(')')? + 1..];
+        let fields: Vec<&str> = rest.split_whitespace().collect();
+        // fields[0] is state (field 3), so field N lives at index N - 3.
+        let ppid = fields.get(1)?.parse().ok()?;
+        let utime: u64 = fields.get(11)?.parse().ok()?;
+        let stime: u64 = fields.get(12)?.parse().ok()?;
+        let cutime: u64 = fields.get(13)?.parse().ok()?;
+        let cstime: u64 = fields.get(14)?.parse().ok()?;
+        Some(ProcStat {
+            ppid,
+            cpu_ticks: utime + stime + cutime + cstime,
+        })
+    }
+
+    fn read_status(pid: u32) -> (u64, u64) {
+        let Ok(content) = fs::read_to_string(format!("/proc/{pid}/status")) else {
+            return (0, 0);
+        };
+        let mut rss_bytes = 0;
+        let mut threads = 0;
+        for line in content.lines() {
+            if let Some(value) = line.strip_prefix("VmRSS:") {
+                let kib: u64 = value
+                    .trim()
+                    .trim_end_matches("kB")
+                    .trim()
+                    .parse()
+                    .unwrap_or(0);
+                rss_bytes = kib * 1024;
+            } else if let Some(value) = line.strip_prefix("Threads:") {
+                threads = value.trim().parse().unwrap_or(0);
+            }
+        }
+        (rss_bytes, threads)
+    }
+
+    fn read_io(pid: u32) -> (u64, u64) {
+        let Ok(content) = fs::read_to_string(format!("/proc/{pid}/io")) else {
+            return (0, 0);
+        };
+        let mut read_bytes = 0;
+        let mut write_bytes = 0;
+        for line in content.lines() {
+            if let Some(value) = line.strip_prefix("read_bytes:") {
+                read_bytes = value.trim().parse().unwrap_or(0);
+            } else if let Some(value) = line.strip_prefix("write_bytes:") {
+                write_bytes = value.trim().parse().unwrap_or(0);
+            }
+        }
+        (read_bytes, write_bytes)
+    }
+
+    pub(super) fn process_table() -> Option<ProcessTable> {
+        let stats: HashMap<u32, ProcStat> = fs::read_dir("/proc")
+            .ok()?
+            .flatten()
+            .filter_map(|entry| entry.file_name().to_str()?.parse::<u32>().ok())
+            .filter_map(|pid| Some((pid, read_stat(pid)?)))
+            .collect();
+        let mut children: HashMap<u32, Vec<u32>> = HashMap::new();
+        for (pid, stat) in &stats {
+            children.entry(stat.ppid).or_default().push(*pid);
+        }
+        Some(ProcessTable { stats, children })
+    }
+
+    pub(super) fn process_tree(table: &ProcessTable, root: u32) -> Option<TreeTotals> {
+        table.stats.get(&root)?;
+        let mut totals = TreeTotals::default();
+        let mut pending = vec![root];
+        while let Some(pid) = pending.pop() {
+            let Some(stat) = table.stats.get(&pid) else {
+                continue;
+            };
+            let (rss_bytes, threads) = read_status(pid);
+            let (read_bytes, write_bytes) = read_io(pid);
+            totals.cpu_ticks += stat.cpu_ticks;
+            totals.rss_bytes += rss_bytes;
+            totals.threads += threads;
+            totals.read_bytes += read_bytes;
+            totals.write_bytes += write_bytes;
+            if pid != root {
+                totals.children += 1;
+            }
+            if let Some(descendants) = table.children.get(&pid) {
+                pending.extend(descendants);
+            }
+        }
+        Some(totals)
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod imp {
+    use super::TreeTotals;
+
+    pub(super) const SUPPORTED: bool = false;
+
+    pub(super) struct ProcessTable;
+
+    pub(super) fn clock_ticks_per_second() -> f64 {
+        100.0
+    }
+
+    pub(super) fn process_table() -> Option<ProcessTable> {
+        None
+    }
+
+    pub(super) fn process_tree(_table: &ProcessTable, _root: u32) -> Option<TreeTotals> {
+        None
+    }
+}
@@ -1,18 +1,60 @@
-use serde::Serialize;
-use std::collections::HashMap;
-use std::fs::{read_to_string, OpenOptions};
+use crate::runtime_log::{
+    open_child_log, prepare_runtime_log_path, read_log_tail, rotate_oversized_log, set_log_secrets,
+    strip_ansi, RuntimeLogOptions,
+};
+use crate::workspace_env::{reserve_runtime_port, resolve_env_for, ResolvedEnv, WorkspaceEnvSpec};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::fs::{self, read_to_string};
 use std::io::{Read, Write};
-use std::net::{TcpListener, TcpStream};
-use std::path::PathBuf;
+use std::net::TcpStream;
+use std::path::{Path, PathBuf};
 use std::process::{Child, Command, Stdio};
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, OnceLock};
 use std::thread::sleep;
 use std::time::{Duration, Instant};
-use tauri::{AppHandle, Manager, State, Wry};
+use tauri::{ipc::Channel, AppHandle, Manager, State, Wry};
 
 #[cfg(unix)]
 use std::os::unix::process::CommandExt;
 
+const RUNTIME_READY_TIMEOUT: Duration = Duration::from_secs(30);
+const RUNTIME_LOG_TAIL_LINES: usize = 80;
+const RESTART_BACKOFF_INITIAL: Duration = Duration::from_secs(1);
+const RESTART_BACKOFF_MAX: Duration = Duration::from_secs(30);
+const RESTART_STABLE_AFTER: Duration = Duration::from_secs(60);
+const CRASH_LOOP_WINDOW: Duration = Duration::from_secs(120);
+const CRASH_LOOP_MAX_RESTARTS: usize = 5;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum WorkspaceRuntimeStatus {
+    Starting,
+    Ready,
+    Restarting,
+    Failed,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum WorkspaceRestartPolicy {
+    Never,
+    #[default]
+    OnFailure,
+    Always,
+}
+
+impl WorkspaceRestartPolicy {
+    pub(crate) fn should_restart(self, exit_code: Option<i32>) -> bool {
+        match self {
+            Self::Never => false,
+            Self::OnFailure => exit_code != Some(0),
+            Self::Always => true,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct WorkspaceRuntimeInfo {
@@ -23,19 +65,264 @@ pub struct WorkspaceRuntimeInfo {
     pub port: u16,
     pub url: String,
     pub log_path: String,
+    pub status: WorkspaceRuntimeStatus,
+    pub restart_policy: WorkspaceRestartPolicy,
+    pub restart_count: u32,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase",
+    tag = "event",
+    content = "data"
+)]
+pub enum WorkspaceRuntimeEvent {
+    Starting {
+        info: WorkspaceRuntimeInfo,
+    },
+    Ready {
+        info: WorkspaceRuntimeInfo,
+    },
+    Restarting {
+        info: WorkspaceRuntimeInfo,
+        exit_code: Option<i32>,
+        log_tail: String,
+        delay_ms: u64,
+    },
+    Failed {
+        info: WorkspaceRuntimeInfo,
+        error: String,
+        exit_code: Option<i32>,
+        log_tail: String,
+    },
+    Exited {
+        info: WorkspaceRuntimeInfo,
+        exit_code: Option<i32>,
+        log_tail: String,
+    },
+}
+
+#[derive(Clone)]
+struct RuntimeLaunch {
+    binary: PathBuf,
+    project_path: String,
+    port: u16,
+    log_path: String,
+    log_options: RuntimeLogOptions,
+    path_env: String,
+    env: Vec<(String, String)>,
+    env_spec: Option<WorkspaceEnvSpec>,
+    secrets: Vec<String>,
+}
+
+#[derive(Default)]
+pub(crate) struct RestartTracker {
+    recent: VecDeque<Instant>,
+    consecutive: u32,
+    pub(crate) ready_since: Option<Instant>,
+}
+
+impl RestartTracker {
+    /// Returns the backoff before the next restart, or `None` once the
+    /// runtime has crashed too often inside `CRASH_LOOP_WINDOW`.
+    pub(crate) fn next_delay(&mut self) -> Option<Duration> {
+        let now = Instant::now();
+        if self
+            .ready_since
+            .take()
+            .is_some_and(|since| now.duration_since(since) >= RESTART_STABLE_AFTER)
+        {
+            self.consecutive = 0;
+        }
+        while self
+            .recent
+            .front()
+            .is_some_and(|at| now.duration_since(*at) > CRASH_LOOP_WINDOW)
+        {
+            self.recent.pop_front();
+        }
+        if self.recent.len() >= CRASH_LOOP_MAX_RESTARTS {
+            return None;
+        }
+        self.recent.push_back(now);
+
+        let delay = RESTART_BACKOFF_INITIAL
+            .saturating_mul(1 << self.consecutive.min(5))
+            .min(RESTART_BACKOFF_MAX);
+        self.consecutive += 1;
+        Some(delay)
+    }
+}
+
+enum RuntimeProcess {
+    Spawned(Child),
+    /// A runtime left running by a previous canvas session. It is not our
+    /// child, so it can only be signalled and polled, never waited on.
+    Adopted(u32),
+}
+
+impl RuntimeProcess {
+    fn try_exit(&mut self) -> std::io::Result<Option<Option<i32>>> {
+        match self {
+            Self::Spawned(child) => child
+                .try_wait()
+                .map(|status| status.map(|status| status.code())),
+            Self::Adopted(pid) => Ok((!process_alive(*pid)).then_some(None)),
+        }
+    }
+
+    fn terminate(&mut self) -> Option<i32> {
+        match self {
+            Self::Spawned(child) => {
+                kill_process_tree(child);
+                child.try_wait().ok().flatten().and_then(|status| status.code())
+            }
+            Self::Adopted(pid) => {
+                kill_process_group(*pid);
+                None
+            }
+        }
+    }
+}
+
+/// Lifecycle channel of the frontend that last started or attached to a
+/// runtime. Adopted runtimes have none until the UI asks for them again.
+#[derive(Clone, Default)]
+struct RuntimeEvents(Option<Channel<WorkspaceRuntimeEvent>>);
+
+impl RuntimeEvents {
+    fn send(&self, event: WorkspaceRuntimeEvent) {
+        if let Some(channel) = &self.0 {
+            let _ = channel.send(event);
+        }
+    }
 }
 
 struct WorkspaceRuntimeEntry {
-    child: Child,
+    /// `None` while the supervisor is waiting out a restart backoff.
+    process: Option<RuntimeProcess>,
     info: WorkspaceRuntimeInfo,
+    events: RuntimeEvents,
+    /// The process appends its output to `launch.log_path` itself, so an
+    /// adopted runtime keeps writing to the same log after a restart.
+    launch: RuntimeLaunch,
+    restarts: RestartTracker,
+}
+
+/// Runtimes are tracked per environment so every worktree of a workspace can
+/// serve at the same time.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct RuntimeKey {
+    workspace_id: String,
+    environment_id: String,
+}
+
+impl RuntimeKey {
+    fn new(workspace_id: &str, environment_id: &str) -> Self {
+        Self {
+            workspace_id: workspace_id.to_string(),
+            environment_id: environment_id.to_string(),
+        }
+    }
+
+    fn matches(&self, workspace_id: Option<&str>, environment_id: Option<&str>) -> bool {
+        workspace_id.is_none_or(|id| id == self.workspace_id)
+            && environment_id.is_none_or(|id| id == self.environment_id)
+    }
+}
+
+impl fmt::Display for RuntimeKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.workspace_id, self.environment_id)
+    }
 }
 
+type RuntimeMap = Arc<Mutex<HashMap<RuntimeKey, WorkspaceRuntimeEntry>>>;
+
+/// Runtime processes outlive a crashed canvas because each one leads its own
+/// session, so the pid, process group and port of every runtime are mirrored
+/// here to let the next launch adopt or reap them.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RuntimeRegistryEntry {
+    workspace_id: String,
+    environment_id: String,
+    project_path: String,
+    pid: u32,
+    pgid: i32,
+    port: u16,
+    log_path: String,
+    binary: PathBuf,
+    restart_policy: WorkspaceRestartPolicy,
+    restart_count: u32,
+    #[serde(default)]
+    log_options: RuntimeLogOptions,
+    /// The env as requested, never the resolved values, so secrets from env
+    /// files stay off disk.
+    #[serde(default)]
+    env_spec: Option<WorkspaceEnvSpec>,
+}
+
+static RUNTIME_REGISTRY_PATH: OnceLock<PathBuf> = OnceLock::new();
+
 #[derive(Clone, Default)]
 pub struct WorkspaceRuntimeManager {
-    inner: Arc<Mutex<HashMap<String, WorkspaceRuntimeEntry>>>,
+    inner: RuntimeMap,
+}
+
+pub(crate) fn process_alive(pid: u32) -> bool {
+    #[cfg(unix)]
+    {
+        unsafe { libc::kill(pid as i32, 0) == 0 }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = pid;
+        false
+    }
+}
+
+pub(crate) fn process_group_alive(pgid: i32) -> bool {
+    #[cfg(unix)]
+    {
+        pgid > 0 && unsafe { libc::kill(-pgid, 0) == 0 }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = pgid;
+        false
+    }
 }
 
-fn kill_process_tree(child: &mut Child) {
+pub(crate) fn kill_process_group(pgid: u32) {
+    #[cfg(unix)]
+    {
+        let pgid = pgid as i32;
+        if pgid <= 0 {
+            return;
+        }
+        unsafe {
+            libc::kill(-pgid, libc::SIGTERM);
+        }
+        let start = Instant::now();
+        while process_group_alive(pgid) {
+            if start.elapsed() >= Duration::from_secs(3) {
+                unsafe {
+                    libc::kill(-pgid, libc::SIGKILL);
+                }
+                break;
+            }
+            sleep(Duration::from_millis(50));
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = pgid;
+    }
+}
+
+pub(crate) fn kill_process_tree(child: &mut Child) {
     #[cfg(unix)]
     {
         let pid = child.id() as i32;
@@ -72,16 +359,162 @@ impl WorkspaceRuntimeManager {
         self.inner.lock().ok().map(|runtimes| runtimes.len()).unwrap_or(0)
     }
 
+    /// Live runtime processes as (workspace id, environment id, pid).
+    pub fn runtime_pids(&self) -> Vec<(String, String, u32)> {
+        let Ok(runtimes) = self.inner.lock() else {
+            return Vec::new();
+        };
+        runtimes
+            .iter()
+            .filter(|(_, entry)| entry.process.is_some())
+            .map(|(key, entry)| {
+                (
+                    key.workspace_id.clone(),
+                    key.environment_id.clone(),
+                    entry.info.pid,
+                )
+            })
+            .collect()
+    }
+
     pub fn stop_all(&self) {
-        if let Ok(mut runtimes) = self.inner.lock() {
-            for (id, entry) in runtimes.iter_mut() {
-                eprintln!(
-                    "[canvas] stopping otto runtime workspace={} pid={}",
-                    id, entry.info.pid
-                );
-                kill_process_tree(&mut entry.child);
+        let entries: Vec<(RuntimeKey, WorkspaceRuntimeEntry)> = match self.inner.lock() {
+            Ok(mut runtimes) => {
+                let entries = runtimes.drain().collect();
+                persist_registry(&runtimes);
+                entries
             }
-            runtimes.clear();
+            Err(_) => return,
+        };
+        for (key, entry) in entries {
+            stop_runtime_entry(&key, entry);
+        }
+    }
+
+    /// Adopts runtimes that survived a previous canvas session and reaps the
+    /// process groups of those that no longer answer.
+    pub fn restore(&self, app_handle: &AppHandle<Wry>) {
+        let registry_path = match app_handle.path().app_data_dir() {
+            Ok(dir) => dir.join("runtime-registry.json"),
+            Err(error) => {
+                eprintln!("[canvas] runtime registry unavailable: {}", error);
+                return;
+            }
+        };
+        if RUNTIME_REGISTRY_PATH.set(registry_path.clone()).is_err() {
+            return;
+        }
+
+        let records: Vec<RuntimeRegistryEntry> = fs::read(&registry_path)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default();
+        if records.is_empty() {
+            return;
+        }
+
+        // Readiness probes can take seconds per record, so they run off the
+        // setup thread and without holding the runtimes lock.
+        let runtimes = self.inner.clone();
+        let spawned = std::thread::Builder::new()
+            .name("otto-runtime-restore".to_string())
+            .spawn(move || adopt_runtimes(&runtimes, records));
+        if let Err(error) = spawned {
+            eprintln!("[canvas] failed to spawn runtime restore: {}", error);
+        }
+    }
+}
+
+fn adopt_runtimes(runtimes: &RuntimeMap, records: Vec<RuntimeRegistryEntry>) {
+    let path_env = runtime_path_env();
+    let live: Vec<(RuntimeRegistryEntry, ResolvedEnv)> = records
+        .into_iter()
+        .filter(|record| {
+            let alive = runtime_record_alive(record) && runtime_http_ready(record.port);
+            if !alive {
+                reap_stale_runtime(record);
+            }
+            alive
+        })
+        .map(|record| {
+            // Resolved again so a restart gets the same variables and env
+            // files as the original launch.
+            let env = resolve_env_for(record.env_spec.clone(), &record.project_path)
+                .unwrap_or_else(|error| {
+                    eprintln!(
+                        "[canvas] runtime env unavailable workspace={} error={}",
+                        record.workspace_id, error
+                    );
+                    ResolvedEnv::default()
+                });
+            (record, env)
+        })
+        .collect();
+
+    let mut adopted = Vec::new();
+    let mut superseded = Vec::new();
+    let Ok(mut locked) = runtimes.lock() else {
+        return;
+    };
+    for (record, env) in live {
+        let key = RuntimeKey::new(&record.workspace_id, &record.environment_id);
+        // The UI started this environment while the probes ran; the old
+        // runtime would only hold its port.
+        if locked.contains_key(&key) {
+            superseded.push(record);
+            continue;
+        }
+
+        eprintln!(
+            "[canvas] adopting otto runtime workspace={} pid={} port={}",
+            record.workspace_id, record.pid, record.port
+        );
+        adopted.push((key.clone(), record.pid));
+        set_log_secrets(Path::new(&record.log_path), env.secrets());
+        locked.insert(
+            key,
+            WorkspaceRuntimeEntry {
+                process: Some(RuntimeProcess::Adopted(record.pid)),
+                info: WorkspaceRuntimeInfo {
+                    workspace_id: record.workspace_id,
+                    environment_id: record.environment_id,
+                    project_path: record.project_path.clone(),
+                    pid: record.pid,
+                    port: record.port,
+                    url: format!("http://localhost:{}", record.port),
+                    log_path: record.log_path.clone(),
+                    status: WorkspaceRuntimeStatus::Ready,
+                    restart_policy: record.restart_policy,
+                    restart_count: record.restart_count,
+                },
+                events: RuntimeEvents::default(),
+                launch: RuntimeLaunch {
+                    binary: record.binary,
+                    project_path: record.project_path,
+                    port: record.port,
+                    log_path: record.log_path,
+                    log_options: record.log_options,
+                    path_env: path_env.clone(),
+                    env: env.pairs(),
+                    env_spec: record.env_spec,
+                    secrets: env.secrets(),
+                },
+                restarts: RestartTracker {
+                    ready_since: Some(Instant::now()),
+                    ..RestartTracker::default()
+                },
+            },
+        );
+    }
+    persist_registry(&locked);
+    drop(locked);
+
+    for record in &superseded {
+        reap_stale_runtime(record);
+    }
+    for (key, pid) in adopted {
+        if let Err(error) = spawn_supervisor(runtimes, key, pid) {
+            eprintln!("[canvas] {}", error);
         }
     }
 }
@@ -92,6 +525,160 @@ impl Drop for WorkspaceRuntimeManager {
     }
 }
 
+fn registry_entry(entry: &WorkspaceRuntimeEntry) -> RuntimeRegistryEntry {
+    RuntimeRegistryEntry {
+        workspace_id: entry.info.workspace_id.clone(),
+        environment_id: entry.info.environment_id.clone(),
+        project_path: entry.info.project_path.clone(),
+        pid: entry.info.pid,
+        // Runtimes call setsid(), so each one leads its own process group.
+        pgid: entry.info.pid as i32,
+        port: entry.info.port,
+        log_path: entry.info.log_path.clone(),
+        binary: entry.launch.binary.clone(),
+        restart_policy: entry.info.restart_policy,
+        restart_count: entry.info.restart_count,
+        log_options: entry.launch.log_options,
+        env_spec: entry.launch.env_spec.clone(),
+    }
+}
+
+fn persist_registry(runtimes: &HashMap<RuntimeKey, WorkspaceRuntimeEntry>) {
+    let Some(registry_path) = RUNTIME_REGISTRY_PATH.get() else {
+        return;
+    };
+    let records: Vec<RuntimeRegistryEntry> = runtimes
+        .values()
+        .filter(|entry| entry.process.is_some())
+        .map(registry_entry)
+        .collect();
+    write_registry(registry_path, &records);
+}
+
+/// Replaces a registry file atomically, so a crash mid-write leaves the
+/// previous records readable.
+pub(crate) fn write_registry<T: Serialize>(registry_path: &Path, records: &[T]) {
+    let result = serde_json::to_vec_pretty(records)
+        .map_err(|error| error.to_string())
+        .and_then(|bytes| {
+            if let Some(parent) = registry_path.parent() {
+                fs::create_dir_all(parent).map_err(|error| error.to_string())?;
+            }
+            let temp_path = registry_path.with_extension("json.tmp");
+            fs::write(&temp_path, bytes).map_err(|error| error.to_string())?;
+            fs::rename(&temp_path, registry_path).map_err(|error| error.to_string())
+        });
+    if let Err(error) = result {
+        eprintln!(
+            "[canvas] failed to write registry {}: {}",
+            registry_path.display(),
+            error
+        );
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn process_looks_like_runtime(pid: u32, port: u16) -> bool {
+    let Ok(cmdline) = fs::read(format!("/proc/{}/cmdline", pid)) else {
+        return false;
+    };
+    let port = port.to_string();
+    let args: Vec<&[u8]> = cmdline.split(|byte| *byte == 0).collect();
+    args.contains(&b"serve".as_slice()) && args.contains(&port.as_bytes())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn process_looks_like_runtime(_pid: u32, _port: u16) -> bool {
+    true
+}
+
+fn runtime_record_alive(record: &RuntimeRegistryEntry) -> bool {
+    if !process_alive(record.pid) {
+        return false;
+    }
+    #[cfg(unix)]
+    {
+        if unsafe { libc::getpgid(record.pid as i32) } != record.pgid {
+            return false;
+        }
+    }
+    process_looks_like_runtime(record.pid, record.port)
+}
+
+fn reap_stale_runtime(record: &RuntimeRegistryEntry) {
+    // A live leader that is not an otto runtime means the pid was reused.
+    if process_alive(record.pid) && !process_looks_like_runtime(record.pid, record.port) {
+        return;
+    }
+    if process_group_alive(record.pgid) {
+        eprintln!(
+            "[canvas] reaping stale otto runtime workspace={} pgid={}",
+            record.workspace_id, record.pgid
+        );
+        kill_process_group(record.pgid as u32);
+    }
+}
+
+fn stop_runtime_entry(key: &RuntimeKey, mut entry: WorkspaceRuntimeEntry) {
+    eprintln!(
+        "[canvas] stopping otto runtime runtime={} pid={}",
+        key, entry.info.pid
+    );
+    let exit_code = entry.process.as_mut().and_then(RuntimeProcess::terminate);
+    entry.events.send(WorkspaceRuntimeEvent::Exited {
+        log_tail: runtime_log_tail(&entry.info.log_path),
+        info: entry.info,
+        exit_code,
+    });
+}
+
+fn report_runtime_exit(entry: WorkspaceRuntimeEntry, exit_code: Option<i32>) {
+    let log_tail = runtime_log_tail(&entry.info.log_path);
+    let event = if entry.info.status == WorkspaceRuntimeStatus::Starting {
+        eprintln!(
+            "[canvas] otto runtime failed workspace={} exit_code={:?}",
+            entry.info.workspace_id, exit_code
+        );
+        WorkspaceRuntimeEvent::Failed {
+            error: format!(
+                "Otto runtime process exited immediately with code {}",
+                exit_code.unwrap_or(-1)
+            ),
+            info: entry.info,
+            exit_code,
+            log_tail,
+        }
+    } else {
+        eprintln!(
+            "[canvas] otto runtime exited workspace={} exit_code={:?}",
+            entry.info.workspace_id, exit_code
+        );
+        WorkspaceRuntimeEvent::Exited {
+            info: entry.info,
+            exit_code,
+            log_tail,
+        }
+    };
+    entry.events.send(event);
+}
+
+fn report_crash_loop(entry: WorkspaceRuntimeEntry, exit_code: Option<i32>) {
+    eprintln!(
+        "[canvas] otto runtime crash loop workspace={} restarts={}",
+        entry.info.workspace_id, entry.info.restart_count
+    );
+    entry.events.send(WorkspaceRuntimeEvent::Failed {
+        error: format!(
+            "Otto runtime crashed {} times within {} seconds; not restarting again",
+            CRASH_LOOP_MAX_RESTARTS + 1,
+            CRASH_LOOP_WINDOW.as_secs()
+        ),
+        log_tail: runtime_log_tail(&entry.info.log_path),
+        info: entry.info,
+        exit_code,
+    });
+}
+
 fn get_binary_path(app: &AppHandle<Wry>) -> PathBuf {
     let os = std::env::consts::OS;
     let arch = std::env::consts::ARCH;
@@ -148,31 +735,6 @@ fn get_binary_path(app: &AppHandle<Wry>) -> PathBuf {
     PathBuf::from("otto")
 }
 
-fn loopback_port_available(port: u16) -> bool {
-    let ipv4_available = TcpListener::bind(("127.0.0.1", port)).is_ok();
-    let ipv6_available = TcpListener::bind(("::1", port)).is_ok();
-    ipv4_available && ipv6_available
-}
-
-fn find_available_port(tracked_ports: &[u16]) -> u16 {
-    let base = 19100u16;
-
-    for offset in 0..1000u16 {
-        let port = base + offset;
-        if port > 60000 {
-            break;
-        }
-        if tracked_ports.contains(&port) {
-            continue;
-        }
-        if loopback_port_available(port) {
-            return port;
-        }
-    }
-
-    19100
-}
-
 fn current_tracked_ports(manager: &WorkspaceRuntimeManager) -> Vec<u16> {
     if let Ok(runtimes) = manager.inner.lock() {
         runtimes.values().map(|entry| entry.info.port).collect()
@@ -217,29 +779,6 @@ fn runtime_http_ready(port: u16) -> bool {
     false
 }
 
-fn strip_ansi(text: &str) -> String {
-    let mut output = String::with_capacity(text.len());
-    let mut chars = text.chars().peekable();
-
-    while let Some(ch) = chars.next() {
-        if ch == '\u{1b}' {
-            if matches!(chars.peek(), Some('[')) {
-                let _ = chars.next();
-                while let Some(next) = chars.next() {
-                    if ('@'..='~').contains(&next) {
-                        break;
-                    }
-                }
-                continue;
-            }
-            continue;
-        }
-        output.push(ch);
-    }
-
-    output
-}
-
 fn runtime_log_ready(log_path: &str) -> bool {
     let content = match read_to_string(log_path) {
         Ok(content) => content,
@@ -250,137 +789,354 @@ fn runtime_log_ready(log_path: &str) -> bool {
         || (stripped.contains("API") && stripped.contains("Web UI"))
 }
 
-fn wait_for_runtime_ready(
-    port: u16,
-    log_path: &str,
-    timeout: Duration,
-    manager: &WorkspaceRuntimeManager,
-    workspace_id: &str,
-) -> Result<bool, String> {
-    let start = Instant::now();
-    while start.elapsed() < timeout {
-        if runtime_http_ready(port) || runtime_log_ready(log_path) {
-            return Ok(true);
-        }
-        if let Ok(mut runtimes) = manager.inner.lock() {
-            if let Some(entry) = runtimes.get_mut(workspace_id) {
-                match entry.child.try_wait() {
-                    Ok(Some(status)) => {
-                        let code = status.code().unwrap_or(-1);
-                        runtimes.remove(workspace_id);
-                        return Err(format!(
-                            "Otto runtime process exited immediately with code {}",
-                            code
-                        ));
+fn runtime_log_tail(log_path: &str) -> String {
+    read_log_tail(Path::new(log_path), RUNTIME_LOG_TAIL_LINES).unwrap_or_default()
+}
+
+fn current_status_event(info: &WorkspaceRuntimeInfo) -> WorkspaceRuntimeEvent {
+    match info.status {
+        WorkspaceRuntimeStatus::Starting => WorkspaceRuntimeEvent::Starting { info: info.clone() },
+        WorkspaceRuntimeStatus::Ready => WorkspaceRuntimeEvent::Ready { info: info.clone() },
+        WorkspaceRuntimeStatus::Restarting => WorkspaceRuntimeEvent::Restarting {
+            info: info.clone(),
+            exit_code: None,
+            log_tail: runtime_log_tail(&info.log_path),
+            delay_ms: 0,
+        },
+        WorkspaceRuntimeStatus::Failed => WorkspaceRuntimeEvent::Failed {
+            info: info.clone(),
+            error: "Otto workspace runtime did not become ready.".to_string(),
+            exit_code: None,
+            log_tail: runtime_log_tail(&info.log_path),
+        },
+    }
+}
+
+fn set_runtime_status(
+    runtimes: &RuntimeMap,
+    key: &RuntimeKey,
+    pid: u32,
+    status: WorkspaceRuntimeStatus,
+) -> Option<(WorkspaceRuntimeInfo, RuntimeEvents)> {
+    let mut runtimes = runtimes.lock().ok()?;
+    let entry = runtimes
+        .get_mut(key)
+        .filter(|entry| entry.info.pid == pid)?;
+    entry.info.status = status;
+    if status == WorkspaceRuntimeStatus::Ready {
+        entry.restarts.ready_since = Some(Instant::now());
+    }
+    Some((entry.info.clone(), entry.events.clone()))
+}
+
+fn runtime_path_env() -> String {
+    let otto_bin_dir = dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("/tmp"))
+        .join("otto")
+        .join("bin");
+    let current_path = std::env::var("PATH").unwrap_or_default();
+    format!(
+        "{}:/opt/homebrew/bin:/usr/local/bin:{}",
+        otto_bin_dir.display(),
+        current_path
+    )
+}
+
+fn spawn_supervisor(runtimes: &RuntimeMap, key: RuntimeKey, pid: u32) -> Result<(), String> {
+    let runtimes = runtimes.clone();
+    std::thread::Builder::new()
+        .name(format!("otto-runtime-{}", key))
+        .spawn(move || supervise_runtime(runtimes, key, pid))
+        .map(|_| ())
+        .map_err(|error| format!("Failed to spawn runtime supervisor: {}", error))
+}
+
+fn spawn_runtime_process(launch: &RuntimeLaunch) -> Result<Child, String> {
+    let log_path = Path::new(&launch.log_path);
+    set_log_secrets(log_path, launch.secrets.clone());
+    let (stdout, stderr) = open_child_log(log_path, launch.log_options).unwrap_or_else(|error| {
+        eprintln!(
+            "[canvas] runtime log unavailable log={} error={}",
+            launch.log_path, error
+        );
+        (Stdio::null(), Stdio::null())
+    });
+
+    let port_arg = launch.port.to_string();
+    let mut cmd = Command::new(&launch.binary);
+    cmd.current_dir(&launch.project_path)
+        .args(["serve", "--port", &port_arg, "--no-open"])
+        .env("PATH", &launch.path_env)
+        .env("TERM", "xterm-256color")
+        .envs(launch.env.iter().map(|(key, value)| (key, value)))
+        .stdout(stdout)
+        .stderr(stderr);
+
+    #[cfg(unix)]
+    unsafe {
+        cmd.pre_exec(|| {
+            libc::setsid();
+            Ok(())
+        });
+    }
+
+    cmd.spawn()
+        .map_err(|error| format!("Failed to start otto runtime: {}", error))
+}
+
+fn restart_runtime(runtimes: &RuntimeMap, key: &RuntimeKey, previous_pid: u32) -> Option<u32> {
+    let mut locked = runtimes.lock().ok()?;
+    let entry = locked
+        .get_mut(key)
+        .filter(|entry| entry.info.pid == previous_pid && entry.process.is_none())?;
+
+    match spawn_runtime_process(&entry.launch) {
+        Ok(child) => {
+            entry.info.pid = child.id();
+            entry.info.status = WorkspaceRuntimeStatus::Starting;
+            entry.process = Some(RuntimeProcess::Spawned(child));
+            eprintln!(
+                "[canvas] otto runtime restarted runtime={} pid={} restarts={}",
+                key, entry.info.pid, entry.info.restart_count
+            );
+            entry.events.send(WorkspaceRuntimeEvent::Starting {
+                info: entry.info.clone(),
+            });
+            let pid = entry.info.pid;
+            persist_registry(&locked);
+            Some(pid)
+        }
+        Err(error) => {
+            let entry = locked.remove(key)?;
+            persist_registry(&locked);
+            drop(locked);
+            eprintln!(
+                "[canvas] otto runtime restart failed runtime={} error={}",
+                key, error
+            );
+            entry.events.send(WorkspaceRuntimeEvent::Failed {
+                error,
+                exit_code: None,
+                log_tail: runtime_log_tail(&entry.info.log_path),
+                info: entry.info,
+            });
+            None
+        }
+    }
+}
+
+/// Stops a runtime that never became ready so it does not keep holding its
+/// port, and reports the timeout.
+fn fail_unready_runtime(runtimes: &RuntimeMap, key: &RuntimeKey, pid: u32) {
+    let Ok(mut locked) = runtimes.lock() else {
+        return;
+    };
+    if locked.get(key).is_none_or(|entry| entry.info.pid != pid) {
+        return;
+    }
+    let Some(mut entry) = locked.remove(key) else {
+        return;
+    };
+    persist_registry(&locked);
+    drop(locked);
+
+    eprintln!(
+        "[canvas] otto runtime readiness timed out workspace={} url={} log={}",
+        entry.info.workspace_id, entry.info.url, entry.info.log_path
+    );
+    let exit_code = entry.process.as_mut().and_then(RuntimeProcess::terminate);
+    entry.info.status = WorkspaceRuntimeStatus::Failed;
+    entry.events.send(WorkspaceRuntimeEvent::Failed {
+        error: "Timed out waiting for otto workspace runtime to become ready.".to_string(),
+        exit_code,
+        log_tail: runtime_log_tail(&entry.info.log_path),
+        info: entry.info,
+    });
+}
+
+fn supervise_runtime(runtimes: RuntimeMap, key: RuntimeKey, mut pid: u32) {
+    let mut start = Instant::now();
+    loop {
+        let (info, log_options) = {
+            let Ok(mut locked) = runtimes.lock() else {
+                return;
+            };
+            let Some(entry) = locked
+                .get_mut(&key)
+                .filter(|entry| entry.info.pid == pid)
+            else {
+                return;
+            };
+            if let Some(Ok(Some(exit_code))) = entry.process.as_mut().map(RuntimeProcess::try_exit) {
+                if !entry.info.restart_policy.should_restart(exit_code) {
+                    if let Some(entry) = locked.remove(&key) {
+                        persist_registry(&locked);
+                        drop(locked);
+                        report_runtime_exit(entry, exit_code);
+                    }
+                    return;
+                }
+
+                let Some(delay) = entry.restarts.next_delay() else {
+                    if let Some(entry) = locked.remove(&key) {
+                        persist_registry(&locked);
+                        drop(locked);
+                        report_crash_loop(entry, exit_code);
+                    }
+                    return;
+                };
+
+                entry.process = None;
+                entry.info.status = WorkspaceRuntimeStatus::Restarting;
+                entry.info.restart_count += 1;
+                eprintln!(
+                    "[canvas] otto runtime exited runtime={} exit_code={:?} restarting_in_ms={}",
+                    key,
+                    exit_code,
+                    delay.as_millis()
+                );
+                entry.events.send(WorkspaceRuntimeEvent::Restarting {
+                    info: entry.info.clone(),
+                    exit_code,
+                    log_tail: runtime_log_tail(&entry.info.log_path),
+                    delay_ms: delay.as_millis() as u64,
+                });
+                persist_registry(&locked);
+                drop(locked);
+
+                sleep(delay);
+                match restart_runtime(&runtimes, &key, pid) {
+                    Some(next_pid) => {
+                        pid = next_pid;
+                        start = Instant::now();
+                        continue;
                     }
-                    Ok(None) => {}
-                    Err(_) => {}
+                    None => return,
+                }
+            }
+
+            (entry.info.clone(), entry.launch.log_options)
+        };
+
+        if let Err(error) = rotate_oversized_log(Path::new(&info.log_path), log_options) {
+            eprintln!(
+                "[canvas] runtime log rotation failed log={} error={}",
+                info.log_path, error
+            );
+        }
+
+        if info.status == WorkspaceRuntimeStatus::Starting {
+            if runtime_http_ready(info.port) || runtime_log_ready(&info.log_path) {
+                if let Some((info, events)) =
+                    set_runtime_status(&runtimes, &key, pid, WorkspaceRuntimeStatus::Ready)
+                {
+                    eprintln!(
+                        "[canvas] otto runtime ready workspace={} url={}",
+                        info.workspace_id, info.url
+                    );
+                    events.send(WorkspaceRuntimeEvent::Ready { info });
                 }
+            } else if start.elapsed() >= RUNTIME_READY_TIMEOUT {
+                fail_unready_runtime(&runtimes, &key, pid);
+                return;
             }
+            sleep(Duration::from_millis(250));
+        } else {
+            sleep(Duration::from_millis(500));
         }
-        sleep(Duration::from_millis(250));
     }
-    Ok(false)
 }
 
-fn cleanup_dead_runtime_locked(
-    runtimes: &mut HashMap<String, WorkspaceRuntimeEntry>,
-    workspace_id: &str,
+/// Returns the tracked runtime unless its process has already exited; exited
+/// processes are left for the supervisor to restart or reap.
+fn live_runtime_locked(
+    runtimes: &mut HashMap<RuntimeKey, WorkspaceRuntimeEntry>,
+    key: &RuntimeKey,
 ) -> Result<Option<WorkspaceRuntimeInfo>, String> {
-    let should_remove = if let Some(entry) = runtimes.get_mut(workspace_id) {
-        match entry.child.try_wait() {
-            Ok(Some(_)) => true,
-            Ok(None) => return Ok(Some(entry.info.clone())),
-            Err(error) => return Err(format!("Failed to inspect runtime process: {}", error)),
-        }
-    } else {
-        false
+    let Some(entry) = runtimes.get_mut(key) else {
+        return Ok(None);
     };
 
-    if should_remove {
-        runtimes.remove(workspace_id);
+    match entry.process.as_mut().map(RuntimeProcess::try_exit) {
+        Some(Ok(Some(_))) => Ok(None),
+        Some(Ok(None)) | None => Ok(Some(entry.info.clone())),
+        Some(Err(error)) => Err(format!("Failed to inspect runtime process: {}", error)),
     }
-
-    Ok(None)
 }
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub fn workspace_start_runtime(
     app_handle: AppHandle<Wry>,
     manager: State<'_, WorkspaceRuntimeManager>,
     workspace_id: String,
     environment_id: String,
     project_path: String,
+    restart_policy: Option<WorkspaceRestartPolicy>,
+    log_options: Option<RuntimeLogOptions>,
+    env_spec: Option<WorkspaceEnvSpec>,
+    on_event: Channel<WorkspaceRuntimeEvent>,
 ) -> Result<WorkspaceRuntimeInfo, String> {
+    let key = RuntimeKey::new(&workspace_id, &environment_id);
     {
         let mut runtimes = manager
             .inner
             .lock()
             .map_err(|_| "Failed to lock runtime manager".to_string())?;
-        if let Some(info) = cleanup_dead_runtime_locked(&mut runtimes, &workspace_id)? {
+        if let Some(info) = live_runtime_locked(&mut runtimes, &key)? {
+            let info = match runtimes.get_mut(&key) {
+                Some(entry) => {
+                    entry.events = RuntimeEvents(Some(on_event.clone()));
+                    if let Some(restart_policy) = restart_policy {
+                        entry.info.restart_policy = restart_policy;
+                    }
+                    if let Some(log_options) = log_options {
+                        entry.launch.log_options = log_options;
+                    }
+                    entry.info.clone()
+                }
+                None => info,
+            };
+            let _ = on_event.send(current_status_event(&info));
             return Ok(info);
         }
     }
 
+    let env = resolve_env_for(env_spec.clone(), &project_path)?;
     let tracked_ports = current_tracked_ports(manager.inner());
-    let port = find_available_port(&tracked_ports);
-    let port_arg = port.to_string();
+    let port = reserve_runtime_port(
+        Path::new(&project_path),
+        &format!("otto-runtime:{}", environment_id),
+        &tracked_ports,
+    )?;
     let binary = get_binary_path(&app_handle);
-
-    let log_path = format!("/tmp/otto-canvas-runtime-{}-{}.log", workspace_id, port);
-    let log_file = OpenOptions::new()
-        .create(true)
-        .write(true)
-        .truncate(true)
-        .open(&log_path)
-        .ok();
-    let stdout = log_file
-        .as_ref()
-        .and_then(|file| file.try_clone().ok())
-        .map(Stdio::from)
-        .unwrap_or(Stdio::null());
-    let stderr = log_file.map(Stdio::from).unwrap_or(Stdio::null());
-
-    let otto_bin_dir = dirs::config_dir()
-        .unwrap_or_else(|| PathBuf::from("/tmp"))
-        .join("otto")
-        .join("bin");
-    let current_path = std::env::var("PATH").unwrap_or_default();
-    let augmented_path = format!(
-        "{}:/opt/homebrew/bin:/usr/local/bin:{}",
-        otto_bin_dir.display(),
-        current_path
-    );
+    let log_path = prepare_runtime_log_path(&app_handle, &workspace_id, &environment_id)?
+        .to_string_lossy()
+        .to_string();
 
     eprintln!(
-        "[canvas] starting otto runtime workspace={} env={} cwd={} port={} binary={} log={}",
+        "[canvas] starting otto runtime workspace={} env={} cwd={} port={} binary={} log={} vars=[{}]",
         workspace_id,
         environment_id,
         project_path,
         port,
         binary.display(),
-        log_path
+        log_path,
+        env.describe()
     );
 
-    let mut cmd = Command::new(&binary);
-    cmd.current_dir(&project_path)
-        .args(["serve", "--port", &port_arg, "--no-open"])
-        .env("PATH", &augmented_path)
-        .env("TERM", "xterm-256color")
-        .stdout(stdout)
-        .stderr(stderr);
-
-    #[cfg(unix)]
-    unsafe {
-        cmd.pre_exec(|| {
-            libc::setsid();
-            Ok(())
-        });
-    }
-
-    let child = cmd
-        .spawn()
-        .map_err(|error| format!("Failed to start otto runtime: {}", error))?;
+    let launch = RuntimeLaunch {
+        binary,
+        project_path: project_path.clone(),
+        port,
+        log_path: log_path.clone(),
+        log_options: log_options.unwrap_or_default(),
+        path_env: runtime_path_env(),
+        env: env.pairs(),
+        env_spec,
+        secrets: env.secrets(),
+    };
+    let child = spawn_runtime_process(&launch)?;
 
     let info = WorkspaceRuntimeInfo {
         workspace_id: workspace_id.clone(),
@@ -390,6 +1146,9 @@ pub fn workspace_start_runtime(
         port,
         url: format!("http://localhost:{}", port),
         log_path,
+        status: WorkspaceRuntimeStatus::Starting,
+        restart_policy: restart_policy.unwrap_or_default(),
+        restart_count: 0,
     };
 
     eprintln!(
@@ -397,77 +1156,102 @@ pub fn workspace_start_runtime(
         info.workspace_id, info.pid, info.url
     );
 
-    manager
-        .inner
-        .lock()
-        .map_err(|_| "Failed to lock runtime manager".to_string())?
-        .insert(
-            workspace_id.clone(),
+    {
+        let mut runtimes = manager
+            .inner
+            .lock()
+            .map_err(|_| "Failed to lock runtime manager".to_string())?;
+        runtimes.insert(
+            key.clone(),
             WorkspaceRuntimeEntry {
-                child,
+                process: Some(RuntimeProcess::Spawned(child)),
                 info: info.clone(),
+                events: RuntimeEvents(Some(on_event.clone())),
+                launch,
+                restarts: RestartTracker::default(),
             },
         );
+        persist_registry(&runtimes);
+    }
+    let _ = on_event.send(WorkspaceRuntimeEvent::Starting { info: info.clone() });
 
-    match wait_for_runtime_ready(
-        port,
-        &info.log_path,
-        Duration::from_secs(30),
-        manager.inner(),
-        &workspace_id,
-    ) {
-        Ok(true) => {
-            eprintln!(
-                "[canvas] otto runtime ready workspace={} url={}",
-                info.workspace_id, info.url
-            );
-            Ok(info)
-        }
-        Ok(false) => {
-            eprintln!(
-                "[canvas] otto runtime readiness timed out workspace={} url={} log={}",
-                info.workspace_id, info.url, info.log_path
-            );
-            Err("Timed out waiting for otto workspace runtime to become ready.".to_string())
-        }
-        Err(error) => {
-            eprintln!(
-                "[canvas] otto runtime failed workspace={} error={}",
-                info.workspace_id, error
-            );
-            Err(error)
+    if let Err(error) = spawn_supervisor(&manager.inner, key.clone(), info.pid) {
+        let entry = manager.inner.lock().ok().and_then(|mut runtimes| {
+            let entry = runtimes.remove(&key);
+            persist_registry(&runtimes);
+            entry
+        });
+        if let Some(mut entry) = entry {
+            if let Some(process) = entry.process.as_mut() {
+                process.terminate();
+            }
         }
+        return Err(error);
     }
+
+    Ok(info)
 }
 
+/// Without an environment this returns the workspace's only live runtime,
+/// and fails when several environments have one.
 #[tauri::command]
 pub fn workspace_get_runtime(
     manager: State<'_, WorkspaceRuntimeManager>,
     workspace_id: String,
+    environment_id: Option<String>,
 ) -> Result<Option<WorkspaceRuntimeInfo>, String> {
     let mut runtimes = manager
         .inner
         .lock()
         .map_err(|_| "Failed to lock runtime manager".to_string())?;
-    cleanup_dead_runtime_locked(&mut runtimes, &workspace_id)
+    let keys: Vec<RuntimeKey> = runtimes
+        .keys()
+        .filter(|key| key.matches(Some(&workspace_id), environment_id.as_deref()))
+        .cloned()
+        .collect();
+    let mut live = Vec::new();
+    for key in keys {
+        if let Some(info) = live_runtime_locked(&mut runtimes, &key)? {
+            live.push(info);
+        }
+    }
+    if live.len() > 1 {
+        return Err(format!(
+            "Workspace {} has runtimes in {} environments; pass an environment id",
+            workspace_id,
+            live.len()
+        ));
+    }
+    Ok(live.pop())
 }
 
+/// Stops one environment's runtime, or every runtime of the workspace when no
+/// environment is given.
 #[tauri::command]
 pub fn workspace_stop_runtime(
     manager: State<'_, WorkspaceRuntimeManager>,
     workspace_id: String,
+    environment_id: Option<String>,
 ) -> Result<(), String> {
     let mut runtimes = manager
         .inner
         .lock()
         .map_err(|_| "Failed to lock runtime manager".to_string())?;
 
-    if let Some(mut entry) = runtimes.remove(&workspace_id) {
-        eprintln!(
-            "[canvas] stopping otto runtime workspace={} pid={}",
-            workspace_id, entry.info.pid
-        );
-        kill_process_tree(&mut entry.child);
+    let keys: Vec<RuntimeKey> = runtimes
+        .keys()
+        .filter(|key| key.matches(Some(&workspace_id), environment_id.as_deref()))
+        .cloned()
+        .collect();
+    let entries: Vec<(RuntimeKey, WorkspaceRuntimeEntry)> = keys
+        .into_iter()
+        .filter_map(|key| runtimes.remove(&key).map(|entry| (key, entry)))
+        .collect();
+    persist_registry(&runtimes);
+    drop(runtimes);
+
+    for (key, entry) in entries {
+        stop_runtime_entry(&key, entry);
     }
 
     Ok(())
@@ -475,28 +1259,29 @@ pub fn workspace_stop_runtime(
 
 #[tauri::command]
 pub fn workspace_read_runtime_log(log_path: String) -> Result<String, String> {
-    let content = read_to_string(&log_path)
-        .map_err(|error| format!("Failed to read runtime log {}: {}", log_path, error))?;
-    let stripped = strip_ansi(&content);
-
-    let lines: Vec<&str> = stripped.lines().collect();
-    let start = lines.len().saturating_sub(80);
-    Ok(lines[start..].join("\n"))
+    read_log_tail(Path::new(&log_path), RUNTIME_LOG_TAIL_LINES)
+        .map_err(|error| format!("Failed to read runtime log {}: {}", log_path, error))
 }
 
 #[tauri::command]
 pub fn workspace_list_runtimes(
     manager: State<'_, WorkspaceRuntimeManager>,
+    workspace_id: Option<String>,
+    environment_id: Option<String>,
 ) -> Result<Vec<WorkspaceRuntimeInfo>, String> {
     let mut runtimes = manager
         .inner
         .lock()
         .map_err(|_| "Failed to lock runtime manager".to_string())?;
 
-    let keys: Vec<String> = runtimes.keys().cloned().collect();
+    let keys: Vec<RuntimeKey> = runtimes
+        .keys()
+        .filter(|key| key.matches(workspace_id.as_deref(), environment_id.as_deref()))
+        .cloned()
+        .collect();
     let mut active = Vec::new();
-    for workspace_id in keys {
-        if let Some(info) = cleanup_dead_runtime_locked(&mut runtimes, &workspace_id)? {
+    for key in keys {
+        if let Some(info) = live_runtime_locked(&mut runtimes, &key)? {
             active.push(info);
         }
     }
@@ -0,0 +1,645 @@
+use crate::workspace_env::mask_secrets;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread::sleep;
+use std::time::{Duration, UNIX_EPOCH};
+use tauri::{ipc::Channel, AppHandle, Manager, State, Wry};
+
+#[cfg(unix)]
+use std::os::unix::fs::{DirBuilderExt, MetadataExt, OpenOptionsExt};
+
+const LOG_POLL_INTERVAL: Duration = Duration::from_millis(200);
+const LOG_READ_CHUNK: u64 = 64 * 1024;
+const LOG_MAX_READ_PER_POLL: u64 = 1024 * 1024;
+const LOG_PAGE_DEFAULT_LIMIT: usize = 200;
+const LOG_PAGE_MAX_LIMIT: usize = 5_000;
+const LOG_FILE_STEM: &str = "runtime";
+const DEFAULT_LOG_MAX_BYTES: u64 = 10 * 1024 * 1024;
+const DEFAULT_LOG_RETENTION: usize = 5;
+
+/// Rotation settings for a workspace runtime log. `retention` counts the
+/// rotated generations kept next to the live log.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RuntimeLogOptions {
+    pub max_bytes: u64,
+    pub retention: usize,
+}
+
+impl Default for RuntimeLogOptions {
+    fn default() -> Self {
+        Self {
+            max_bytes: DEFAULT_LOG_MAX_BYTES,
+            retention: DEFAULT_LOG_RETENTION,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuntimeLogGeneration {
+    /// 0 is the live log; higher numbers are older rotations.
+    pub generation: usize,
+    pub path: String,
+    pub size: u64,
+    pub modified_ms: Option<u64>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuntimeLogLine {
+    /// Byte offset of the start of the line; doubles as a backfill cursor.
+    pub offset: u64,
+    pub text: String,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuntimeLogPage {
+    pub lines: Vec<RuntimeLogLine>,
+    pub start_offset: u64,
+    pub end_offset: u64,
+    pub has_more: bool,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuntimeLogSubscription {
+    pub subscription_id: u64,
+    pub offset: u64,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase",
+    tag = "event",
+    content = "data"
+)]
+pub enum RuntimeLogEvent {
+    Lines {
+        lines: Vec<RuntimeLogLine>,
+        end_offset: u64,
+    },
+    /// The file shrank or was replaced; earlier offsets are no longer valid.
+    Truncated,
+}
+
+#[derive(Clone, Default)]
+pub struct RuntimeLogManager {
+    next_id: Arc<AtomicU64>,
+    subscriptions: Arc<Mutex<HashMap<u64, Arc<AtomicBool>>>>,
+}
+
+impl RuntimeLogManager {
+    pub fn stop_all(&self) {
+        if let Ok(mut subscriptions) = self.subscriptions.lock() {
+            for (_, cancelled) in subscriptions.drain() {
+                cancelled.store(true, Ordering::SeqCst);
+            }
+        }
+    }
+}
+
+pub(crate) fn strip_ansi(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        if ch == '\u{1b}' {
+            if matches!(chars.peek(), Some('[')) {
+                let _ = chars.next();
+                for next in chars.by_ref() {
+                    if ('@'..='~').contains(&next) {
+                        break;
+                    }
+                }
+                continue;
+            }
+            continue;
+        }
+        output.push(ch);
+    }
+
+    output
+}
+
+/// Secrets to mask in each live log, keyed by its path. Processes write
+/// their logs directly, so values are masked when the log is read.
+static LOG_SECRETS: OnceLock<Mutex<HashMap<PathBuf, Vec<String>>>> = OnceLock::new();
+
+/// Masks these values (see `ResolvedEnv::secrets`) whenever the log at
+/// `log_path`, or one of its rotations, is read.
+pub(crate) fn set_log_secrets(log_path: &Path, secrets: Vec<String>) {
+    if let Ok(mut registered) = LOG_SECRETS.get_or_init(Default::default).lock() {
+        if secrets.is_empty() {
+            registered.remove(log_path);
+        } else {
+            registered.insert(log_path.to_path_buf(), secrets);
+        }
+    }
+}
+
+fn log_secrets(log_path: &Path) -> Vec<String> {
+    LOG_SECRETS
+        .get_or_init(Default::default)
+        .lock()
+        .ok()
+        .and_then(|registered| registered.get(log_path).cloned())
+        .unwrap_or_default()
+}
+
+fn log_line(offset: u64, bytes: &[u8], secrets: &[String]) -> RuntimeLogLine {
+    let bytes = bytes.strip_suffix(b"\r").unwrap_or(bytes);
+    let masked;
+    let bytes = if secrets.is_empty() {
+        bytes
+    } else {
+        masked = mask_secrets(bytes, secrets);
+        &masked
+    };
+    RuntimeLogLine {
+        offset,
+        text: strip_ansi(&String::from_utf8_lossy(bytes)),
+    }
+}
+
+/// Reads up to `limit` complete lines that end at or before `before`,
+/// walking backwards in chunks so large logs are never read in full.
+pub(crate) fn read_log_page(
+    path: &Path,
+    before: Option<u64>,
+    limit: usize,
+    secrets: &[String],
+) -> std::io::Result<RuntimeLogPage> {
+    let mut file = File::open(path)?;
+    let len = file.metadata()?.len();
+    let end = before.unwrap_or(len).min(len);
+
+    let mut start = end;
+    let mut buffer: Vec<u8> = Vec::new();
+    while start > 0 && buffer.iter().filter(|byte| **byte == b'\n').count() <= limit {
+        let chunk_start = start.saturating_sub(LOG_READ_CHUNK);
+        let mut chunk = vec![0; (start - chunk_start) as usize];
+        file.seek(SeekFrom::Start(chunk_start))?;
+        file.read_exact(&mut chunk)?;
+        chunk.extend_from_slice(&buffer);
+        buffer = chunk;
+        start = chunk_start;
+    }
+
+    let mut lines = Vec::new();
+    let mut line_start = 0;
+    for (index, byte) in buffer.iter().enumerate() {
+        if *byte == b'\n' {
+            lines.push((start + line_start as u64, &buffer[line_start..index]));
+            line_start = index + 1;
+        }
+    }
+    if line_start < buffer.len() {
+        lines.push((start + line_start as u64, &buffer[line_start..]));
+    }
+    // The first chunk may begin in the middle of a line.
+    if start > 0 && !lines.is_empty() {
+        lines.remove(0);
+    }
+
+    let skip = lines.len().saturating_sub(limit);
+    let lines: Vec<RuntimeLogLine> = lines[skip..]
+        .iter()
+        .map(|(offset, bytes)| log_line(*offset, bytes, secrets))
+        .collect();
+    let start_offset = lines.first().map(|line| line.offset).unwrap_or(end);
+
+    Ok(RuntimeLogPage {
+        has_more: start_offset > 0,
+        lines,
+        start_offset,
+        end_offset: end,
+    })
+}
+
+/// Last `limit` lines of a log, ANSI-stripped and joined for display.
+pub(crate) fn read_log_tail(path: &Path, limit: usize) -> std::io::Result<String> {
+    let page = read_log_page(path, None, limit, &log_secrets(path))?;
+    Ok(page
+        .lines
+        .into_iter()
+        .map(|line| line.text)
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+/// Offset just past the last newline, so a tail never starts mid-line.
+fn last_line_boundary(file: &mut File, len: u64) -> std::io::Result<u64> {
+    let mut end = len;
+    while end > 0 {
+        let chunk_start = end.saturating_sub(LOG_READ_CHUNK);
+        let mut chunk = vec![0; (end - chunk_start) as usize];
+        file.seek(SeekFrom::Start(chunk_start))?;
+        file.read_exact(&mut chunk)?;
+        if let Some(index) = chunk.iter().rposition(|byte| *byte == b'\n') {
+            return Ok(chunk_start + index as u64 + 1);
+        }
+        end = chunk_start;
+    }
+    Ok(0)
+}
+
+fn sanitize_path_segment(value: &str) -> String {
+    let sanitized: String = value
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_' | '.') {
+                ch
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    match sanitized.trim_matches('.') {
+        "" => "workspace".to_string(),
+        trimmed => trimmed.to_string(),
+    }
+}
+
+fn log_dir(
+    app_handle: &AppHandle<Wry>,
+    category: &str,
+    workspace_id: &str,
+    environment_id: &str,
+) -> PathBuf {
+    let root = app_handle
+        .path()
+        .app_log_dir()
+        .unwrap_or_else(|_| std::env::temp_dir().join("otto-canvas"));
+    root.join(category)
+        .join(sanitize_path_segment(workspace_id))
+        .join(sanitize_path_segment(environment_id))
+}
+
+fn runtime_log_dir(
+    app_handle: &AppHandle<Wry>,
+    workspace_id: &str,
+    environment_id: &str,
+) -> PathBuf {
+    log_dir(app_handle, "runtimes", workspace_id, environment_id)
+}
+
+fn create_private_dir(dir: &Path) -> Result<(), String> {
+    let mut builder = fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    builder.mode(0o700);
+    builder
+        .create(dir)
+        .map_err(|error| format!("Failed to create log dir {}: {}", dir.display(), error))
+}
+
+/// Creates the per-environment log directory (private to the user) and returns
+/// the path of the live log inside it.
+pub(crate) fn prepare_runtime_log_path(
+    app_handle: &AppHandle<Wry>,
+    workspace_id: &str,
+    environment_id: &str,
+) -> Result<PathBuf, String> {
+    let dir = runtime_log_dir(app_handle, workspace_id, environment_id);
+    create_private_dir(&dir)?;
+    Ok(dir.join(format!("{}.log", LOG_FILE_STEM)))
+}
+
+/// Background services log next to each other, one file per service id, and
+/// rotate the same way runtime logs do.
+pub(crate) fn prepare_service_log_path(
+    app_handle: &AppHandle<Wry>,
+    workspace_id: &str,
+    environment_id: &str,
+    service_id: &str,
+) -> Result<PathBuf, String> {
+    let dir = log_dir(app_handle, "services", workspace_id, environment_id);
+    create_private_dir(&dir)?;
+    Ok(dir.join(format!("{}.log", sanitize_path_segment(service_id))))
+}
+
+fn log_generation_path(log_path: &Path, generation: usize) -> PathBuf {
+    if generation == 0 {
+        return log_path.to_path_buf();
+    }
+    let stem = log_path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or(LOG_FILE_STEM);
+    log_path.with_file_name(format!("{}.{}.log", stem, generation))
+}
+
+/// Shifts `runtime.N.log` up by one, dropping anything past `retention`.
+fn shift_log_generations(log_path: &Path, retention: usize) -> std::io::Result<()> {
+    let mut generation = retention.max(1);
+    loop {
+        let path = log_generation_path(log_path, generation);
+        if generation >= retention && path.exists() {
+            fs::remove_file(&path)?;
+        }
+        if generation == 1 {
+            break;
+        }
+        let previous = log_generation_path(log_path, generation - 1);
+        if previous.exists() && generation <= retention {
+            fs::rename(&previous, &path)?;
+        }
+        generation -= 1;
+    }
+    Ok(())
+}
+
+/// Moves the live log to generation 1 before a new process starts writing,
+/// so the previous run's output survives.
+fn rotate_runtime_log(log_path: &Path, options: RuntimeLogOptions) -> std::io::Result<()> {
+    match fs::metadata(log_path) {
+        Ok(metadata) if metadata.len() > 0 => {}
+        _ => return Ok(()),
+    }
+    shift_log_generations(log_path, options.retention)?;
+    if options.retention == 0 {
+        return fs::remove_file(log_path);
+    }
+    fs::rename(log_path, log_generation_path(log_path, 1))
+}
+
+/// Rotates the live log once it outgrows `max_bytes` by copying it to
+/// generation 1 and truncating it in place. The process writing it keeps
+/// its `O_APPEND` descriptor, so its next write lands at the start of the
+/// emptied file; output written between the copy and the truncate is lost.
+pub(crate) fn rotate_oversized_log(
+    log_path: &Path,
+    options: RuntimeLogOptions,
+) -> std::io::Result<()> {
+    if options.max_bytes == 0 {
+        return Ok(());
+    }
+    match fs::metadata(log_path) {
+        Ok(metadata) if metadata.len() > options.max_bytes => {}
+        _ => return Ok(()),
+    }
+    shift_log_generations(log_path, options.retention)?;
+    if options.retention > 0 {
+        fs::copy(log_path, log_generation_path(log_path, 1))?;
+    }
+    OpenOptions::new().write(true).open(log_path)?.set_len(0)
+}
+
+/// Rotates the previous run's log away and opens a fresh one for a child's
+/// stdout and stderr. The child writes the file itself, so its output keeps
+/// landing there if the canvas exits while it runs.
+pub(crate) fn open_child_log(
+    log_path: &Path,
+    options: RuntimeLogOptions,
+) -> std::io::Result<(Stdio, Stdio)> {
+    if let Err(error) = rotate_runtime_log(log_path, options) {
+        eprintln!(
+            "[canvas] log rotation failed log={} error={}",
+            log_path.display(),
+            error
+        );
+    }
+    let stdout = open_runtime_log(log_path)?;
+    let stderr = stdout.try_clone()?;
+    Ok((stdout.into(), stderr.into()))
+}
+
+fn open_runtime_log(log_path: &Path) -> std::io::Result<File> {
+    let mut options = OpenOptions::new();
+    options.create(true).append(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+    options.open(log_path)
+}
+
+fn list_log_generations(log_path: &Path) -> Vec<RuntimeLogGeneration> {
+    let mut generations = Vec::new();
+    let mut generation = 0;
+    loop {
+        let path = log_generation_path(log_path, generation);
+        let Ok(metadata) = fs::metadata(&path) else {
+            if generation == 0 {
+                generation += 1;
+                continue;
+            }
+            break;
+        };
+        generations.push(RuntimeLogGeneration {
+            generation,
+            path: path.to_string_lossy().to_string(),
+            size: metadata.len(),
+            modified_ms: metadata
+                .modified()
+                .ok()
+                .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+                .map(|elapsed| elapsed.as_millis() as u64),
+        });
+        generation += 1;
+    }
+    generations
+}
+
+#[cfg(unix)]
+fn file_identity(metadata: &std::fs::Metadata) -> u64 {
+    metadata.ino()
+}
+
+#[cfg(not(unix))]
+fn file_identity(_metadata: &std::fs::Metadata) -> u64 {
+    0
+}
+
+struct LogTail {
+    path: String,
+    offset: u64,
+    identity: Option<u64>,
+    pending: Vec<u8>,
+    pending_offset: u64,
+    /// Set once the frontend's channel rejects a message, e.g. after the
+    /// webview reloaded without unsubscribing.
+    closed: bool,
+}
+
+impl LogTail {
+    fn send(&mut self, events: &Channel<RuntimeLogEvent>, event: RuntimeLogEvent) {
+        if events.send(event).is_err() {
+            self.closed = true;
+        }
+    }
+
+    fn poll(&mut self, events: &Channel<RuntimeLogEvent>) -> std::io::Result<()> {
+        let mut file = File::open(&self.path)?;
+        let metadata = file.metadata()?;
+        let identity = file_identity(&metadata);
+        let replaced = self.identity.is_some_and(|known| known != identity);
+        self.identity = Some(identity);
+
+        if replaced || metadata.len() < self.offset {
+            self.offset = 0;
+            self.pending.clear();
+            self.pending_offset = 0;
+            self.send(events, RuntimeLogEvent::Truncated);
+        }
+        if metadata.len() == self.offset {
+            return Ok(());
+        }
+
+        let read_len = (metadata.len() - self.offset).min(LOG_MAX_READ_PER_POLL);
+        let mut chunk = vec![0; read_len as usize];
+        file.seek(SeekFrom::Start(self.offset))?;
+        file.read_exact(&mut chunk)?;
+        if self.pending.is_empty() {
+            self.pending_offset = self.offset;
+        }
+        self.offset += read_len;
+        self.pending.extend_from_slice(&chunk);
+
+        let secrets = log_secrets(Path::new(&self.path));
+        let mut lines = Vec::new();
+        let mut line_start = 0;
+        for (index, byte) in self.pending.iter().enumerate() {
+            if *byte == b'\n' {
+                lines.push(log_line(
+                    self.pending_offset + line_start as u64,
+                    &self.pending[line_start..index],
+                    &secrets,
+                ));
+                line_start = index + 1;
+            }
+        }
+        self.pending.drain(..line_start);
+        self.pending_offset += line_start as u64;
+
+        if !lines.is_empty() {
+            let end_offset = self.pending_offset;
+            self.send(events, RuntimeLogEvent::Lines { lines, end_offset });
+        }
+        Ok(())
+    }
+}
+
+#[tauri::command]
+pub fn workspace_subscribe_runtime_log(
+    manager: State<'_, RuntimeLogManager>,
+    log_path: String,
+    from_offset: Option<u64>,
+    on_event: Channel<RuntimeLogEvent>,
+) -> Result<RuntimeLogSubscription, String> {
+    let offset = match from_offset {
+        Some(offset) => offset,
+        None => File::open(&log_path)
+            .and_then(|mut file| {
+                let len = file.metadata()?.len();
+                last_line_boundary(&mut file, len)
+            })
+            .unwrap_or(0),
+    };
+
+    let subscription_id = manager.next_id.fetch_add(1, Ordering::SeqCst) + 1;
+    let cancelled = Arc::new(AtomicBool::new(false));
+    manager
+        .subscriptions
+        .lock()
+        .map_err(|_| "Failed to lock runtime log subscriptions".to_string())?
+        .insert(subscription_id, cancelled.clone());
+
+    let mut tail = LogTail {
+        path: log_path,
+        offset,
+        identity: None,
+        pending: Vec::new(),
+        pending_offset: offset,
+        closed: false,
+    };
+    let subscriptions = manager.subscriptions.clone();
+    std::thread::Builder::new()
+        .name(format!("otto-runtime-log-{}", subscription_id))
+        .spawn(move || {
+            while !cancelled.load(Ordering::SeqCst) {
+                // A missing file just means the runtime has not written yet.
+                let _ = tail.poll(&on_event);
+                if tail.closed {
+                    if let Ok(mut subscriptions) = subscriptions.lock() {
+                        subscriptions.remove(&subscription_id);
+                    }
+                    break;
+                }
+                sleep(LOG_POLL_INTERVAL);
+            }
+        })
+        .map_err(|error| format!("Failed to spawn runtime log tail: {}", error))?;
+
+    Ok(RuntimeLogSubscription {
+        subscription_id,
+        offset,
+    })
+}
+
+#[tauri::command]
+pub fn workspace_unsubscribe_runtime_log(
+    manager: State<'_, RuntimeLogManager>,
+    subscription_id: u64,
+) -> Result<(), String> {
+    let cancelled = manager
+        .subscriptions
+        .lock()
+        .map_err(|_| "Failed to lock runtime log subscriptions".to_string())?
+        .remove(&subscription_id);
+    if let Some(cancelled) = cancelled {
+        cancelled.store(true, Ordering::SeqCst);
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn workspace_read_runtime_log_page(
+    log_path: String,
+    before_offset: Option<u64>,
+    limit: Option<usize>,
+) -> Result<RuntimeLogPage, String> {
+    let limit = limit
+        .unwrap_or(LOG_PAGE_DEFAULT_LIMIT)
+        .clamp(1, LOG_PAGE_MAX_LIMIT);
+    let path = Path::new(&log_path);
+    read_log_page(path, before_offset, limit, &log_secrets(path))
+        .map_err(|error| format!("Failed to read runtime log {}: {}", log_path, error))
+}
+
+#[tauri::command]
+pub fn workspace_list_runtime_logs(
+    app_handle: AppHandle<Wry>,
+    workspace_id: String,
+    environment_id: String,
+) -> Result<Vec<RuntimeLogGeneration>, String> {
+    let log_path = runtime_log_dir(&app_handle, &workspace_id, &environment_id)
+        .join(format!("{}.log", LOG_FILE_STEM));
+    Ok(list_log_generations(&log_path))
+}
+
+#[tauri::command]
+pub fn workspace_read_runtime_log_generation(
+    app_handle: AppHandle<Wry>,
+    workspace_id: String,
+    environment_id: String,
+    generation: usize,
+    before_offset: Option<u64>,
+    limit: Option<usize>,
+) -> Result<RuntimeLogPage, String> {
+    let log_path = runtime_log_dir(&app_handle, &workspace_id, &environment_id)
+        .join(format!("{}.log", LOG_FILE_STEM));
+    let path = log_generation_path(&log_path, generation);
+    let limit = limit
+        .unwrap_or(LOG_PAGE_DEFAULT_LIMIT)
+        .clamp(1, LOG_PAGE_MAX_LIMIT);
+    read_log_page(&path, before_offset, limit, &log_secrets(&log_path))
+        .map_err(|error| format!("Failed to read runtime log {}: {}", path.display(), error))
+}
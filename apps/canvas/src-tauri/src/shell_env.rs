@@ -0,0 +1,112 @@
+use std::{collections::HashMap, process::Command, sync::OnceLock};
+
+#[cfg(unix)]
+use std::ffi::CStr;
+
+static LOGIN_SHELL_PATH: OnceLock<Option<String>> = OnceLock::new();
+static LOGIN_SHELL_ENV: OnceLock<Option<HashMap<String, String>>> = OnceLock::new();
+
+pub(crate) fn resolve_login_shell_path(shell_path: &str) -> Option<String> {
+    resolve_shell_path(shell_path, &["-i", "-l", "-c"])
+        .or_else(|| resolve_shell_path(shell_path, &["-l", "-c"]))
+}
+
+/// PATH as seen by the user's login shell, resolved once per app run since
+/// starting an interactive shell is slow.
+pub(crate) fn login_shell_path() -> Option<String> {
+    LOGIN_SHELL_PATH
+        .get_or_init(|| resolve_login_shell_path(&resolve_user_shell_path()))
+        .clone()
+}
+
+/// The environment of the user's login shell, resolved once per app run.
+/// `${env:NAME}` reads from it so a GUI launch sees the same variables as a
+/// terminal.
+pub(crate) fn login_shell_env() -> Option<&'static HashMap<String, String>> {
+    LOGIN_SHELL_ENV
+        .get_or_init(|| {
+            let shell_path = resolve_user_shell_path();
+            resolve_shell_env(&shell_path, &["-i", "-l", "-c"])
+                .or_else(|| resolve_shell_env(&shell_path, &["-l", "-c"]))
+        })
+        .as_ref()
+}
+
+const START_MARKER: &str = "__OTTO_CANVAS_SHELL_START__";
+const END_MARKER: &str = "__OTTO_CANVAS_SHELL_END__";
+
+/// Runs `script` in the shell and returns what it printed between the
+/// markers, ignoring anything rc files print around it.
+fn run_marked(shell_path: &str, shell_args: &[&str], script: &str) -> Option<String> {
+    let output = Command::new(shell_path)
+        .args(shell_args)
+        .arg(format!(
+            "printf '%s' '{START_MARKER}'; {script}; printf '%s' '{END_MARKER}'"
+        ))
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let start = stdout.rfind(START_MARKER)? + START_MARKER.len();
+    let end = stdout[start..].find(END_MARKER)? + start;
+    Some(stdout[start..end].to_string())
+}
+
+fn resolve_shell_path(shell_path: &str, shell_args: &[&str]) -> Option<String> {
+    let path = run_marked(shell_path, shell_args, "printf '%s' \"$PATH\"")?;
+    let path = path.trim();
+    if path.is_empty() {
+        return None;
+    }
+    Some(path.to_string())
+}
+
+fn resolve_shell_env(shell_path: &str, shell_args: &[&str]) -> Option<HashMap<String, String>> {
+    // NUL separated so values with line breaks survive.
+    let output = run_marked(shell_path, shell_args, "env -0")?;
+    let env: HashMap<String, String> = output
+        .split('\0')
+        .filter_map(|entry| {
+            let (name, value) = entry.split_once('=')?;
+            (!name.is_empty()).then(|| (name.to_string(), value.to_string()))
+        })
+        .collect();
+    (!env.is_empty()).then_some(env)
+}
+
+pub(crate) fn resolve_user_shell_path() -> String {
+    if let Some(shell_path) = std::env::var("SHELL")
+        .ok()
+        .filter(|value| !value.is_empty())
+    {
+        return shell_path;
+    }
+
+    #[cfg(unix)]
+    {
+        unsafe {
+            let passwd = libc::getpwuid(libc::geteuid());
+            if !passwd.is_null() {
+                let shell_ptr = (*passwd).pw_shell;
+                if !shell_ptr.is_null() {
+                    if let Ok(shell_path) = CStr::from_ptr(shell_ptr).to_str() {
+                        if !shell_path.is_empty() {
+                            return shell_path.to_string();
+                        }
+                    }
+                }
+            }
+        }
+
+        "/bin/sh".to_string()
+    }
+    #[cfg(not(unix))]
+    {
+        std::env::var("COMSPEC")
+            .ok()
+            .filter(|value| !value.is_empty())
+            .unwrap_or_else(|| "cmd.exe".to_string())
+    }
+}
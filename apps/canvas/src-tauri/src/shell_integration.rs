@@ -0,0 +1,592 @@
+// Shell integration is only wired up when libghostty-vt is enabled.
+#![cfg_attr(not(otto_canvas_libghostty_vt), allow(dead_code))]
+
+use serde::Serialize;
+use std::{
+    collections::VecDeque,
+    fs,
+    path::{Path, PathBuf},
+};
+
+const MAX_OSC_LEN: usize = 8 * 1024;
+const MAX_COMMAND_RECORDS: usize = 1_000;
+
+const ZSH_ENV: &str = r#"# Written by Otto Canvas. Restores the user's ZDOTDIR before zsh reads
+# the rest of its startup files, then installs the prompt hooks.
+if [[ -n "${OTTO_USER_ZDOTDIR-}" ]]; then
+  ZDOTDIR="$OTTO_USER_ZDOTDIR"
+else
+  unset ZDOTDIR
+fi
+unset OTTO_USER_ZDOTDIR
+if [[ -f "${ZDOTDIR:-$HOME}/.zshenv" ]]; then
+  builtin source "${ZDOTDIR:-$HOME}/.zshenv"
+fi
+if [[ -o interactive && -n "${OTTO_SHELL_INTEGRATION_DIR-}" ]]; then
+  builtin source "$OTTO_SHELL_INTEGRATION_DIR/otto.zsh"
+fi
+"#;
+
+const ZSH_HOOKS: &str = r#"# Written by Otto Canvas. Reports prompts (OSC 133) and the working
+# directory (OSC 7) to the terminal.
+unset OTTO_SHELL_INTEGRATION_DIR
+autoload -Uz add-zsh-hook
+typeset -gi _otto_command_running=0
+
+_otto_urlencode() {
+  emulate -L zsh
+  setopt nomultibyte
+  local input=$1 out= char hex
+  local -i i
+  for (( i = 1; i <= ${#input}; i++ )); do
+    char=$input[i]
+    case $char in
+      [a-zA-Z0-9.~_/-]) out+=$char ;;
+      *) builtin printf -v hex '%%%02X' "'$char"; out+=$hex ;;
+    esac
+  done
+  REPLY=$out
+}
+
+_otto_precmd() {
+  local exit_code=$?
+  if (( _otto_command_running )); then
+    builtin printf '\e]133;D;%d\a' $exit_code
+    _otto_command_running=0
+  fi
+  _otto_urlencode "$PWD"
+  builtin printf '\e]7;file://%s%s\a' "$HOST" "$REPLY"
+  builtin printf '\e]133;A\a'
+}
+
+_otto_preexec() {
+  _otto_command_running=1
+  _otto_urlencode "$1"
+  builtin printf '\e]133;C;cmdline_url=%s\a' "$REPLY"
+}
+
+add-zsh-hook precmd _otto_precmd
+add-zsh-hook preexec _otto_preexec
+"#;
+
+const BASH_RC: &str = r#"# Written by Otto Canvas. bash ignores --rcfile for login shells, so
+# this reads the files a login shell would before installing the hooks.
+if [[ -f /etc/profile ]]; then
+  builtin source /etc/profile
+fi
+for _otto_profile in ~/.bash_profile ~/.bash_login ~/.profile; do
+  if [[ -f "$_otto_profile" ]]; then
+    builtin source "$_otto_profile"
+    break
+  fi
+done
+unset _otto_profile
+
+__otto_command_running=0
+__otto_armed=0
+__otto_history_line=
+
+__otto_urlencode() {
+  local LC_ALL=C input=$1 out= char hex i
+  for (( i = 0; i < ${#input}; i++ )); do
+    char=${input:i:1}
+    case $char in
+      [a-zA-Z0-9.~_/-]) out+=$char ;;
+      *) builtin printf -v hex '%%%02X' "'$char"; out+=$hex ;;
+    esac
+  done
+  REPLY=$out
+}
+
+__otto_precmd() {
+  local exit_code=$?
+  if (( __otto_command_running )); then
+    builtin printf '\e]133;D;%d\a' "$exit_code"
+    __otto_command_running=0
+  fi
+  __otto_urlencode "$PWD"
+  builtin printf '\e]7;file://%s%s\a' "$HOSTNAME" "$REPLY"
+  builtin printf '\e]133;A\a'
+}
+
+__otto_arm() {
+  __otto_armed=1
+  __otto_history_line=$(HISTTIMEFORMAT= builtin history 1)
+}
+
+# The DEBUG trap also fires for PROMPT_COMMAND, so only the first command
+# after __otto_arm counts as the user's. An empty command line goes
+# straight to __otto_precmd. A command left out of the history, e.g. by
+# HISTCONTROL=ignorespace, leaves the last entry unchanged; BASH_COMMAND,
+# its first simple command, is reported instead.
+__otto_preexec() {
+  (( __otto_armed )) || return
+  [[ -n "${COMP_LINE-}" ]] && return
+  __otto_armed=0
+  [[ "$BASH_COMMAND" == __otto_precmd* ]] && return
+  __otto_command_running=1
+  local line
+  line=$(HISTTIMEFORMAT= builtin history 1)
+  if [[ $line != "$__otto_history_line" && $line =~ ^[[:space:]]*[0-9]+[*]?[[:space:]]+(.*)$ ]]; then
+    line=${BASH_REMATCH[1]}
+  else
+    line=$BASH_COMMAND
+  fi
+  __otto_urlencode "$line"
+  builtin printf '\e]133;C;cmdline_url=%s\a' "$REPLY"
+}
+
+if [[ "$(declare -p PROMPT_COMMAND 2>/dev/null)" == "declare -a"* ]]; then
+  PROMPT_COMMAND=(__otto_precmd "${PROMPT_COMMAND[@]}" __otto_arm)
+else
+  PROMPT_COMMAND=$'__otto_precmd\n'"${PROMPT_COMMAND-}"$'\n__otto_arm'
+fi
+
+# Keeps running a DEBUG trap the user's startup files set.
+__otto_user_debug_trap=$(builtin trap -p DEBUG)
+__otto_user_debug_trap=${__otto_user_debug_trap#"trap -- '"}
+__otto_user_debug_trap=${__otto_user_debug_trap%"' DEBUG"}
+__otto_user_debug_trap=${__otto_user_debug_trap//"'\\''"/"'"}
+
+__otto_set_status() {
+  return "$1"
+}
+
+__otto_debug() {
+  local status=$?
+  __otto_preexec
+  if [[ -n "$__otto_user_debug_trap" ]]; then
+    __otto_set_status "$status"
+    builtin eval -- "$__otto_user_debug_trap"
+  fi
+}
+trap '__otto_debug' DEBUG
+"#;
+
+const FISH_CONF: &str = r#"# Written by Otto Canvas. Loaded through XDG_DATA_DIRS, which is put
+# back the way it was before anything else sees it.
+if set -q OTTO_SHELL_INTEGRATION_XDG_DIR
+    set -l otto_xdg_dir $OTTO_SHELL_INTEGRATION_XDG_DIR
+    set -e OTTO_SHELL_INTEGRATION_XDG_DIR
+    set -l remaining (string split : -- "$XDG_DATA_DIRS" | string match -v -- $otto_xdg_dir)
+    if set -q remaining[1]
+        set -gx XDG_DATA_DIRS (string join : -- $remaining)
+    else
+        set -e XDG_DATA_DIRS
+    end
+end
+
+status is-interactive; or exit 0
+
+function __otto_postexec --on-event fish_postexec
+    printf '\e]133;D;%s\a' $status
+end
+
+function __otto_prompt --on-event fish_prompt
+    printf '\e]7;file://%s%s\a' $hostname (string escape --style=url -- $PWD)
+    printf '\e]133;A\a'
+end
+
+function __otto_preexec --on-event fish_preexec
+    printf '\e]133;C;cmdline_url=%s\a' (string escape --style=url -- $argv[1])
+end
+"#;
+
+/// Changes to how an interactive shell is launched so it loads the hooks.
+pub struct ShellLaunch {
+    pub env: Vec<(String, String)>,
+    /// For bash: start `bash --rcfile <path>` instead of a login shell.
+    pub bash_rcfile: Option<PathBuf>,
+}
+
+fn write_if_changed(path: &Path, contents: &str) -> Result<(), String> {
+    if fs::read_to_string(path).is_ok_and(|existing| existing == contents) {
+        return Ok(());
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|error| format!("Failed to create {}: {}", parent.display(), error))?;
+    }
+    fs::write(path, contents)
+        .map_err(|error| format!("Failed to write {}: {}", path.display(), error))
+}
+
+fn lookup_env(env: &[(String, String)], key: &str) -> Option<String> {
+    env.iter()
+        .rev()
+        .find(|(name, _)| name == key)
+        .map(|(_, value)| value.clone())
+        .or_else(|| std::env::var(key).ok())
+}
+
+/// Writes the integration scripts for `shell_name` and returns how to
+/// launch it. `None` for unsupported shells or when
+/// `OTTO_SHELL_INTEGRATION=0` is set in the process or workspace env.
+pub fn prepare_shell_integration(
+    shell_name: &str,
+    env: &[(String, String)],
+) -> Option<ShellLaunch> {
+    if lookup_env(env, "OTTO_SHELL_INTEGRATION")
+        .is_some_and(|value| matches!(value.as_str(), "0" | "false" | "off"))
+    {
+        return None;
+    }
+    // Kept under the user's cache dir; a shared temp dir would let other
+    // users plant startup scripts.
+    let dir = dirs::cache_dir()?
+        .join("otto-canvas")
+        .join("shell-integration");
+    let prepared = match shell_name {
+        "zsh" => {
+            let zsh_dir = dir.join("zsh");
+            write_if_changed(&zsh_dir.join(".zshenv"), ZSH_ENV)
+                .and_then(|_| write_if_changed(&zsh_dir.join("otto.zsh"), ZSH_HOOKS))
+                .map(|_| {
+                    let mut launch_env = vec![
+                        ("ZDOTDIR".to_string(), zsh_dir.to_string_lossy().to_string()),
+                        (
+                            "OTTO_SHELL_INTEGRATION_DIR".to_string(),
+                            zsh_dir.to_string_lossy().to_string(),
+                        ),
+                    ];
+                    if let Some(user_zdotdir) = lookup_env(env, "ZDOTDIR") {
+                        launch_env.push(("OTTO_USER_ZDOTDIR".to_string(), user_zdotdir));
+                    }
+                    ShellLaunch {
+                        env: launch_env,
+                        bash_rcfile: None,
+                    }
+                })
+        }
+        "bash" => {
+            let rcfile = dir.join("bash").join("otto.bash");
+            write_if_changed(&rcfile, BASH_RC).map(|_| ShellLaunch {
+                env: Vec::new(),
+                bash_rcfile: Some(rcfile),
+            })
+        }
+        "fish" => {
+            let xdg_dir = dir.join("xdg");
+            write_if_changed(
+                &xdg_dir.join("fish/vendor_conf.d/otto-shell-integration.fish"),
+                FISH_CONF,
+            )
+            .map(|_| {
+                let xdg_dir = xdg_dir.to_string_lossy().to_string();
+                // An unset XDG_DATA_DIRS means the spec default, which has
+                // to stay in the list once ours is prepended.
+                let data_dirs = lookup_env(env, "XDG_DATA_DIRS")
+                    .filter(|value| !value.is_empty())
+                    .unwrap_or_else(|| "/usr/local/share:/usr/share".to_string());
+                ShellLaunch {
+                    env: vec![
+                        (
+                            "XDG_DATA_DIRS".to_string(),
+                            format!("{xdg_dir}:{data_dirs}"),
+                        ),
+                        ("OTTO_SHELL_INTEGRATION_XDG_DIR".to_string(), xdg_dir),
+                    ],
+                    bash_rcfile: None,
+                }
+            })
+        }
+        _ => return None,
+    };
+    prepared
+        .map_err(|error| eprintln!("[canvas] shell integration disabled: {error}"))
+        .ok()
+}
+
+#[derive(Clone, Copy, Default)]
+enum OscState {
+    #[default]
+    Ground,
+    Escape,
+    Osc,
+    OscEscape,
+}
+
+/// Finds OSC sequences in PTY output, including ones split across reads.
+#[derive(Default)]
+pub struct OscScanner {
+    state: OscState,
+    payload: Vec<u8>,
+    overflow: bool,
+}
+
+impl OscScanner {
+    fn start(&mut self) -> OscState {
+        self.payload.clear();
+        self.overflow = false;
+        OscState::Osc
+    }
+
+    fn finish(&mut self, end: usize, found: &mut Vec<(usize, String)>) -> OscState {
+        if !self.overflow {
+            found.push((end, String::from_utf8_lossy(&self.payload).into_owned()));
+        }
+        self.payload.clear();
+        OscState::Ground
+    }
+
+    /// Returns the payload of every OSC completed in `bytes`, paired with
+    /// the offset just past its terminator.
+    pub fn feed(&mut self, bytes: &[u8]) -> Vec<(usize, String)> {
+        let mut found = Vec::new();
+        for (index, &byte) in bytes.iter().enumerate() {
+            self.state = match (self.state, byte) {
+                (OscState::Ground, 0x1b) => OscState::Escape,
+                (OscState::Ground, _) => OscState::Ground,
+                (OscState::Escape | OscState::OscEscape, b']') => self.start(),
+                (OscState::Escape, 0x1b) => OscState::Escape,
+                (OscState::Escape, _) => OscState::Ground,
+                (OscState::Osc, 0x07) => self.finish(index + 1, &mut found),
+                (OscState::Osc, 0x1b) => OscState::OscEscape,
+                // CAN and SUB abort the sequence.
+                (OscState::Osc, 0x18 | 0x1a) => OscState::Ground,
+                (OscState::Osc, _) => {
+                    if self.payload.len() < MAX_OSC_LEN {
+                        self.payload.push(byte);
+                    } else {
+                        self.overflow = true;
+                    }
+                    OscState::Osc
+                }
+                (OscState::OscEscape, b'\\') => self.finish(index + 1, &mut found),
+                (OscState::OscEscape, 0x1b) => OscState::Escape,
+                (OscState::OscEscape, _) => OscState::Ground,
+            };
+        }
+        found
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ShellMark {
+    PromptStart,
+    CommandStart { command: Option<String> },
+    CommandFinished { exit_code: Option<i32> },
+    Cwd(String),
+}
+
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let hex = (bytes[index] == b'%')
+            .then(|| value.get(index + 1..index + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match hex {
+            Some(byte) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            None => {
+                decoded.push(bytes[index]);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Local path of a `file://host/path` URL. A bare absolute path is
+/// accepted as well.
+pub fn file_url_path(url: &str) -> Option<String> {
+    let path = match url.strip_prefix("file://") {
+        Some(rest) => &rest[rest.find('/')?..],
+        None if url.starts_with('/') => url,
+        None => return None,
+    };
+    Some(percent_decode(path))
+}
+
+/// Parses the OSC 133 semantic prompt marks and OSC 7 working directory
+/// reports. Other payloads return `None`.
+pub fn parse_shell_mark(payload: &str) -> Option<ShellMark> {
+    if let Some(url) = payload.strip_prefix("7;") {
+        return file_url_path(url).map(ShellMark::Cwd);
+    }
+
+    let mut fields = payload.strip_prefix("133;")?.split(';');
+    match fields.next()? {
+        "A" => Some(ShellMark::PromptStart),
+        "C" => {
+            let command = fields.find_map(|option| {
+                if let Some(encoded) = option.strip_prefix("cmdline_url=") {
+                    Some(percent_decode(encoded))
+                } else {
+                    option.strip_prefix("cmdline=").map(ToOwned::to_owned)
+                }
+            });
+            Some(ShellMark::CommandStart { command })
+        }
+        "D" => Some(ShellMark::CommandFinished {
+            exit_code: fields.next().and_then(|code| code.trim().parse().ok()),
+        }),
+        _ => None,
+    }
+}
+
+/// A command run at a shell prompt. Rows count from the first line the
+/// session printed, so they stay put as the scrollback is trimmed.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShellCommandRecord {
+    pub id: u64,
+    pub command: Option<String>,
+    pub cwd: Option<String>,
+    pub prompt_row: u64,
+    pub output_start_row: Option<u64>,
+    /// Exclusive.
+    pub output_end_row: Option<u64>,
+    pub started_at_ms: Option<u64>,
+    pub finished_at_ms: Option<u64>,
+    pub exit_code: Option<i32>,
+}
+
+impl ShellCommandRecord {
+    fn running(&self) -> bool {
+        self.started_at_ms.is_some() && self.finished_at_ms.is_none()
+    }
+}
+
+pub enum CommandChange {
+    Started(ShellCommandRecord),
+    Finished(ShellCommandRecord),
+    Cwd(String),
+}
+
+#[derive(Default)]
+pub struct CommandTracker {
+    records: VecDeque<ShellCommandRecord>,
+    next_id: u64,
+    cwd: Option<String>,
+}
+
+impl CommandTracker {
+    pub fn records(&self) -> Vec<ShellCommandRecord> {
+        self.records.iter().cloned().collect()
+    }
+
+    pub fn cwd(&self) -> Option<&str> {
+        self.cwd.as_deref()
+    }
+
+    /// Working directory `row` was printed in: that of the command whose
+    /// prompt is at or above it, or the current one.
+    pub fn cwd_at(&self, row: u64) -> Option<&str> {
+        self.records
+            .iter()
+            .rev()
+            .find(|record| record.prompt_row <= row)
+            .and_then(|record| record.cwd.as_deref())
+            .or(self.cwd.as_deref())
+    }
+
+    /// Drops finished commands whose output ended above `row`, the first
+    /// one still retained.
+    pub fn forget_before(&mut self, row: u64) {
+        while self.records.front().is_some_and(|record| {
+            !record.running() && record.output_end_row.unwrap_or(record.prompt_row + 1) <= row
+        }) {
+            self.records.pop_front();
+        }
+    }
+
+    fn push(&mut self, prompt_row: u64) -> &mut ShellCommandRecord {
+        if self.records.len() == MAX_COMMAND_RECORDS {
+            self.records.pop_front();
+        }
+        self.next_id += 1;
+        self.records.push_back(ShellCommandRecord {
+            id: self.next_id,
+            command: None,
+            cwd: self.cwd.clone(),
+            prompt_row,
+            output_start_row: None,
+            output_end_row: None,
+            started_at_ms: None,
+            finished_at_ms: None,
+            exit_code: None,
+        });
+        self.records.back_mut().expect("record was just pushed")
+    }
+
+    /// Applies a mark seen with the cursor at `row`/`col`.
+    pub fn apply(
+        &mut self,
+        mark: ShellMark,
+        row: u64,
+        col: u16,
+        now_ms: u64,
+    ) -> Option<CommandChange> {
+        let output_end = if col > 0 { row + 1 } else { row };
+        match mark {
+            ShellMark::PromptStart => {
+                // A prompt without a finish mark, e.g. after the shell was
+                // interrupted, ends the running command without a status.
+                let mut change = None;
+                if let Some(last) = self.records.back_mut().filter(|last| last.running()) {
+                    last.finished_at_ms = Some(now_ms);
+                    last.output_end_row = Some(output_end);
+                    change = Some(CommandChange::Finished(last.clone()));
+                }
+                let cwd = self.cwd.clone();
+                match self
+                    .records
+                    .back_mut()
+                    .filter(|last| last.started_at_ms.is_none())
+                {
+                    // Empty command lines reuse the unused prompt record.
+                    Some(last) => {
+                        last.prompt_row = row;
+                        last.cwd = cwd;
+                    }
+                    None => {
+                        self.push(row);
+                    }
+                }
+                change
+            }
+            ShellMark::CommandStart { command } => {
+                let unused_prompt = self
+                    .records
+                    .back()
+                    .is_some_and(|last| last.started_at_ms.is_none());
+                let record = if unused_prompt {
+                    self.records.back_mut().expect("checked above")
+                } else {
+                    self.push(row)
+                };
+                record.command = command.filter(|command| !command.trim().is_empty());
+                record.started_at_ms = Some(now_ms);
+                record.output_start_row = Some(row);
+                Some(CommandChange::Started(record.clone()))
+            }
+            ShellMark::CommandFinished { exit_code } => {
+                let last = self.records.back_mut().filter(|last| last.running())?;
+                last.finished_at_ms = Some(now_ms);
+                last.exit_code = exit_code;
+                last.output_end_row = Some(output_end);
+                Some(CommandChange::Finished(last.clone()))
+            }
+            ShellMark::Cwd(cwd) => {
+                if self.cwd.as_deref() == Some(cwd.as_str()) {
+                    return None;
+                }
+                self.cwd = Some(cwd.clone());
+                if let Some(last) = self
+                    .records
+                    .back_mut()
+                    .filter(|last| last.started_at_ms.is_none())
+                {
+                    last.cwd = Some(cwd.clone());
+                }
+                Some(CommandChange::Cwd(cwd))
+            }
+        }
+    }
+}
@@ -0,0 +1,483 @@
+// Link detection is only wired up when libghostty-vt is enabled.
+#![cfg_attr(not(otto_canvas_libghostty_vt), allow(dead_code))]
+
+use crate::ghostty_vt::GhosttyVtLinkKind;
+use crate::shell_env::login_shell_env;
+use crate::shell_integration::file_url_path;
+use crate::workspace_automation::step_path_env;
+use regex::Regex;
+use serde::Serialize;
+use std::{
+    collections::VecDeque,
+    path::{Component, Path, PathBuf},
+    process::{Command, Stdio},
+    sync::OnceLock,
+    thread,
+};
+use tauri::{AppHandle, Emitter};
+
+const MAX_HYPERLINKS: usize = 1_000;
+
+/// An OSC 8 hyperlink escape: `8;params;uri` opens a link, an empty URI
+/// closes it.
+#[derive(Clone, Debug, PartialEq)]
+pub enum HyperlinkMark {
+    Open(String),
+    Close,
+}
+
+pub fn parse_hyperlink(payload: &str) -> Option<HyperlinkMark> {
+    let (_params, uri) = payload.strip_prefix("8;")?.split_once(';')?;
+    Some(if uri.is_empty() {
+        HyperlinkMark::Close
+    } else {
+        HyperlinkMark::Open(uri.to_string())
+    })
+}
+
+/// Rows count from the first line the session printed, like command
+/// records; the end column is exclusive.
+struct Hyperlink {
+    uri: String,
+    start: (u64, u16),
+    end: (u64, u16),
+}
+
+/// Where OSC 8 hyperlinked text was printed.
+#[derive(Default)]
+pub struct HyperlinkTracker {
+    links: VecDeque<Hyperlink>,
+    open: Option<(String, u64, u16)>,
+}
+
+impl HyperlinkTracker {
+    /// Applies a mark seen with the cursor at `row`/`col`.
+    pub fn apply(&mut self, mark: HyperlinkMark, row: u64, col: u16) {
+        if let Some((uri, start_row, start_col)) = self.open.take() {
+            if (row, col) > (start_row, start_col) {
+                if self.links.len() == MAX_HYPERLINKS {
+                    self.links.pop_front();
+                }
+                self.links.push_back(Hyperlink {
+                    uri,
+                    start: (start_row, start_col),
+                    end: (row, col),
+                });
+            }
+        }
+        if let HyperlinkMark::Open(uri) = mark {
+            self.open = Some((uri, row, col));
+        }
+    }
+
+    /// Drops links at or below `row`, which is being printed over, e.g.
+    /// by a new prompt after `clear`.
+    pub fn forget_from(&mut self, row: u64) {
+        self.links.retain(|link| link.start.0 < row);
+        if self
+            .open
+            .as_ref()
+            .is_some_and(|(_, start_row, _)| *start_row >= row)
+        {
+            self.open = None;
+        }
+    }
+
+    /// Drops links that ended above `row`, the first one still retained.
+    pub fn forget_before(&mut self, row: u64) {
+        self.links.retain(|link| link.end.0 >= row);
+    }
+
+    /// Hyperlinked column ranges of `row` in a terminal `cols` wide. Newer
+    /// links win where they overlap older ones.
+    pub fn row_spans(&self, row: u64, cols: u16) -> Vec<(u16, u16, &str)> {
+        let mut spans: Vec<(u16, u16, &str)> = Vec::new();
+        for link in self.links.iter().rev() {
+            if row < link.start.0 || row > link.end.0 {
+                continue;
+            }
+            let start = if row == link.start.0 { link.start.1 } else { 0 };
+            let end = if row == link.end.0 { link.end.1 } else { cols };
+            if start < end && !spans.iter().any(|(s, e, _)| start < *e && *s < end) {
+                spans.push((start, end, &link.uri));
+            }
+        }
+        spans.sort_by_key(|(start, _, _)| *start);
+        spans
+    }
+}
+
+/// A link found in the text of one row. `start` and `end` are byte
+/// offsets; `target` is the URL or the absolute path of a file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextLink {
+    pub start: usize,
+    pub end: usize,
+    pub kind: GhosttyVtLinkKind,
+    pub target: String,
+    pub line: Option<u32>,
+    pub column: Option<u32>,
+}
+
+fn url_regex() -> &'static Regex {
+    static URL: OnceLock<Regex> = OnceLock::new();
+    URL.get_or_init(|| {
+        Regex::new(r#"\b(?:https?|ftp|file)://[^\s<>"'`]+"#).expect("URL pattern is valid")
+    })
+}
+
+/// `path:line:col` (rustc, tsc --pretty, eslint -f unix), `path(line,col)`
+/// (tsc) or a bare path.
+fn path_regex() -> &'static Regex {
+    static PATH: OnceLock<Regex> = OnceLock::new();
+    PATH.get_or_init(|| {
+        Regex::new(
+            r"(?P<path>(?:~|\.\.?)?/?(?:[\w.@+-]+/)*[\w@+-][\w.@+-]*)(?::(?P<line>\d+)(?::(?P<col>\d+))?|\((?P<pline>\d+)(?:,(?P<pcol>\d+))?\))?",
+        )
+        .expect("path pattern is valid")
+    })
+}
+
+/// Length of `url` without trailing punctuation and unbalanced closing
+/// brackets, which usually belong to the surrounding text.
+fn trim_url(url: &str) -> usize {
+    let mut end = url.len();
+    while let Some(last) = url[..end].chars().next_back() {
+        let unbalanced =
+            |open: char| url[..end].matches(open).count() < url[..end].matches(last).count();
+        let trailing = match last {
+            '.' | ',' | ';' | ':' | '!' | '?' => true,
+            ')' => unbalanced('('),
+            ']' => unbalanced('['),
+            '}' => unbalanced('{'),
+            _ => false,
+        };
+        if !trailing {
+            break;
+        }
+        end -= last.len_utf8();
+    }
+    end
+}
+
+fn has_extension(path: &str) -> bool {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    name.rsplit_once('.').is_some_and(|(stem, extension)| {
+        !stem.is_empty()
+            && extension.starts_with(|ch: char| ch.is_ascii_alphabetic())
+            && extension.chars().all(|ch| ch.is_ascii_alphanumeric())
+    })
+}
+
+/// Filters out words that only look like paths: `localhost:3000`,
+/// `12:30:45`, `and/or`.
+fn looks_like_path(path: &str, has_line: bool) -> bool {
+    let explicit = ["/", "./", "../", "~/"]
+        .iter()
+        .any(|prefix| path.starts_with(prefix));
+    if has_line {
+        path.contains('/') || has_extension(path)
+    } else {
+        explicit || (path.contains('/') && has_extension(path))
+    }
+}
+
+/// Lexically removes `.` and `..` components.
+fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+/// Resolves `~/` against the home directory and relative paths against
+/// `cwd`.
+pub fn resolve_path(path: &str, cwd: Option<&Path>) -> Option<PathBuf> {
+    let resolved = if let Some(rest) = path.strip_prefix("~/") {
+        dirs::home_dir()?.join(rest)
+    } else if Path::new(path).is_absolute() {
+        PathBuf::from(path)
+    } else {
+        cwd?.join(path)
+    };
+    Some(normalize_path(&resolved))
+}
+
+/// Finds URLs and file references in one row of text. Links wrapped
+/// across rows are only found through OSC 8.
+pub fn find_text_links(text: &str, cwd: Option<&Path>) -> Vec<TextLink> {
+    let mut links: Vec<TextLink> = Vec::new();
+    for found in url_regex().find_iter(text) {
+        let end = found.start() + trim_url(found.as_str());
+        links.push(TextLink {
+            start: found.start(),
+            end,
+            kind: GhosttyVtLinkKind::Url,
+            target: text[found.start()..end].to_string(),
+            line: None,
+            column: None,
+        });
+    }
+
+    let url_count = links.len();
+    for captures in path_regex().captures_iter(text) {
+        let (Some(found), Some(path)) = (captures.get(0), captures.name("path")) else {
+            continue;
+        };
+        if links[..url_count]
+            .iter()
+            .any(|url| found.start() < url.end && url.start < found.end())
+        {
+            continue;
+        }
+        let number = |name: &str| {
+            captures
+                .name(name)
+                .and_then(|value| value.as_str().parse().ok())
+        };
+        let line = number("line").or_else(|| number("pline"));
+        let column = number("col").or_else(|| number("pcol"));
+        if !looks_like_path(path.as_str(), line.is_some()) {
+            continue;
+        }
+        let Some(target) = resolve_path(path.as_str(), cwd) else {
+            continue;
+        };
+        links.push(TextLink {
+            start: found.start(),
+            end: found.end(),
+            kind: GhosttyVtLinkKind::File,
+            target: target.to_string_lossy().into_owned(),
+            line,
+            column,
+        });
+    }
+
+    links.sort_by_key(|link| link.start);
+    links
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct OpenUrlEvent {
+    session_id: String,
+    url: String,
+}
+
+/// Arguments that make a known GUI editor open `path` at a position.
+/// Terminal editors and unknown commands return `None`.
+fn editor_args(
+    program: &str,
+    path: &Path,
+    line: Option<u32>,
+    column: Option<u32>,
+) -> Option<Vec<String>> {
+    let path = path.to_string_lossy().into_owned();
+    let position = match (line, column) {
+        (Some(line), Some(column)) => format!("{path}:{line}:{column}"),
+        (Some(line), None) => format!("{path}:{line}"),
+        _ => path.clone(),
+    };
+    let name = Path::new(program).file_name()?.to_str()?;
+    match name {
+        "code" | "code-insiders" | "codium" | "cursor" | "windsurf" => {
+            Some(vec!["--goto".to_string(), position])
+        }
+        "zed" | "subl" | "sublime_text" => Some(vec![position]),
+        "idea" | "webstorm" | "pycharm" | "goland" | "rustrover" | "clion" | "phpstorm"
+        | "rubymine" => {
+            let mut args = Vec::new();
+            if let Some(line) = line {
+                args.extend(["--line".to_string(), line.to_string()]);
+            }
+            if let Some(column) = column {
+                args.extend(["--column".to_string(), column.to_string()]);
+            }
+            args.push(path);
+            Some(args)
+        }
+        _ => None,
+    }
+}
+
+/// Arguments that open `path` at `line` and `column` in a terminal editor,
+/// which needs a terminal of its own.
+fn terminal_editor_args(
+    program: &str,
+    path: &Path,
+    line: Option<u32>,
+    column: Option<u32>,
+) -> Option<Vec<String>> {
+    let path = path.to_string_lossy().to_string();
+    let name = Path::new(program).file_name()?.to_str()?;
+    let args = match (name, line) {
+        ("vi" | "vim" | "nvim", Some(line)) => match column {
+            Some(column) => vec![format!("+call cursor({line}, {column})"), path],
+            None => vec![format!("+{line}"), path],
+        },
+        ("nano", Some(line)) => vec![format!("+{line},{}", column.unwrap_or(1)), path],
+        ("kak", Some(line)) => vec![format!("+{line}:{}", column.unwrap_or(1)), path],
+        ("hx" | "helix" | "micro", Some(line)) => {
+            vec![format!("{path}:{line}:{}", column.unwrap_or(1))]
+        }
+        ("vi" | "vim" | "nvim" | "nano" | "kak" | "hx" | "helix" | "micro", None) => vec![path],
+        _ => return None,
+    };
+    Some(args)
+}
+
+fn shell_quote(word: &str) -> String {
+    if !word.is_empty()
+        && word
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || "-_./:+,=@".contains(ch))
+    {
+        return word.to_string();
+    }
+    format!("'{}'", word.replace('\'', "'\\''"))
+}
+
+/// `$OTTO_EDITOR`, `$VISUAL` or `$EDITOR` as the login shell sets them, so
+/// an app started from the dock sees the editor configured in rc files.
+fn configured_editor() -> Option<String> {
+    ["OTTO_EDITOR", "VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|key| match login_shell_env() {
+            Some(env) => env.get(*key).cloned(),
+            None => std::env::var(key).ok(),
+        })
+        .find(|value| !value.trim().is_empty())
+}
+
+/// Payload of `ghostty-vt-open-editor`: run `command` in a new terminal
+/// block next to the session.
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct OpenEditorEvent {
+    session_id: String,
+    label: String,
+    command: String,
+    cwd: Option<String>,
+}
+
+/// Opens `path` in `$OTTO_EDITOR`, `$VISUAL` or `$EDITOR`: a GUI editor
+/// that can jump to a line is started directly and a terminal editor runs
+/// in a new terminal block next to the session. Any other editor, or none,
+/// reveals the file in the file manager; the system default app for a file
+/// can be the file itself.
+pub fn open_in_editor(
+    app_handle: &AppHandle,
+    session_id: &str,
+    path: &Path,
+    line: Option<u32>,
+    column: Option<u32>,
+) -> Result<(), String> {
+    if !path.exists() {
+        return Err(format!("{} does not exist", path.display()));
+    }
+
+    if let Some(editor) = configured_editor() {
+        let mut words = editor.split_whitespace();
+        let program = words.next().unwrap_or_default();
+        if let Some(args) = terminal_editor_args(program, path, line, column) {
+            let command = editor
+                .split_whitespace()
+                .map(str::to_string)
+                .chain(args)
+                .map(|word| shell_quote(&word))
+                .collect::<Vec<_>>()
+                .join(" ");
+            let label = path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| path.display().to_string());
+            return app_handle
+                .emit(
+                    "ghostty-vt-open-editor",
+                    OpenEditorEvent {
+                        session_id: session_id.to_string(),
+                        label,
+                        command,
+                        cwd: path
+                            .parent()
+                            .map(|parent| parent.to_string_lossy().to_string()),
+                    },
+                )
+                .map_err(|error| format!("Failed to open {}: {}", path.display(), error));
+        }
+        if let Some(args) = editor_args(program, path, line, column) {
+            let spawned = Command::new(program)
+                .args(words)
+                .args(args)
+                .env("PATH", step_path_env())
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .spawn();
+            match spawned {
+                Ok(mut child) => {
+                    // Editor launchers exit right away; reap them.
+                    thread::spawn(move || child.wait());
+                    return Ok(());
+                }
+                Err(error) => eprintln!("[canvas] failed to start editor {program}: {error}"),
+            }
+        }
+    }
+
+    tauri_plugin_opener::reveal_item_in_dir(path)
+        .map_err(|error| format!("Failed to reveal {}: {}", path.display(), error))
+}
+
+/// Opens a link printed in a terminal. The program that printed it picked
+/// the target, so only web URLs (in a browser block next to the session)
+/// and mail links open right away and files only reach the editor or the
+/// file manager. Other schemes return a warning instead, unless
+/// `allow_external` says the user confirmed handing them to the system.
+pub fn open_link(
+    app_handle: &AppHandle,
+    session_id: &str,
+    target: &str,
+    line: Option<u32>,
+    column: Option<u32>,
+    allow_external: bool,
+) -> Result<Option<String>, String> {
+    if let Some(path) = file_url_path(target) {
+        return open_in_editor(app_handle, session_id, Path::new(&path), line, column)
+            .map(|_| None);
+    }
+    let Some((scheme, _)) = target.split_once(':') else {
+        return Err(format!("Cannot open {target}: not a URL or absolute path"));
+    };
+    match scheme.to_ascii_lowercase().as_str() {
+        "http" | "https" => {
+            return app_handle
+                .emit(
+                    "ghostty-vt-open-url",
+                    OpenUrlEvent {
+                        session_id: session_id.to_string(),
+                        url: target.to_string(),
+                    },
+                )
+                .map(|_| None)
+                .map_err(|error| format!("Failed to open {target}: {error}"));
+        }
+        "mailto" => {}
+        _ if !allow_external => {
+            return Ok(Some(format!(
+                "{target} will be opened by the app registered for {scheme}: links."
+            )));
+        }
+        _ => {}
+    }
+    tauri_plugin_opener::open_url(target, None::<&str>)
+        .map(|_| None)
+        .map_err(|error| format!("Failed to open {target}: {error}"))
+}
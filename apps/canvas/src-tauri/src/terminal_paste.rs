@@ -0,0 +1,144 @@
+// Pasting is only wired up when libghostty-vt is enabled.
+#![cfg_attr(not(otto_canvas_libghostty_vt), allow(dead_code))]
+
+const BRACKETED_PASTE_START: &[u8] = b"\x1b[200~";
+const BRACKETED_PASTE_END: &[u8] = b"\x1b[201~";
+
+/// Text ready to write to a PTY.
+pub struct PreparedPaste {
+    pub bytes: Vec<u8>,
+    /// Escape sequences and control characters removed from the text.
+    pub stripped_controls: usize,
+    /// The text has a line break, so a shell without bracketed paste would
+    /// run each line as it arrives.
+    pub multiline: bool,
+    /// Lines in the cleaned text, with a lone CR also ending a line.
+    pub lines: usize,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum EscapeState {
+    Text,
+    Escape,
+    /// CSI: parameters until a final byte in `@`..`~`.
+    Csi,
+    /// OSC, DCS, SOS, PM and APC: a string until BEL or ST.
+    String,
+    StringEscape,
+}
+
+/// Removes escape sequences and control characters other than tab and
+/// line breaks. An escape sequence counts once however long it is; that
+/// includes `ESC[201~`, which would end a bracketed paste early and let
+/// the rest of the text run as typed input.
+fn strip_controls(text: &str) -> (String, usize) {
+    let mut output = String::with_capacity(text.len());
+    let mut stripped = 0;
+    let mut state = EscapeState::Text;
+    for ch in text.chars() {
+        state = match (state, ch) {
+            (EscapeState::Text, '\x1b') => {
+                stripped += 1;
+                EscapeState::Escape
+            }
+            (EscapeState::Text, '\t' | '\n' | '\r') => {
+                output.push(ch);
+                EscapeState::Text
+            }
+            (EscapeState::Text, _) if ch.is_control() => {
+                stripped += 1;
+                EscapeState::Text
+            }
+            (EscapeState::Text, _) => {
+                output.push(ch);
+                EscapeState::Text
+            }
+            (EscapeState::Escape, '[') => EscapeState::Csi,
+            (EscapeState::Escape, ']' | 'P' | 'X' | '^' | '_') => EscapeState::String,
+            // Intermediate bytes, e.g. the `(` of a charset designation.
+            (EscapeState::Escape, '\x20'..='\x2f') => EscapeState::Escape,
+            (EscapeState::Escape, _) => EscapeState::Text,
+            (EscapeState::Csi, '\x40'..='\x7e') => EscapeState::Text,
+            (EscapeState::Csi, _) => EscapeState::Csi,
+            (EscapeState::String, '\x07') => EscapeState::Text,
+            (EscapeState::String, '\x1b') => EscapeState::StringEscape,
+            (EscapeState::String, _) => EscapeState::String,
+            (EscapeState::StringEscape, '\\') => EscapeState::Text,
+            (EscapeState::StringEscape, _) => EscapeState::String,
+        };
+    }
+    (output, stripped)
+}
+
+/// Cleans `text` and encodes it the way a terminal pastes: line breaks
+/// become CR, and with bracketed paste (DEC mode 2004) the text is wrapped
+/// in `ESC[200~` and `ESC[201~` with its line feeds kept.
+pub fn prepare_paste(text: &str, bracketed: bool) -> PreparedPaste {
+    let (cleaned, stripped_controls) = strip_controls(text);
+    let normalized = cleaned.replace("\r\n", "\n");
+    let multiline = normalized.contains(['\n', '\r']);
+    let lines = if normalized.is_empty() {
+        0
+    } else {
+        normalized
+            .trim_end_matches(['\n', '\r'])
+            .split(['\n', '\r'])
+            .count()
+    };
+
+    let mut bytes = Vec::with_capacity(normalized.len() + 12);
+    if normalized.is_empty() {
+        return PreparedPaste {
+            bytes,
+            stripped_controls,
+            multiline,
+            lines,
+        };
+    }
+    if bracketed {
+        bytes.extend_from_slice(BRACKETED_PASTE_START);
+        bytes.extend_from_slice(normalized.as_bytes());
+        bytes.extend_from_slice(BRACKETED_PASTE_END);
+    } else {
+        bytes.extend(
+            normalized
+                .bytes()
+                .map(|byte| if byte == b'\n' { b'\r' } else { byte }),
+        );
+    }
+    PreparedPaste {
+        bytes,
+        stripped_controls,
+        multiline,
+        lines,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_an_injected_bracketed_paste_end() {
+        let prepared = prepare_paste("echo safe\x1b[201~rm -rf ~\n", true);
+        let expected = b"\x1b[200~echo saferm -rf ~\n\x1b[201~";
+        assert_eq!(prepared.bytes, expected);
+        assert_eq!(prepared.stripped_controls, 1);
+        assert!(prepared.multiline);
+        assert_eq!(prepared.lines, 1);
+    }
+
+    #[test]
+    fn strips_osc_strings_and_control_characters() {
+        let (cleaned, stripped) = strip_controls("a\x1b]0;title\x07b\x00c\td");
+        assert_eq!(cleaned, "abc\td");
+        assert_eq!(stripped, 2);
+    }
+
+    #[test]
+    fn converts_line_feeds_without_bracketed_paste() {
+        let prepared = prepare_paste("one\r\ntwo\nthree", false);
+        assert_eq!(prepared.bytes, b"one\rtwo\rthree");
+        assert_eq!(prepared.lines, 3);
+    }
+}
@@ -0,0 +1,318 @@
+// Recording is only wired up when libghostty-vt is enabled.
+#![cfg_attr(not(otto_canvas_libghostty_vt), allow(dead_code))]
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::BTreeMap,
+    fs::{self, File, OpenOptions},
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+#[cfg(unix)]
+use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+
+const ASCIICAST_VERSION: u64 = 2;
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Serialize, Deserialize)]
+struct AsciicastHeader {
+    version: u64,
+    width: u16,
+    height: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    timestamp: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    idle_time_limit: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    title: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    env: BTreeMap<String, String>,
+}
+
+/// Writes terminal output in the asciinema v2 format: a JSON header line
+/// followed by one `[seconds, code, data]` line per event.
+pub struct AsciicastWriter {
+    path: PathBuf,
+    file: BufWriter<File>,
+    started: Instant,
+    last_flush: Instant,
+    /// Bytes of a UTF-8 sequence split across PTY reads.
+    pending: Vec<u8>,
+}
+
+/// Length of an incomplete UTF-8 sequence at the end of `bytes`.
+fn incomplete_utf8_tail(bytes: &[u8]) -> usize {
+    for back in 1..=bytes.len().min(3) {
+        let byte = bytes[bytes.len() - back];
+        if byte & 0xC0 == 0x80 {
+            continue;
+        }
+        let needed = match byte {
+            0xF0..=0xFF => 4,
+            0xE0..=0xEF => 3,
+            0xC0..=0xDF => 2,
+            _ => 1,
+        };
+        return if needed > back { back } else { 0 };
+    }
+    0
+}
+
+/// Creates or truncates `path` readable only by the user, since recordings
+/// can hold anything typed or printed in the terminal.
+fn create_private_file(path: &Path) -> std::io::Result<File> {
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+    let file = options.open(path)?;
+    #[cfg(unix)]
+    file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    Ok(file)
+}
+
+impl AsciicastWriter {
+    pub fn create(path: &Path, cols: u16, rows: u16, title: Option<&str>) -> Result<Self, String> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|error| {
+                format!(
+                    "Failed to create recording dir {}: {}",
+                    parent.display(),
+                    error
+                )
+            })?;
+        }
+        let file = create_private_file(path)
+            .map_err(|error| format!("Failed to create recording {}: {}", path.display(), error))?;
+        let header = AsciicastHeader {
+            version: ASCIICAST_VERSION,
+            width: cols,
+            height: rows,
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .ok()
+                .map(|elapsed| elapsed.as_secs()),
+            idle_time_limit: None,
+            title: title.map(ToOwned::to_owned),
+            env: BTreeMap::from([("TERM".to_string(), "xterm-256color".to_string())]),
+        };
+
+        let mut writer = Self {
+            path: path.to_path_buf(),
+            file: BufWriter::new(file),
+            started: Instant::now(),
+            last_flush: Instant::now(),
+            pending: Vec::new(),
+        };
+        let line = serde_json::to_string(&header).map_err(|error| error.to_string())?;
+        writer.write_line(&line)?;
+        writer.flush()?;
+        Ok(writer)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn write_line(&mut self, line: &str) -> Result<(), String> {
+        self.file
+            .write_all(line.as_bytes())
+            .and_then(|_| self.file.write_all(b"\n"))
+            .map_err(|error| {
+                format!(
+                    "Failed to write recording {}: {}",
+                    self.path.display(),
+                    error
+                )
+            })
+    }
+
+    fn flush(&mut self) -> Result<(), String> {
+        self.last_flush = Instant::now();
+        self.file.flush().map_err(|error| {
+            format!(
+                "Failed to write recording {}: {}",
+                self.path.display(),
+                error
+            )
+        })
+    }
+
+    fn event(&mut self, code: &str, data: &str) -> Result<(), String> {
+        let seconds = self.started.elapsed().as_micros() as f64 / 1_000_000.0;
+        let line =
+            serde_json::to_string(&(seconds, code, data)).map_err(|error| error.to_string())?;
+        self.write_line(&line)?;
+        if self.last_flush.elapsed() >= FLUSH_INTERVAL {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Records bytes read from the PTY.
+    pub fn output(&mut self, bytes: &[u8]) -> Result<(), String> {
+        self.pending.extend_from_slice(bytes);
+        let complete = self.pending.len() - incomplete_utf8_tail(&self.pending);
+        if complete == 0 {
+            return Ok(());
+        }
+        let data = String::from_utf8_lossy(&self.pending[..complete]).into_owned();
+        self.pending.drain(..complete);
+        self.event("o", &data)
+    }
+
+    pub fn resize(&mut self, cols: u16, rows: u16) -> Result<(), String> {
+        self.event("r", &format!("{cols}x{rows}"))
+    }
+
+    pub fn finish(mut self) -> Result<PathBuf, String> {
+        if !self.pending.is_empty() {
+            let data = String::from_utf8_lossy(&self.pending).into_owned();
+            self.pending.clear();
+            self.event("o", &data)?;
+        }
+        self.flush()?;
+        Ok(self.path)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum AsciicastEvent {
+    Output(String),
+    Resize { cols: u16, rows: u16 },
+}
+
+pub struct AsciicastRecording {
+    pub cols: u16,
+    pub rows: u16,
+    pub idle_time_limit: Option<f64>,
+    /// Events with their offset in seconds from the start.
+    pub events: Vec<(f64, AsciicastEvent)>,
+}
+
+fn parse_resize(data: &str) -> Option<(u16, u16)> {
+    let (cols, rows) = data.split_once('x')?;
+    Some((cols.trim().parse().ok()?, rows.trim().parse().ok()?))
+}
+
+/// Reads an asciicast v2 file. Event codes other than output and resize
+/// (input, markers) are skipped.
+pub fn read_asciicast(path: &Path) -> Result<AsciicastRecording, String> {
+    let text = fs::read_to_string(path)
+        .map_err(|error| format!("Failed to read recording {}: {}", path.display(), error))?;
+    let mut lines = text
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty());
+
+    let (_, header_line) = lines
+        .next()
+        .ok_or_else(|| format!("{} is empty", path.display()))?;
+    let header: AsciicastHeader = serde_json::from_str(header_line)
+        .map_err(|error| format!("{}:1: invalid asciicast header: {}", path.display(), error))?;
+    if header.version != ASCIICAST_VERSION {
+        return Err(format!(
+            "{}: unsupported asciicast version {}",
+            path.display(),
+            header.version
+        ));
+    }
+
+    let mut events = Vec::new();
+    for (index, line) in lines {
+        let invalid = || format!("{}:{}: invalid asciicast event", path.display(), index + 1);
+        let (seconds, code, data): (f64, String, Value) =
+            serde_json::from_str(line).map_err(|_| invalid())?;
+        let Some(data) = data.as_str() else {
+            continue;
+        };
+        let event = match code.as_str() {
+            "o" => AsciicastEvent::Output(data.to_string()),
+            "r" => {
+                let (cols, rows) = parse_resize(data).ok_or_else(invalid)?;
+                AsciicastEvent::Resize { cols, rows }
+            }
+            _ => continue,
+        };
+        events.push((seconds.max(0.0), event));
+    }
+
+    Ok(AsciicastRecording {
+        cols: header.width.max(1),
+        rows: header.height.max(1),
+        idle_time_limit: header.idle_time_limit.filter(|limit| *limit > 0.0),
+        events,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir()
+            .join(format!("otto-recording-test-{}", std::process::id()))
+            .join(name)
+    }
+
+    #[test]
+    fn round_trips_output_and_resizes() {
+        let path = temp_path("round-trip.cast");
+        let mut writer = AsciicastWriter::create(&path, 80, 24, Some("demo")).unwrap();
+        // "é" split across two reads is recorded as one character.
+        writer.output(b"hello \xc3").unwrap();
+        writer.output(b"\xa9\r\n\x1b[1mbold\x1b[0m").unwrap();
+        writer.resize(120, 40).unwrap();
+        writer.output(b"done").unwrap();
+        let written = writer.finish().unwrap();
+        assert_eq!(written, path);
+
+        let recording = read_asciicast(&path).unwrap();
+        assert_eq!((recording.cols, recording.rows), (80, 24));
+        let events: Vec<AsciicastEvent> = recording
+            .events
+            .iter()
+            .map(|(_, event)| event.clone())
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                AsciicastEvent::Output("hello ".to_string()),
+                AsciicastEvent::Output("é\r\n\x1b[1mbold\x1b[0m".to_string()),
+                AsciicastEvent::Resize {
+                    cols: 120,
+                    rows: 40
+                },
+                AsciicastEvent::Output("done".to_string()),
+            ]
+        );
+        assert!(recording
+            .events
+            .windows(2)
+            .all(|pair| pair[0].0 <= pair[1].0));
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn skips_unknown_events_and_rejects_other_versions() {
+        let path = temp_path("foreign.cast");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(
+            &path,
+            "{\"version\":2,\"width\":10,\"height\":5}\n[0.5,\"i\",\"ls\\r\"]\n[1.0,\"o\",\"x\"]\n",
+        )
+        .unwrap();
+        let recording = read_asciicast(&path).unwrap();
+        assert_eq!(
+            recording.events,
+            vec![(1.0, AsciicastEvent::Output("x".to_string()))]
+        );
+
+        fs::write(&path, "{\"version\":1,\"width\":10,\"height\":5}\n").unwrap();
+        assert!(read_asciicast(&path).is_err());
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,313 @@
+use crate::shell_env::{login_shell_path, resolve_user_shell_path};
+use crate::workspace_env::{resolve_env_for, ResolvedEnv, WorkspaceEnvSpec};
+use serde::{Deserialize, Serialize};
+use std::{
+    io::{BufRead, BufReader, Read},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    thread,
+    time::Instant,
+};
+use tauri::ipc::Channel;
+
+#[cfg(unix)]
+use std::os::unix::fs::PermissionsExt;
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceStepCondition {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path_exists: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path_missing: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command_exists: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceEnsureStep {
+    pub id: String,
+    pub label: String,
+    pub run: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub when: Option<WorkspaceStepCondition>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum WorkspaceStepStatus {
+    Skipped,
+    Ok,
+    Failed,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceStepResult {
+    pub step_id: String,
+    pub status: WorkspaceStepStatus,
+    pub exit_code: Option<i32>,
+    pub duration_ms: u64,
+    /// Why a step was skipped or could not be started.
+    pub reason: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum WorkspaceStepStream {
+    Stdout,
+    Stderr,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase",
+    tag = "event",
+    content = "data"
+)]
+pub enum WorkspaceEnsureEvent {
+    Started {
+        step_id: String,
+        label: String,
+    },
+    Output {
+        step_id: String,
+        stream: WorkspaceStepStream,
+        line: String,
+    },
+    Finished {
+        result: WorkspaceStepResult,
+    },
+}
+
+pub(crate) fn resolve_step_path(root: &Path, path: &str) -> PathBuf {
+    let path = Path::new(path.trim());
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        root.join(path)
+    }
+}
+
+fn is_executable(path: &Path) -> bool {
+    let Ok(metadata) = path.metadata() else {
+        return false;
+    };
+    #[cfg(unix)]
+    {
+        metadata.is_file() && metadata.permissions().mode() & 0o111 != 0
+    }
+    #[cfg(not(unix))]
+    {
+        metadata.is_file()
+    }
+}
+
+pub(crate) fn command_exists(command: &str, path_env: &str) -> bool {
+    let command = command.trim();
+    if command.is_empty() {
+        return false;
+    }
+    if command.contains('/') {
+        return is_executable(Path::new(command));
+    }
+    std::env::split_paths(path_env).any(|dir| is_executable(&dir.join(command)))
+}
+
+/// Returns the reason a step should be skipped, if any. Every condition that
+/// is set must hold for the step to run.
+pub(crate) fn unmet_condition(
+    condition: &WorkspaceStepCondition,
+    root: &Path,
+    path_env: &str,
+) -> Option<String> {
+    if let Some(path) = condition.path_exists.as_deref() {
+        if !resolve_step_path(root, path).exists() {
+            return Some(format!("{} does not exist", path));
+        }
+    }
+    if let Some(path) = condition.path_missing.as_deref() {
+        if resolve_step_path(root, path).exists() {
+            return Some(format!("{} already exists", path));
+        }
+    }
+    if let Some(command) = condition.command_exists.as_deref() {
+        if !command_exists(command, path_env) {
+            return Some(format!("{} is not on PATH", command));
+        }
+    }
+    None
+}
+
+pub(crate) fn step_path_env() -> String {
+    login_shell_path()
+        .or_else(|| std::env::var("PATH").ok())
+        .unwrap_or_default()
+}
+
+fn forward_lines<R: Read + Send + 'static>(
+    reader: R,
+    step_id: String,
+    stream: WorkspaceStepStream,
+    events: Channel<WorkspaceEnsureEvent>,
+) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        for line in BufReader::new(reader).lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            let _ = events.send(WorkspaceEnsureEvent::Output {
+                step_id: step_id.clone(),
+                stream,
+                line,
+            });
+        }
+    })
+}
+
+fn run_ensure_step(
+    step: &WorkspaceEnsureStep,
+    root: &Path,
+    shell_path: &str,
+    path_env: &str,
+    env: &ResolvedEnv,
+    events: &Channel<WorkspaceEnsureEvent>,
+) -> WorkspaceStepResult {
+    let started = Instant::now();
+    let result = |status, exit_code, reason| WorkspaceStepResult {
+        step_id: step.id.clone(),
+        status,
+        exit_code,
+        duration_ms: started.elapsed().as_millis() as u64,
+        reason,
+    };
+
+    if let Some(reason) = step
+        .when
+        .as_ref()
+        .and_then(|condition| unmet_condition(condition, root, path_env))
+    {
+        return result(WorkspaceStepStatus::Skipped, None, Some(reason));
+    }
+
+    let _ = events.send(WorkspaceEnsureEvent::Started {
+        step_id: step.id.clone(),
+        label: step.label.clone(),
+    });
+
+    let interpolated = env.interpolate(&step.run).and_then(|run| {
+        let cwd = match step.cwd.as_deref() {
+            Some(cwd) if !cwd.trim().is_empty() => resolve_step_path(root, &env.interpolate(cwd)?),
+            _ => root.to_path_buf(),
+        };
+        Ok((run, cwd))
+    });
+    let (run, cwd) = match interpolated {
+        Ok(interpolated) => interpolated,
+        Err(error) => return result(WorkspaceStepStatus::Failed, None, Some(error)),
+    };
+    let spawned = Command::new(shell_path)
+        .args(["-l", "-c", &run])
+        .current_dir(&cwd)
+        .env("PATH", path_env)
+        .envs(env.pairs())
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn();
+    let mut child = match spawned {
+        Ok(child) => child,
+        Err(error) => {
+            return result(
+                WorkspaceStepStatus::Failed,
+                None,
+                Some(format!("Failed to start step in {}: {}", cwd.display(), error)),
+            );
+        }
+    };
+
+    let readers: Vec<_> = [
+        child.stdout.take().map(|stdout| {
+            forward_lines(stdout, step.id.clone(), WorkspaceStepStream::Stdout, events.clone())
+        }),
+        child.stderr.take().map(|stderr| {
+            forward_lines(stderr, step.id.clone(), WorkspaceStepStream::Stderr, events.clone())
+        }),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+
+    let status = child.wait();
+    for reader in readers {
+        let _ = reader.join();
+    }
+
+    match status {
+        Ok(status) if status.success() => result(WorkspaceStepStatus::Ok, status.code(), None),
+        Ok(status) => result(WorkspaceStepStatus::Failed, status.code(), None),
+        Err(error) => result(
+            WorkspaceStepStatus::Failed,
+            None,
+            Some(format!("Failed to wait for step: {}", error)),
+        ),
+    }
+}
+
+/// Runs ensure steps in order. A failed step stops the run; the remaining
+/// steps are reported as skipped.
+pub(crate) fn run_ensure_steps(
+    environment_path: &Path,
+    steps: &[WorkspaceEnsureStep],
+    env: &ResolvedEnv,
+    events: &Channel<WorkspaceEnsureEvent>,
+) -> Vec<WorkspaceStepResult> {
+    let shell_path = resolve_user_shell_path();
+    let path_env = step_path_env();
+    let mut results = Vec::with_capacity(steps.len());
+    let mut failed_step: Option<&str> = None;
+
+    for step in steps {
+        let result = match failed_step {
+            Some(failed) => WorkspaceStepResult {
+                step_id: step.id.clone(),
+                status: WorkspaceStepStatus::Skipped,
+                exit_code: None,
+                duration_ms: 0,
+                reason: Some(format!("Step {} failed", failed)),
+            },
+            None => run_ensure_step(step, environment_path, &shell_path, &path_env, env, events),
+        };
+        if result.status == WorkspaceStepStatus::Failed {
+            failed_step = Some(&step.id);
+        }
+        let _ = events.send(WorkspaceEnsureEvent::Finished {
+            result: result.clone(),
+        });
+        results.push(result);
+    }
+
+    results
+}
+
+#[tauri::command]
+pub async fn workspace_run_ensure_steps(
+    environment_path: String,
+    steps: Vec<WorkspaceEnsureStep>,
+    env_spec: Option<WorkspaceEnvSpec>,
+    on_event: Channel<WorkspaceEnsureEvent>,
+) -> Result<Vec<WorkspaceStepResult>, String> {
+    let root = PathBuf::from(environment_path.trim());
+    if !root.is_dir() {
+        return Err(format!("Environment path {} is not a directory", root.display()));
+    }
+    let env = resolve_env_for(env_spec, &environment_path)?;
+
+    tauri::async_runtime::spawn_blocking(move || run_ensure_steps(&root, &steps, &env, &on_event))
+        .await
+        .map_err(|error| format!("Ensure steps did not complete: {}", error))
+}
@@ -0,0 +1,466 @@
+use crate::shell_env::login_shell_env;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+const MASK: &str = "********";
+const MIN_MASKED_LEN: usize = 4;
+const PORT_RANGE_START: u16 = 19100;
+const PORT_RANGE_LEN: u16 = 1000;
+const SECRET_NAME_MARKERS: &[&str] = &[
+    "SECRET",
+    "TOKEN",
+    "PASSWORD",
+    "PASSWD",
+    "CREDENTIAL",
+    "PRIVATE",
+    "API_KEY",
+    "ACCESS_KEY",
+];
+
+/// Environment requested for a process: literal `env` entries plus `.env`
+/// files, both of which may use `${...}` variables.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceEnvSpec {
+    pub workspace_root: Option<String>,
+    pub environment_path: Option<String>,
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    #[serde(default)]
+    pub env_files: Vec<String>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolvedEnvVar {
+    pub name: String,
+    pub value: String,
+    pub secret: bool,
+    /// The env file that defined the variable, or `None` for `env` entries.
+    pub source: Option<String>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ResolvedEnv {
+    workspace_root: Option<PathBuf>,
+    environment_path: Option<PathBuf>,
+    vars: Vec<ResolvedEnvVar>,
+}
+
+fn allocated_ports() -> &'static Mutex<HashMap<(PathBuf, String), u16>> {
+    static PORTS: OnceLock<Mutex<HashMap<(PathBuf, String), u16>>> = OnceLock::new();
+    PORTS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn loopback_port_available(port: u16) -> bool {
+    let ipv4_available = TcpListener::bind(("127.0.0.1", port)).is_ok();
+    let ipv6_available = TcpListener::bind(("::1", port)).is_ok();
+    ipv4_available && ipv6_available
+}
+
+/// Runtimes and `${port:NAME}` share one range and one reservation table, so
+/// a port promised to one is never handed to the other before it is bound.
+fn next_free_port(reserved: &HashMap<(PathBuf, String), u16>, in_use: &[u16]) -> Option<u16> {
+    (PORT_RANGE_START..PORT_RANGE_START + PORT_RANGE_LEN).find(|port| {
+        !in_use.contains(port)
+            && !reserved.values().any(|reserved| reserved == port)
+            && loopback_port_available(*port)
+    })
+}
+
+/// Picks a free port for `${port:NAME}`. The same name in the same
+/// environment resolves to the same port for the lifetime of the app.
+fn named_port(scope: &Path, name: &str) -> Result<u16, String> {
+    let mut ports = allocated_ports()
+        .lock()
+        .map_err(|_| "Failed to lock port allocations".to_string())?;
+    let key = (scope.to_path_buf(), name.to_string());
+    if let Some(port) = ports.get(&key) {
+        return Ok(*port);
+    }
+    let port = next_free_port(&ports, &[])
+        .ok_or_else(|| format!("Failed to allocate port {}: no free port", name))?;
+    ports.insert(key, port);
+    Ok(port)
+}
+
+/// Reserves a fresh port for a runtime, replacing its previous reservation.
+/// `in_use` lists ports held by runtimes adopted from an earlier session.
+pub(crate) fn reserve_runtime_port(
+    scope: &Path,
+    name: &str,
+    in_use: &[u16],
+) -> Result<u16, String> {
+    let mut ports = allocated_ports()
+        .lock()
+        .map_err(|_| "Failed to lock port allocations".to_string())?;
+    let key = (scope.to_path_buf(), name.to_string());
+    ports.remove(&key);
+    let port = next_free_port(&ports, in_use).ok_or_else(|| {
+        format!(
+            "No free port between {} and {}",
+            PORT_RANGE_START,
+            PORT_RANGE_START + PORT_RANGE_LEN - 1
+        )
+    })?;
+    ports.insert(key, port);
+    Ok(port)
+}
+
+fn is_secret_name(name: &str) -> bool {
+    let upper = name.to_ascii_uppercase();
+    upper.ends_with("_KEY")
+        || SECRET_NAME_MARKERS
+            .iter()
+            .any(|marker| upper.contains(marker))
+}
+
+fn unquote(value: &str) -> String {
+    let value = value.trim();
+    if value.len() >= 2 && value.starts_with('"') && value.ends_with('"') {
+        // One pass, so an escaped backslash is never read as the start of
+        // another escape.
+        let mut output = String::with_capacity(value.len());
+        let mut chars = value[1..value.len() - 1].chars();
+        while let Some(ch) = chars.next() {
+            if ch != '\\' {
+                output.push(ch);
+                continue;
+            }
+            match chars.next() {
+                Some('n') => output.push('\n'),
+                Some(escaped @ ('"' | '\\')) => output.push(escaped),
+                Some(other) => {
+                    output.push('\\');
+                    output.push(other);
+                }
+                None => output.push('\\'),
+            }
+        }
+        return output;
+    }
+    if value.len() >= 2 && value.starts_with('\'') && value.ends_with('\'') {
+        return value[1..value.len() - 1].to_string();
+    }
+    // Unquoted values may carry a trailing ` # comment`.
+    match value.find(" #") {
+        Some(index) => value[..index].trim_end().to_string(),
+        None => value.to_string(),
+    }
+}
+
+/// Parses `KEY=VALUE` lines, ignoring blanks, comments and `export `.
+pub(crate) fn parse_env_file(text: &str) -> Vec<(String, String)> {
+    text.lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                return None;
+            }
+            let line = line.strip_prefix("export ").unwrap_or(line);
+            let (name, value) = line.split_once('=')?;
+            let name = name.trim();
+            if name.is_empty() || name.contains(char::is_whitespace) {
+                return None;
+            }
+            Some((name.to_string(), unquote(value)))
+        })
+        .collect()
+}
+
+impl ResolvedEnv {
+    fn context(workspace_root: Option<&str>, environment_path: Option<&str>) -> Self {
+        let path = |value: Option<&str>| {
+            value
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(PathBuf::from)
+        };
+        let workspace_root = path(workspace_root);
+        let environment_path = path(environment_path).or_else(|| workspace_root.clone());
+        Self {
+            workspace_root: workspace_root.or_else(|| environment_path.clone()),
+            environment_path,
+            vars: Vec::new(),
+        }
+    }
+
+    fn lookup(&self, name: &str) -> Option<String> {
+        self.vars
+            .iter()
+            .rev()
+            .find(|var| var.name == name)
+            .map(|var| var.value.clone())
+            .or_else(|| match login_shell_env() {
+                Some(env) => env.get(name).cloned(),
+                None => std::env::var(name).ok(),
+            })
+    }
+
+    fn set(&mut self, var: ResolvedEnvVar) {
+        self.vars.retain(|existing| existing.name != var.name);
+        self.vars.push(var);
+    }
+
+    /// Returns `None` for anything that is not one of our variables so shell
+    /// expansions like `${HOME}` reach the shell untouched.
+    fn resolve_variable(&self, variable: &str) -> Option<Result<String, String>> {
+        let path_text = |path: &Option<PathBuf>| {
+            path.as_ref()
+                .map(|path| path.to_string_lossy().to_string())
+                .ok_or_else(|| format!("${{{}}} is not available here", variable))
+        };
+        match variable.split_once(':') {
+            Some(("env", name)) => Some(
+                self.lookup(name.trim())
+                    .ok_or_else(|| format!("${{{}}} is not set", variable)),
+            ),
+            Some(("port", name)) => {
+                let scope = self.environment_path.clone().unwrap_or_default();
+                Some(named_port(&scope, name.trim()).map(|port| port.to_string()))
+            }
+            None if variable == "workspaceRoot" => Some(path_text(&self.workspace_root)),
+            None if variable == "environmentPath" => Some(path_text(&self.environment_path)),
+            _ => None,
+        }
+    }
+
+    /// Expands `${env:NAME}`, `${workspaceRoot}`, `${environmentPath}` and
+    /// `${port:NAME}`; any other `${...}` is left as written. A variable
+    /// that is set neither here nor in the login shell is an error.
+    pub fn interpolate(&self, text: &str) -> Result<String, String> {
+        let mut output = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(start) = rest.find("${") {
+            output.push_str(&rest[..start]);
+            let after = &rest[start + 2..];
+            let Some(end) = after.find('}') else {
+                rest = &rest[start..];
+                break;
+            };
+            match self.resolve_variable(after[..end].trim()) {
+                Some(value) => output.push_str(&value?),
+                None => output.push_str(&rest[start..start + 2 + end + 1]),
+            }
+            rest = &after[end + 1..];
+        }
+        output.push_str(rest);
+        Ok(output)
+    }
+
+    fn resolve_path(&self, path: &str) -> Result<PathBuf, String> {
+        let path = PathBuf::from(self.interpolate(path.trim())?);
+        if path.is_absolute() {
+            return Ok(path);
+        }
+        Ok(self
+            .environment_path
+            .as_ref()
+            .map(|root| root.join(&path))
+            .unwrap_or(path))
+    }
+
+    pub fn vars(&self) -> &[ResolvedEnvVar] {
+        &self.vars
+    }
+
+    pub fn pairs(&self) -> Vec<(String, String)> {
+        self.vars
+            .iter()
+            .map(|var| (var.name.clone(), var.value.clone()))
+            .collect()
+    }
+
+    /// `NAME=value` pairs with secrets masked, for log lines.
+    pub fn describe(&self) -> String {
+        self.vars
+            .iter()
+            .map(|var| {
+                if var.secret {
+                    format!("{}={}", var.name, MASK)
+                } else {
+                    format!("{}={}", var.name, var.value)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Secret values long enough to mask, longest first so a secret that
+    /// contains another is masked whole.
+    pub fn secrets(&self) -> Vec<String> {
+        let mut secrets: Vec<String> = self
+            .vars
+            .iter()
+            .filter(|var| var.secret && var.value.len() >= MIN_MASKED_LEN)
+            .map(|var| var.value.clone())
+            .collect();
+        secrets.sort_by_key(|value| std::cmp::Reverse(value.len()));
+        secrets
+    }
+
+    /// Replaces secret values in `text`, e.g. before it is written to a log.
+    pub fn mask(&self, text: &str) -> String {
+        self.secrets()
+            .iter()
+            .fold(text.to_string(), |masked, secret| {
+                masked.replace(secret, MASK)
+            })
+    }
+}
+
+/// Byte-level `ResolvedEnv::mask` for process output, which need not be
+/// UTF-8. `secrets` comes from `ResolvedEnv::secrets`.
+pub(crate) fn mask_secrets(text: &[u8], secrets: &[String]) -> Vec<u8> {
+    let mut masked = text.to_vec();
+    for secret in secrets {
+        let secret = secret.as_bytes();
+        let mut output = Vec::with_capacity(masked.len());
+        let mut rest = masked.as_slice();
+        while let Some(index) = rest
+            .windows(secret.len())
+            .position(|window| window == secret)
+        {
+            output.extend_from_slice(&rest[..index]);
+            output.extend_from_slice(MASK.as_bytes());
+            rest = &rest[index + secret.len()..];
+        }
+        output.extend_from_slice(rest);
+        masked = output;
+    }
+    masked
+}
+
+/// Loads env files in order, then applies `env` entries on top. Values from
+/// env files are treated as secrets; `env` entries only when their name
+/// looks like one or they pull in a secret through `${env:NAME}`.
+pub fn resolve_workspace_env(spec: &WorkspaceEnvSpec) -> Result<ResolvedEnv, String> {
+    let mut resolved = ResolvedEnv::context(
+        spec.workspace_root.as_deref(),
+        spec.environment_path.as_deref(),
+    );
+
+    for env_file in &spec.env_files {
+        let path = resolved.resolve_path(env_file)?;
+        let text = fs::read_to_string(&path)
+            .map_err(|error| format!("Failed to read env file {}: {}", path.display(), error))?;
+        let source = path.to_string_lossy().to_string();
+        for (name, value) in parse_env_file(&text) {
+            resolved.set(ResolvedEnvVar {
+                name,
+                value,
+                secret: true,
+                source: Some(source.clone()),
+            });
+        }
+    }
+
+    let mut entries = Vec::with_capacity(spec.env.len());
+    for (name, value) in &spec.env {
+        let interpolated = resolved.interpolate(value)?;
+        let secret = is_secret_name(name) || resolved.mask(&interpolated) != interpolated;
+        entries.push(ResolvedEnvVar {
+            name: name.clone(),
+            value: interpolated,
+            secret,
+            source: None,
+        });
+    }
+    for entry in entries {
+        resolved.set(entry);
+    }
+    Ok(resolved)
+}
+
+/// Resolves `spec` for a process started in `default_path`, which also
+/// stands in for `workspaceRoot` and `environmentPath` when they are unset.
+pub fn resolve_env_for(
+    spec: Option<WorkspaceEnvSpec>,
+    default_path: &str,
+) -> Result<ResolvedEnv, String> {
+    let mut spec = spec.unwrap_or_default();
+    let default_path = Some(default_path.trim().to_string()).filter(|path| !path.is_empty());
+    if spec.environment_path.is_none() {
+        spec.environment_path = default_path.clone();
+    }
+    if spec.workspace_root.is_none() {
+        spec.workspace_root = default_path;
+    }
+    resolve_workspace_env(&spec)
+}
+
+/// Resolves the environment for display. Secret values are masked unless
+/// `reveal` is set.
+#[tauri::command]
+pub fn workspace_resolve_env(
+    spec: WorkspaceEnvSpec,
+    reveal: Option<bool>,
+) -> Result<Vec<ResolvedEnvVar>, String> {
+    let resolved = resolve_workspace_env(&spec)?;
+    Ok(resolved
+        .vars
+        .into_iter()
+        .map(|var| {
+            if var.secret && !reveal.unwrap_or(false) {
+                ResolvedEnvVar {
+                    value: MASK.to_string(),
+                    ..var
+                }
+            } else {
+                var
+            }
+        })
+        .collect())
+}
+
+#[tauri::command]
+pub fn workspace_interpolate(spec: WorkspaceEnvSpec, text: String) -> Result<String, String> {
+    resolve_workspace_env(&spec)?.interpolate(&text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_escaped_quotes_in_double_quoted_values() {
+        let vars = parse_env_file(
+            "export GREETING=\"say \\\"hi\\\"\\n\"\nPATH_LIKE=\"C:\\\\dir\\\\\"\nRAW='a \\\" b'\n",
+        );
+        assert_eq!(
+            vars,
+            vec![
+                ("GREETING".to_string(), "say \"hi\"\n".to_string()),
+                ("PATH_LIKE".to_string(), "C:\\dir\\".to_string()),
+                ("RAW".to_string(), "a \\\" b".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn strips_trailing_comments_from_unquoted_values() {
+        let vars = parse_env_file("# comment\n\nPORT=3000 # dev server\n");
+        assert_eq!(vars, vec![("PORT".to_string(), "3000".to_string())]);
+    }
+
+    #[test]
+    fn leaves_unknown_variables_for_the_shell() {
+        let mut env = ResolvedEnv::context(Some("/work"), Some("/work/app"));
+        env.set(ResolvedEnvVar {
+            name: "NAME".to_string(),
+            value: "otto".to_string(),
+            secret: false,
+            source: None,
+        });
+        assert_eq!(
+            env.interpolate("${unknown} ${HOME} ${env:NAME} ${workspaceRoot} ${environmentPath}")
+                .unwrap(),
+            "${unknown} ${HOME} otto /work /work/app"
+        );
+        assert_eq!(env.interpolate("${unterminated").unwrap(), "${unterminated");
+    }
+}
@@ -1,6 +1,83 @@
-use std::{fs, path::PathBuf};
+use crate::workspace_history::record_revision;
+use crate::workspace_schema::{check_workspace_file, WorkspaceFileCheck};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::{
+    collections::HashMap,
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, SystemTime},
+};
+use tauri::{AppHandle, Emitter, State, Wry};
 
-fn otto_file_path(project_path: &str) -> Result<PathBuf, String> {
+const WATCH_POLL_INTERVAL: Duration = Duration::from_millis(750);
+const WORKSPACE_FILE_CHANGED_EVENT: &str = "workspace-file-changed";
+
+/// Prefix of the error returned when a write is rejected because otto.yaml
+/// changed on disk after the caller read it.
+pub const WORKSPACE_FILE_CONFLICT: &str = "Workspace file changed on disk";
+
+/// Serializes our writes with each other and with the watchers' change
+/// checks, so a watcher never sees our own write before its token is known.
+static WRITE_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceFileContents {
+    pub content: String,
+    /// Opaque concurrency token; pass it back to `workspace_file_write`.
+    pub token: String,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceFileChangedPayload {
+    pub project_path: String,
+    pub file_path: String,
+    /// `None` when the file was deleted.
+    pub token: Option<String>,
+}
+
+struct WorkspaceFileWatch {
+    cancelled: Arc<AtomicBool>,
+    known_token: Arc<Mutex<Option<String>>>,
+}
+
+/// Polls watched otto.yaml files and emits `workspace-file-changed` when
+/// their content changes for any reason other than our own writes.
+#[derive(Clone, Default)]
+pub struct WorkspaceFileWatcherManager {
+    watches: Arc<Mutex<HashMap<PathBuf, WorkspaceFileWatch>>>,
+}
+
+impl WorkspaceFileWatcherManager {
+    /// Replaces the token the watcher of `file_path` considers current and
+    /// returns the previous one. Callers must hold `WRITE_LOCK`.
+    fn swap_known_token(&self, file_path: &Path, token: Option<String>) -> Option<String> {
+        let Ok(watches) = self.watches.lock() else {
+            return None;
+        };
+        let watch = watches.get(file_path)?;
+        let mut known = watch.known_token.lock().ok()?;
+        std::mem::replace(&mut *known, token)
+    }
+
+    pub fn stop_all(&self) {
+        if let Ok(mut watches) = self.watches.lock() {
+            for (_, watch) in watches.drain() {
+                watch.cancelled.store(true, Ordering::SeqCst);
+            }
+        }
+    }
+}
+
+pub(crate) fn otto_file_path(project_path: &str) -> Result<PathBuf, String> {
     let trimmed = project_path.trim();
     if trimmed.is_empty() {
         return Err("Project path is required".to_string());
@@ -8,6 +85,52 @@ fn otto_file_path(project_path: &str) -> Result<PathBuf, String> {
     Ok(PathBuf::from(trimmed).join("otto.yaml"))
 }
 
+pub(crate) fn content_token(content: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(content))
+}
+
+fn current_token(file_path: &Path) -> Result<Option<String>, String> {
+    match fs::read(file_path) {
+        Ok(content) => Ok(Some(content_token(&content))),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(format!("Failed to read {}: {error}", file_path.display())),
+    }
+}
+
+/// Writes through a temp file in the same directory and renames it over the
+/// target, so readers never observe a partially written file.
+pub(crate) fn write_file_atomic(file_path: &Path, content: &[u8]) -> Result<(), String> {
+    // Replace the target of a symlinked otto.yaml rather than the link.
+    let file_path = &fs::canonicalize(file_path).unwrap_or_else(|_| file_path.to_path_buf());
+    let dir = file_path
+        .parent()
+        .ok_or_else(|| format!("Invalid workspace file path {}", file_path.display()))?;
+    let file_name = file_path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| "otto.yaml".to_string());
+    let temp_path = dir.join(format!(".{}.{}.tmp", file_name, std::process::id()));
+
+    let result = (|| {
+        let mut file = fs::File::create(&temp_path)?;
+        file.write_all(content)?;
+        file.sync_all()?;
+        if let Ok(metadata) = fs::metadata(file_path) {
+            fs::set_permissions(&temp_path, metadata.permissions())?;
+        }
+        fs::rename(&temp_path, file_path)
+    })();
+    if let Err(error) = result {
+        let _ = fs::remove_file(&temp_path);
+        return Err(format!("Failed to write {}: {error}", file_path.display()));
+    }
+
+    if let Ok(dir) = fs::File::open(dir) {
+        let _ = dir.sync_all();
+    }
+    Ok(())
+}
+
 #[tauri::command]
 pub fn workspace_file_exists(project_path: String) -> Result<bool, String> {
     let file_path = otto_file_path(&project_path)?;
@@ -22,8 +145,192 @@ pub fn workspace_file_read(project_path: String) -> Result<String, String> {
 }
 
 #[tauri::command]
-pub fn workspace_file_write(project_path: String, content: String) -> Result<(), String> {
+pub fn workspace_file_read_versioned(project_path: String) -> Result<WorkspaceFileContents, String> {
+    let content = workspace_file_read(project_path)?;
+    let token = content_token(content.as_bytes());
+    Ok(WorkspaceFileContents { content, token })
+}
+
+/// Atomically replaces otto.yaml and returns the new token. When
+/// `expected_token` is given and the file no longer matches it, nothing is
+/// written and an error starting with `WORKSPACE_FILE_CONFLICT` is returned.
+#[tauri::command]
+pub fn workspace_file_write(
+    app_handle: AppHandle<Wry>,
+    watcher: State<'_, WorkspaceFileWatcherManager>,
+    project_path: String,
+    content: String,
+    expected_token: Option<String>,
+) -> Result<String, String> {
     let file_path = otto_file_path(&project_path)?;
-    fs::write(&file_path, content)
-        .map_err(|error| format!("Failed to write {}: {error}", file_path.display()))
+    write_workspace_file(
+        &app_handle,
+        &watcher,
+        &file_path,
+        &content,
+        expected_token.as_deref(),
+    )
+}
+
+pub(crate) fn write_workspace_file(
+    app_handle: &AppHandle<Wry>,
+    watcher: &WorkspaceFileWatcherManager,
+    file_path: &Path,
+    content: &str,
+    expected_token: Option<&str>,
+) -> Result<String, String> {
+    let _guard = WRITE_LOCK
+        .lock()
+        .map_err(|_| "Failed to lock workspace file writes".to_string())?;
+
+    let previous = match fs::read_to_string(file_path) {
+        Ok(previous) => Some(previous),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => None,
+        Err(error) => return Err(format!("Failed to read {}: {error}", file_path.display())),
+    };
+    if let Some(expected) = expected_token {
+        match previous.as_deref().map(|previous| content_token(previous.as_bytes())) {
+            Some(current) if current == expected => {}
+            Some(_) => {
+                return Err(format!(
+                    "{}: {} was modified by another program",
+                    WORKSPACE_FILE_CONFLICT,
+                    file_path.display()
+                ))
+            }
+            None => {
+                return Err(format!(
+                    "{}: {} was deleted",
+                    WORKSPACE_FILE_CONFLICT,
+                    file_path.display()
+                ))
+            }
+        }
+    }
+
+    // Keep the content being replaced too, so edits made outside the app
+    // since our last write are not lost from the history.
+    if let Some(previous) = previous.as_deref() {
+        if let Err(error) = record_revision(app_handle, file_path, previous) {
+            eprintln!("[canvas] workspace history: {}", error);
+        }
+    }
+    // The watcher must know the new token before the rename lands, or it
+    // reports our own write as an external change.
+    let token = content_token(content.as_bytes());
+    let known = watcher.swap_known_token(file_path, Some(token.clone()));
+    if let Err(error) = write_file_atomic(file_path, content.as_bytes()) {
+        watcher.swap_known_token(file_path, known);
+        return Err(error);
+    }
+    if let Err(error) = record_revision(app_handle, file_path, content) {
+        eprintln!("[canvas] workspace history: {}", error);
+    }
+    Ok(token)
+}
+
+/// Checks otto.yaml against the typed schema. Pass `content` to validate
+/// unsaved text instead of the file on disk.
+#[tauri::command]
+pub fn workspace_file_validate(
+    project_path: String,
+    content: Option<String>,
+) -> Result<WorkspaceFileCheck, String> {
+    let content = match content {
+        Some(content) => content,
+        None => workspace_file_read(project_path)?,
+    };
+    Ok(check_workspace_file(&content))
+}
+
+fn file_stamp(file_path: &Path) -> Option<(SystemTime, u64)> {
+    let metadata = fs::metadata(file_path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+#[tauri::command]
+pub fn workspace_file_watch(
+    app_handle: AppHandle<Wry>,
+    watcher: State<'_, WorkspaceFileWatcherManager>,
+    project_path: String,
+) -> Result<Option<String>, String> {
+    let file_path = otto_file_path(&project_path)?;
+    let mut watches = watcher
+        .watches
+        .lock()
+        .map_err(|_| "Failed to lock workspace file watches".to_string())?;
+    if let Some(watch) = watches.get(&file_path) {
+        return Ok(watch.known_token.lock().ok().and_then(|token| token.clone()));
+    }
+
+    let token = current_token(&file_path)?;
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let known_token = Arc::new(Mutex::new(token.clone()));
+    watches.insert(
+        file_path.clone(),
+        WorkspaceFileWatch {
+            cancelled: cancelled.clone(),
+            known_token: known_token.clone(),
+        },
+    );
+    drop(watches);
+
+    let project_path = project_path.trim().to_string();
+    thread::spawn(move || {
+        let mut stamp = file_stamp(&file_path);
+        while !cancelled.load(Ordering::SeqCst) {
+            thread::sleep(WATCH_POLL_INTERVAL);
+            let next_stamp = file_stamp(&file_path);
+            if next_stamp == stamp {
+                continue;
+            }
+            stamp = next_stamp;
+
+            // mtime alone is noisy (touch, checkout of identical content), so
+            // only content changes are reported.
+            let Ok(write_guard) = WRITE_LOCK.lock() else {
+                return;
+            };
+            let Ok(token) = current_token(&file_path) else {
+                continue;
+            };
+            let Ok(mut known) = known_token.lock() else {
+                return;
+            };
+            if *known == token {
+                continue;
+            }
+            *known = token.clone();
+            drop(known);
+            drop(write_guard);
+
+            let _ = app_handle.emit(
+                WORKSPACE_FILE_CHANGED_EVENT,
+                WorkspaceFileChangedPayload {
+                    project_path: project_path.clone(),
+                    file_path: file_path.to_string_lossy().to_string(),
+                    token,
+                },
+            );
+        }
+    });
+
+    Ok(token)
+}
+
+#[tauri::command]
+pub fn workspace_file_unwatch(
+    watcher: State<'_, WorkspaceFileWatcherManager>,
+    project_path: String,
+) -> Result<(), String> {
+    let file_path = otto_file_path(&project_path)?;
+    if let Some(watch) = watcher
+        .watches
+        .lock()
+        .map_err(|_| "Failed to lock workspace file watches".to_string())?
+        .remove(&file_path)
+    {
+        watch.cancelled.store(true, Ordering::SeqCst);
+    }
+    Ok(())
 }
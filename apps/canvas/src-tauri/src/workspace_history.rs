@@ -0,0 +1,416 @@
+use crate::workspace_file::{
+    content_token, otto_file_path, write_workspace_file, WorkspaceFileWatcherManager,
+};
+use crate::workspace_schema::{migrate_workspace_document, OttoWorkspaceFile};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager, State, Wry};
+
+const MAX_REVISIONS: usize = 50;
+/// Above this many line pairs the diff falls back to replacing everything.
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceRevisionItem {
+    pub id: String,
+    pub label: String,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceRevisionSummary {
+    pub tabs_added: Vec<WorkspaceRevisionItem>,
+    pub tabs_removed: Vec<WorkspaceRevisionItem>,
+    pub blocks_added: Vec<WorkspaceRevisionItem>,
+    pub blocks_removed: Vec<WorkspaceRevisionItem>,
+    /// Set when either side could not be parsed, so only the text diff is
+    /// meaningful.
+    #[serde(default)]
+    pub unparsed: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceFileRevision {
+    pub revision_id: u64,
+    pub created_at_ms: u64,
+    pub token: String,
+    pub size: u64,
+    /// Changes relative to the previous revision.
+    pub summary: WorkspaceRevisionSummary,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum WorkspaceDiffLineKind {
+    Context,
+    Added,
+    Removed,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceDiffLine {
+    pub kind: WorkspaceDiffLineKind,
+    pub text: String,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceRevisionDiff {
+    pub summary: WorkspaceRevisionSummary,
+    pub lines: Vec<WorkspaceDiffLine>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RevisionIndex {
+    next_id: u64,
+    revisions: Vec<WorkspaceFileRevision>,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// FNV-1a, used for directory names because it is stable across builds.
+fn stable_hash(text: &str) -> u64 {
+    text.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+fn history_dir(app_handle: &AppHandle<Wry>, file_path: &Path) -> Result<PathBuf, String> {
+    let file_path = fs::canonicalize(file_path).unwrap_or_else(|_| file_path.to_path_buf());
+    let project_name = file_path
+        .parent()
+        .and_then(|parent| parent.file_name())
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| "workspace".to_string());
+    let project_name: String = project_name
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() || ch == '-' || ch == '_' {
+                ch
+            } else {
+                '-'
+            }
+        })
+        .take(48)
+        .collect();
+    let app_data_dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|error| format!("Failed to resolve app data dir: {}", error))?;
+    Ok(app_data_dir.join("workspace-history").join(format!(
+        "{}-{:016x}",
+        project_name,
+        stable_hash(&file_path.to_string_lossy())
+    )))
+}
+
+fn revision_path(dir: &Path, revision_id: u64) -> PathBuf {
+    dir.join(format!("{:06}.yaml", revision_id))
+}
+
+fn read_index(dir: &Path) -> RevisionIndex {
+    fs::read(dir.join("index.json"))
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default()
+}
+
+fn write_index(dir: &Path, index: &RevisionIndex) -> Result<(), String> {
+    let bytes = serde_json::to_vec_pretty(index).map_err(|error| error.to_string())?;
+    let temp_path = dir.join("index.json.tmp");
+    fs::write(&temp_path, bytes)
+        .and_then(|_| fs::rename(&temp_path, dir.join("index.json")))
+        .map_err(|error| format!("Failed to write revision index: {}", error))
+}
+
+fn read_revision(dir: &Path, revision_id: u64) -> Result<String, String> {
+    fs::read_to_string(revision_path(dir, revision_id))
+        .map_err(|_| format!("Revision {} was not found", revision_id))
+}
+
+fn tab_items(file: &OttoWorkspaceFile) -> BTreeMap<String, String> {
+    file.tabs
+        .iter()
+        .map(|tab| (tab.id().to_string(), tab.title().to_string()))
+        .collect()
+}
+
+fn block_items(file: &OttoWorkspaceFile) -> BTreeMap<String, String> {
+    file.tabs
+        .iter()
+        .flat_map(|tab| {
+            tab.blocks()
+                .iter()
+                .map(move |block| (format!("{}/{}", tab.id(), block.id), block.label.clone()))
+        })
+        .collect()
+}
+
+fn added(
+    before: &BTreeMap<String, String>,
+    after: &BTreeMap<String, String>,
+) -> Vec<WorkspaceRevisionItem> {
+    after
+        .iter()
+        .filter(|(id, _)| !before.contains_key(*id))
+        .map(|(id, label)| WorkspaceRevisionItem {
+            id: id.clone(),
+            label: label.clone(),
+        })
+        .collect()
+}
+
+/// Tabs and blocks added or removed between two versions of otto.yaml.
+fn summarize_change(before: Option<&str>, after: &str) -> WorkspaceRevisionSummary {
+    // Lenient on purpose: a revision with validation issues still has tabs.
+    let parse = |text: &str| {
+        let document = serde_yaml::from_str(text).ok()?;
+        let (document, _) = migrate_workspace_document(document).ok()?;
+        serde_yaml::from_value::<OttoWorkspaceFile>(document).ok()
+    };
+    let unparsed = || WorkspaceRevisionSummary {
+        unparsed: true,
+        ..WorkspaceRevisionSummary::default()
+    };
+    let Some(after) = parse(after) else {
+        return unparsed();
+    };
+    let (before_tabs, before_blocks) = match before.map(parse) {
+        Some(Some(before)) => (tab_items(&before), block_items(&before)),
+        Some(None) => return unparsed(),
+        None => (BTreeMap::new(), BTreeMap::new()),
+    };
+    let (after_tabs, after_blocks) = (tab_items(&after), block_items(&after));
+    WorkspaceRevisionSummary {
+        tabs_added: added(&before_tabs, &after_tabs),
+        tabs_removed: added(&after_tabs, &before_tabs),
+        blocks_added: added(&before_blocks, &after_blocks),
+        blocks_removed: added(&after_blocks, &before_blocks),
+        unparsed: false,
+    }
+}
+
+/// Line diff from `before` to `after` using the longest common subsequence.
+fn diff_lines(before: &str, after: &str) -> Vec<WorkspaceDiffLine> {
+    let old: Vec<&str> = before.lines().collect();
+    let new: Vec<&str> = after.lines().collect();
+    let line = |kind, text: &str| WorkspaceDiffLine {
+        kind,
+        text: text.to_string(),
+    };
+
+    if old.len().saturating_mul(new.len()) > MAX_DIFF_CELLS {
+        return old
+            .iter()
+            .map(|text| line(WorkspaceDiffLineKind::Removed, text))
+            .chain(
+                new.iter()
+                    .map(|text| line(WorkspaceDiffLineKind::Added, text)),
+            )
+            .collect();
+    }
+
+    // lengths[i][j] is the LCS length of old[i..] and new[j..].
+    let width = new.len() + 1;
+    let mut lengths = vec![0u32; (old.len() + 1) * width];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i * width + j] = if old[i] == new[j] {
+                lengths[(i + 1) * width + j + 1] + 1
+            } else {
+                lengths[(i + 1) * width + j].max(lengths[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut lines = Vec::with_capacity(old.len().max(new.len()));
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            lines.push(line(WorkspaceDiffLineKind::Context, old[i]));
+            i += 1;
+            j += 1;
+        } else if lengths[(i + 1) * width + j] >= lengths[i * width + j + 1] {
+            lines.push(line(WorkspaceDiffLineKind::Removed, old[i]));
+            i += 1;
+        } else {
+            lines.push(line(WorkspaceDiffLineKind::Added, new[j]));
+            j += 1;
+        }
+    }
+    lines.extend(
+        old[i..]
+            .iter()
+            .map(|text| line(WorkspaceDiffLineKind::Removed, text)),
+    );
+    lines.extend(
+        new[j..]
+            .iter()
+            .map(|text| line(WorkspaceDiffLineKind::Added, text)),
+    );
+    lines
+}
+
+/// Stores `content` as the newest revision of `file_path` unless it matches
+/// the newest one, then prunes to `MAX_REVISIONS`.
+pub(crate) fn record_revision(
+    app_handle: &AppHandle<Wry>,
+    file_path: &Path,
+    content: &str,
+) -> Result<(), String> {
+    let dir = history_dir(app_handle, file_path)?;
+    fs::create_dir_all(&dir)
+        .map_err(|error| format!("Failed to create history dir {}: {}", dir.display(), error))?;
+
+    let mut index = read_index(&dir);
+    let token = content_token(content.as_bytes());
+    let previous = index.revisions.last();
+    if previous.is_some_and(|previous| previous.token == token) {
+        return Ok(());
+    }
+    let previous_content =
+        previous.and_then(|previous| read_revision(&dir, previous.revision_id).ok());
+
+    let revision_id = index.next_id.max(1);
+    fs::write(revision_path(&dir, revision_id), content)
+        .map_err(|error| format!("Failed to store revision: {}", error))?;
+    index.next_id = revision_id + 1;
+    index.revisions.push(WorkspaceFileRevision {
+        revision_id,
+        created_at_ms: now_ms(),
+        token,
+        size: content.len() as u64,
+        summary: summarize_change(previous_content.as_deref(), content),
+    });
+
+    while index.revisions.len() > MAX_REVISIONS {
+        let pruned = index.revisions.remove(0);
+        let _ = fs::remove_file(revision_path(&dir, pruned.revision_id));
+    }
+    write_index(&dir, &index)
+}
+
+fn list_revisions(
+    app_handle: &AppHandle<Wry>,
+    file_path: &Path,
+) -> Result<Vec<WorkspaceFileRevision>, String> {
+    let dir = history_dir(app_handle, file_path)?;
+    let mut revisions = read_index(&dir).revisions;
+    revisions.reverse();
+    Ok(revisions)
+}
+
+fn revision_content(
+    app_handle: &AppHandle<Wry>,
+    file_path: &Path,
+    revision_id: u64,
+) -> Result<String, String> {
+    read_revision(&history_dir(app_handle, file_path)?, revision_id)
+}
+
+#[tauri::command]
+pub fn workspace_file_history_list(
+    app_handle: AppHandle<Wry>,
+    project_path: String,
+) -> Result<Vec<WorkspaceFileRevision>, String> {
+    let file_path = otto_file_path(&project_path)?;
+    list_revisions(&app_handle, &file_path)
+}
+
+/// Diffs a revision against `against`, or against otto.yaml on disk when
+/// `against` is not given.
+#[tauri::command]
+pub fn workspace_file_history_diff(
+    app_handle: AppHandle<Wry>,
+    project_path: String,
+    revision_id: u64,
+    against: Option<u64>,
+) -> Result<WorkspaceRevisionDiff, String> {
+    let file_path = otto_file_path(&project_path)?;
+    let before = revision_content(&app_handle, &file_path, revision_id)?;
+    let after = match against {
+        Some(against) => revision_content(&app_handle, &file_path, against)?,
+        None => fs::read_to_string(&file_path).unwrap_or_default(),
+    };
+    Ok(WorkspaceRevisionDiff {
+        summary: summarize_change(Some(&before), &after),
+        lines: diff_lines(&before, &after),
+    })
+}
+
+/// Writes a revision back to otto.yaml, recording the restore as a new
+/// revision. `expected_token` guards against clobbering unsaved external
+/// edits the same way `workspace_file_write` does.
+#[tauri::command]
+pub fn workspace_file_history_restore(
+    app_handle: AppHandle<Wry>,
+    watcher: State<'_, WorkspaceFileWatcherManager>,
+    project_path: String,
+    revision_id: u64,
+    expected_token: Option<String>,
+) -> Result<String, String> {
+    let file_path = otto_file_path(&project_path)?;
+    let content = revision_content(&app_handle, &file_path, revision_id)?;
+    write_workspace_file(
+        &app_handle,
+        &watcher,
+        &file_path,
+        &content,
+        expected_token.as_deref(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(lines: &[WorkspaceDiffLine]) -> Vec<(WorkspaceDiffLineKind, &str)> {
+        lines
+            .iter()
+            .map(|line| (line.kind, line.text.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn diffs_changed_lines_against_their_context() {
+        let lines = diff_lines("a\nb\nc\n", "a\nB\nc\nd\n");
+        assert_eq!(
+            kinds(&lines),
+            vec![
+                (WorkspaceDiffLineKind::Context, "a"),
+                (WorkspaceDiffLineKind::Removed, "b"),
+                (WorkspaceDiffLineKind::Added, "B"),
+                (WorkspaceDiffLineKind::Context, "c"),
+                (WorkspaceDiffLineKind::Added, "d"),
+            ]
+        );
+    }
+
+    #[test]
+    fn falls_back_to_a_full_replacement_for_large_files() {
+        let side = (MAX_DIFF_CELLS as f64).sqrt() as usize + 1;
+        let before: String = (0..side).map(|index| format!("line {index}\n")).collect();
+        let after = before.replacen("line 0\n", "first\n", 1);
+        let lines = diff_lines(&before, &after);
+        assert_eq!(lines.len(), side * 2);
+        assert!(lines[..side]
+            .iter()
+            .all(|line| line.kind == WorkspaceDiffLineKind::Removed));
+        assert!(lines[side..]
+            .iter()
+            .all(|line| line.kind == WorkspaceDiffLineKind::Added));
+        assert_eq!(lines[side].text, "first");
+    }
+}
@@ -0,0 +1,627 @@
+use crate::workspace_automation::WorkspaceEnsureStep;
+use crate::workspace_file::content_token;
+use crate::workspace_schema::{
+    parse_workspace_file, validate_workspace_file, OttoCanvasTab, OttoLayoutNode,
+    OttoSplitDirection, OttoWorkspaceFile, OttoWorkspaceTab, WorkspaceFileIssue,
+    OTTO_WORKSPACE_FILE_VERSION,
+};
+use crate::workspace_services::WorkspaceStartupStep;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const LOCAL_FILE_NAME: &str = "otto.local.yaml";
+const FRAGMENTS_DIR: &str = ".otto/workspace.d";
+
+/// A partial workspace file. Fragments and otto.local.yaml only list what
+/// they add or replace.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OttoWorkspaceOverlay {
+    version: Option<u64>,
+    workspace: Option<OttoWorkspaceOverlayMeta>,
+    #[serde(default)]
+    ensure: Vec<WorkspaceEnsureStep>,
+    #[serde(default)]
+    startup: Vec<WorkspaceStartupStep>,
+    #[serde(default)]
+    env: BTreeMap<String, String>,
+    #[serde(default)]
+    env_files: Vec<String>,
+    #[serde(default)]
+    tabs: Vec<OttoWorkspaceTab>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OttoWorkspaceOverlayMeta {
+    name: Option<String>,
+    active_tab_id: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum WorkspaceElementKind {
+    Workspace,
+    Tab,
+    Block,
+    Ensure,
+    Startup,
+    Env,
+    EnvFile,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceElementSource {
+    pub kind: WorkspaceElementKind,
+    pub id: String,
+    /// File that supplied the element as merged.
+    pub source: String,
+    /// Lower-precedence files whose definition was replaced, in order.
+    pub overridden: Vec<String>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MergedWorkspaceFile {
+    pub file: OttoWorkspaceFile,
+    /// Files that contributed, lowest precedence first.
+    pub layers: Vec<String>,
+    pub sources: Vec<WorkspaceElementSource>,
+    pub issues: Vec<WorkspaceFileIssue>,
+    /// Token of the otto.yaml content that was merged, for
+    /// `workspace_file_write`.
+    pub token: String,
+}
+
+fn describe_issues(file_path: &Path, issues: &[WorkspaceFileIssue]) -> String {
+    issues
+        .iter()
+        .map(|issue| match (issue.line, issue.column) {
+            (Some(line), Some(column)) => {
+                format!("{}:{}:{}: {}", file_path.display(), line, column, issue.message)
+            }
+            _ => format!("{}: {}", file_path.display(), issue.message),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Fragment files in `.otto/workspace.d`, applied in file name order.
+fn fragment_paths(project_root: &Path) -> Result<Vec<PathBuf>, String> {
+    let dir = project_root.join(FRAGMENTS_DIR);
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => return Err(format!("Failed to read {}: {error}", dir.display())),
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.is_file()
+                && path
+                    .extension()
+                    .is_some_and(|extension| extension == "yaml" || extension == "yml")
+        })
+        .collect();
+    paths.sort();
+    Ok(paths)
+}
+
+fn read_base(project_root: &Path) -> Result<(PathBuf, OttoWorkspaceFile, String), String> {
+    let base_path = project_root.join("otto.yaml");
+    let text = fs::read_to_string(&base_path)
+        .map_err(|error| format!("Failed to read {}: {error}", base_path.display()))?;
+    let (base, _) =
+        parse_workspace_file(&text).map_err(|issues| describe_issues(&base_path, &issues))?;
+    Ok((base_path, base, text))
+}
+
+fn read_overlay(file_path: &Path) -> Result<OttoWorkspaceOverlay, String> {
+    let text = fs::read_to_string(file_path)
+        .map_err(|error| format!("Failed to read {}: {error}", file_path.display()))?;
+    let overlay: Option<OttoWorkspaceOverlay> = serde_yaml::from_str(&text).map_err(|error| {
+        match error.location() {
+            Some(location) => format!(
+                "{}:{}:{}: {}",
+                file_path.display(),
+                location.line(),
+                location.column(),
+                error
+            ),
+            None => format!("{}: {}", file_path.display(), error),
+        }
+    })?;
+    let overlay = overlay.unwrap_or_default();
+    if let Some(version) = overlay.version.filter(|version| *version != OTTO_WORKSPACE_FILE_VERSION)
+    {
+        return Err(format!(
+            "{}: overlay version {} does not match otto.yaml version {}",
+            file_path.display(),
+            version,
+            OTTO_WORKSPACE_FILE_VERSION
+        ));
+    }
+    Ok(overlay)
+}
+
+struct Merger {
+    file: OttoWorkspaceFile,
+    sources: Vec<WorkspaceElementSource>,
+}
+
+impl Merger {
+    fn record(&mut self, kind: WorkspaceElementKind, id: &str, source: &str) {
+        match self
+            .sources
+            .iter_mut()
+            .find(|entry| entry.kind == kind && entry.id == id)
+        {
+            Some(entry) => {
+                let previous = std::mem::replace(&mut entry.source, source.to_string());
+                entry.overridden.push(previous);
+            }
+            None => self.sources.push(WorkspaceElementSource {
+                kind,
+                id: id.to_string(),
+                source: source.to_string(),
+                overridden: Vec::new(),
+            }),
+        }
+    }
+
+    fn apply(&mut self, overlay: OttoWorkspaceOverlay, source: &str) {
+        if let Some(meta) = overlay.workspace {
+            if let Some(name) = meta.name {
+                self.file.workspace.name = name;
+                self.record(WorkspaceElementKind::Workspace, "name", source);
+            }
+            if let Some(active_tab_id) = meta.active_tab_id {
+                self.file.workspace.active_tab_id = Some(active_tab_id);
+                self.record(WorkspaceElementKind::Workspace, "activeTabId", source);
+            }
+        }
+        for step in overlay.ensure {
+            self.record(WorkspaceElementKind::Ensure, &step.id, source);
+            upsert(&mut self.file.ensure, step, |step| &step.id);
+        }
+        for step in overlay.startup {
+            self.record(WorkspaceElementKind::Startup, &step.id, source);
+            upsert(&mut self.file.startup, step, |step| &step.id);
+        }
+        for (name, value) in overlay.env {
+            self.record(WorkspaceElementKind::Env, &name, source);
+            self.file.env.insert(name, value);
+        }
+        for env_file in overlay.env_files {
+            self.record(WorkspaceElementKind::EnvFile, &env_file, source);
+            if !self.file.env_files.contains(&env_file) {
+                self.file.env_files.push(env_file);
+            }
+        }
+        for tab in overlay.tabs {
+            self.merge_tab(tab, source);
+        }
+    }
+
+    /// Canvas tabs that exist in both layers are merged: blocks by id and
+    /// layout splits by id. Any other tab replaces the one with its id.
+    fn merge_tab(&mut self, tab: OttoWorkspaceTab, source: &str) {
+        self.record(WorkspaceElementKind::Tab, tab.id(), source);
+        for block in tab.blocks() {
+            self.record(
+                WorkspaceElementKind::Block,
+                &block_element_id(tab.id(), &block.id),
+                source,
+            );
+        }
+        let existing = self
+            .file
+            .tabs
+            .iter_mut()
+            .find(|existing| existing.id() == tab.id());
+        match (existing, tab) {
+            (Some(OttoWorkspaceTab::Canvas(existing)), OttoWorkspaceTab::Canvas(tab)) => {
+                existing.title = tab.title;
+                if tab.focused_block_id.is_some() {
+                    existing.focused_block_id = tab.focused_block_id;
+                }
+                for block in tab.blocks {
+                    upsert(&mut existing.blocks, block, |block| &block.id);
+                }
+                existing.layout = merge_layout(existing.layout.take(), tab.layout);
+                place_unlaid_blocks(existing);
+            }
+            (Some(existing), tab) => *existing = tab,
+            (None, tab) => self.file.tabs.push(tab),
+        }
+    }
+}
+
+fn block_element_id(tab_id: &str, block_id: &str) -> String {
+    format!("{}/{}", tab_id, block_id)
+}
+
+/// An overlay layout replaces the split with the same id, or the whole
+/// layout when its root matches no split.
+fn merge_layout(
+    base: Option<OttoLayoutNode>,
+    overlay: Option<OttoLayoutNode>,
+) -> Option<OttoLayoutNode> {
+    let Some(overlay) = overlay else {
+        return base;
+    };
+    let Some(mut base) = base else {
+        return Some(overlay);
+    };
+    match replace_split(&mut base, overlay) {
+        Ok(()) => Some(base),
+        Err(overlay) => Some(overlay),
+    }
+}
+
+fn replace_split(
+    node: &mut OttoLayoutNode,
+    replacement: OttoLayoutNode,
+) -> Result<(), OttoLayoutNode> {
+    let OttoLayoutNode::Split {
+        id: replacement_id, ..
+    } = &replacement
+    else {
+        return Err(replacement);
+    };
+    match node {
+        OttoLayoutNode::Split { id, .. } if id == replacement_id => {
+            *node = replacement;
+            Ok(())
+        }
+        OttoLayoutNode::Split { first, second, .. } => replace_split(first, replacement)
+            .or_else(|replacement| replace_split(second, replacement)),
+        OttoLayoutNode::Leaf { .. } => Err(replacement),
+    }
+}
+
+fn collect_leaves<'a>(node: &'a OttoLayoutNode, leaves: &mut Vec<&'a str>) {
+    match node {
+        OttoLayoutNode::Split { first, second, .. } => {
+            collect_leaves(first, leaves);
+            collect_leaves(second, leaves);
+        }
+        OttoLayoutNode::Leaf { block_id } => leaves.push(block_id),
+    }
+}
+
+/// Splits blocks that an overlay added without laying them out onto the
+/// right of the tab, so they are not silently hidden.
+fn place_unlaid_blocks(tab: &mut OttoCanvasTab) {
+    let Some(layout) = tab.layout.as_mut() else {
+        return;
+    };
+    let mut leaves = Vec::new();
+    collect_leaves(layout, &mut leaves);
+    let unlaid: Vec<String> = tab
+        .blocks
+        .iter()
+        .filter(|block| !leaves.contains(&block.id.as_str()))
+        .map(|block| block.id.clone())
+        .collect();
+    for block_id in unlaid {
+        let first = std::mem::replace(
+            layout,
+            OttoLayoutNode::Leaf {
+                block_id: block_id.clone(),
+            },
+        );
+        *layout = OttoLayoutNode::Split {
+            id: format!("{}-split", block_id),
+            direction: OttoSplitDirection::Horizontal,
+            ratio: 0.5,
+            first: Box::new(first),
+            second: Box::new(OttoLayoutNode::Leaf { block_id }),
+        };
+    }
+}
+
+/// Replaces the element with the same id in place, or appends it.
+fn upsert<T>(items: &mut Vec<T>, item: T, id: impl Fn(&T) -> &str) {
+    match items.iter().position(|existing| id(existing) == id(&item)) {
+        Some(index) => items[index] = item,
+        None => items.push(item),
+    }
+}
+
+/// Merges otto.yaml, `.otto/workspace.d/*.yaml` in name order and then
+/// otto.local.yaml. Later layers replace earlier elements with the same id,
+/// including blocks and layout splits inside canvas tabs; workspace fields
+/// and env variables are overridden individually and env files are appended.
+/// otto.local.yaml is personal and should be listed in `.gitignore`.
+pub fn merge_workspace_layers(project_root: &Path) -> Result<MergedWorkspaceFile, String> {
+    let (base_path, base, text) = read_base(project_root)?;
+    let base_source = base_path.to_string_lossy().to_string();
+    let mut merger = Merger {
+        file: base,
+        sources: Vec::new(),
+    };
+    merger.record(WorkspaceElementKind::Workspace, "name", &base_source);
+    if merger.file.workspace.active_tab_id.is_some() {
+        merger.record(WorkspaceElementKind::Workspace, "activeTabId", &base_source);
+    }
+    let ids: Vec<(WorkspaceElementKind, String)> = merger
+        .file
+        .ensure
+        .iter()
+        .map(|step| (WorkspaceElementKind::Ensure, step.id.clone()))
+        .chain(
+            merger
+                .file
+                .startup
+                .iter()
+                .map(|step| (WorkspaceElementKind::Startup, step.id.clone())),
+        )
+        .chain(
+            merger
+                .file
+                .env
+                .keys()
+                .map(|name| (WorkspaceElementKind::Env, name.clone())),
+        )
+        .chain(
+            merger
+                .file
+                .env_files
+                .iter()
+                .map(|env_file| (WorkspaceElementKind::EnvFile, env_file.clone())),
+        )
+        .chain(
+            merger
+                .file
+                .tabs
+                .iter()
+                .map(|tab| (WorkspaceElementKind::Tab, tab.id().to_string())),
+        )
+        .chain(merger.file.tabs.iter().flat_map(|tab| {
+            tab.blocks().iter().map(|block| {
+                (
+                    WorkspaceElementKind::Block,
+                    block_element_id(tab.id(), &block.id),
+                )
+            })
+        }))
+        .collect();
+    for (kind, id) in ids {
+        merger.record(kind, &id, &base_source);
+    }
+
+    let mut layers = vec![base_source];
+    let local_path = project_root.join(LOCAL_FILE_NAME);
+    let overlay_paths = fragment_paths(project_root)?
+        .into_iter()
+        .chain(local_path.is_file().then_some(local_path));
+    for overlay_path in overlay_paths {
+        let overlay = read_overlay(&overlay_path)?;
+        let source = overlay_path.to_string_lossy().to_string();
+        merger.apply(overlay, &source);
+        layers.push(source);
+    }
+
+    let issues = validate_workspace_file(&merger.file, None);
+    Ok(MergedWorkspaceFile {
+        file: merger.file,
+        layers,
+        sources: merger.sources,
+        issues,
+        token: content_token(text.as_bytes()),
+    })
+}
+
+/// Which layer an element of the merged workspace belongs to.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Owner {
+    /// otto.yaml, or nothing yet because it was added in the app.
+    Base,
+    /// An overlay that replaced the otto.yaml definition.
+    OverlayOverride,
+    /// An overlay that added it.
+    Overlay,
+}
+
+struct Owners {
+    sources: Vec<WorkspaceElementSource>,
+    base_source: String,
+}
+
+impl Owners {
+    fn owner(&self, kind: WorkspaceElementKind, id: &str) -> Owner {
+        match self
+            .sources
+            .iter()
+            .find(|entry| entry.kind == kind && entry.id == id)
+        {
+            Some(entry) if entry.source != self.base_source => {
+                if entry.overridden.contains(&self.base_source) {
+                    Owner::OverlayOverride
+                } else {
+                    Owner::Overlay
+                }
+            }
+            _ => Owner::Base,
+        }
+    }
+
+    /// Keeps elements of otto.yaml, puts back the otto.yaml definition of
+    /// those an overlay replaced and drops those an overlay added.
+    fn restore<T: Clone>(
+        &self,
+        kind: WorkspaceElementKind,
+        items: Vec<T>,
+        base_items: &[T],
+        id: impl Fn(&T) -> String,
+    ) -> Vec<T> {
+        items
+            .into_iter()
+            .filter_map(|item| match self.owner(kind, &id(&item)) {
+                Owner::Base => Some(item),
+                Owner::OverlayOverride => base_items
+                    .iter()
+                    .find(|base_item| id(base_item) == id(&item))
+                    .cloned(),
+                Owner::Overlay => None,
+            })
+            .collect()
+    }
+}
+
+/// Removes leaves whose block is gone, collapsing their splits.
+fn prune_layout(node: OttoLayoutNode, keep: &impl Fn(&str) -> bool) -> Option<OttoLayoutNode> {
+    match node {
+        OttoLayoutNode::Leaf { block_id } => {
+            keep(&block_id).then_some(OttoLayoutNode::Leaf { block_id })
+        }
+        OttoLayoutNode::Split {
+            id,
+            direction,
+            ratio,
+            first,
+            second,
+        } => match (prune_layout(*first, keep), prune_layout(*second, keep)) {
+            (Some(first), Some(second)) => Some(OttoLayoutNode::Split {
+                id,
+                direction,
+                ratio,
+                first: Box::new(first),
+                second: Box::new(second),
+            }),
+            (Some(node), None) | (None, Some(node)) => Some(node),
+            (None, None) => None,
+        },
+    }
+}
+
+fn restore_canvas_tab(
+    owners: &Owners,
+    mut tab: OttoCanvasTab,
+    base_tab: Option<&OttoCanvasTab>,
+) -> OttoCanvasTab {
+    if let Some(base_tab) = base_tab {
+        if owners.owner(WorkspaceElementKind::Tab, &tab.id) != Owner::Base {
+            tab.title = base_tab.title.clone();
+            tab.focused_block_id = base_tab.focused_block_id.clone();
+        }
+    }
+    let tab_id = tab.id.clone();
+    tab.blocks = owners.restore(
+        WorkspaceElementKind::Block,
+        tab.blocks,
+        base_tab.map_or(&[], |base_tab| &base_tab.blocks),
+        |block| block_element_id(&tab_id, &block.id),
+    );
+    let block_ids: Vec<String> = tab.blocks.iter().map(|block| block.id.clone()).collect();
+    let keep = |block_id: &str| block_ids.iter().any(|id| id == block_id);
+    tab.layout = tab.layout.and_then(|layout| prune_layout(layout, &keep));
+    if tab
+        .focused_block_id
+        .as_deref()
+        .is_some_and(|block_id| !keep(block_id))
+    {
+        tab.focused_block_id = block_ids.first().cloned();
+    }
+    tab
+}
+
+/// Reduces `file`, the whole workspace as edited in the app, to what
+/// belongs in otto.yaml: elements an overlay added are left out and those
+/// an overlay replaced go back to their otto.yaml definition, so exporting
+/// never copies fragment or otto.local.yaml content into the shared file.
+pub fn base_layer_file(
+    project_root: &Path,
+    mut file: OttoWorkspaceFile,
+) -> Result<OttoWorkspaceFile, String> {
+    use WorkspaceElementKind as Kind;
+
+    let merged = merge_workspace_layers(project_root)?;
+    let (base_path, base, _) = read_base(project_root)?;
+    let owners = Owners {
+        sources: merged.sources,
+        base_source: base_path.to_string_lossy().to_string(),
+    };
+
+    if owners.owner(Kind::Workspace, "name") != Owner::Base {
+        file.workspace.name = base.workspace.name.clone();
+    }
+    if owners.owner(Kind::Workspace, "activeTabId") != Owner::Base {
+        file.workspace.active_tab_id = base.workspace.active_tab_id.clone();
+    }
+    file.ensure = owners.restore(Kind::Ensure, file.ensure, &base.ensure, |step| {
+        step.id.clone()
+    });
+    file.startup = owners.restore(Kind::Startup, file.startup, &base.startup, |step| {
+        step.id.clone()
+    });
+    file.env = std::mem::take(&mut file.env)
+        .into_iter()
+        .filter_map(|(name, value)| match owners.owner(Kind::Env, &name) {
+            Owner::Base => Some((name, value)),
+            Owner::OverlayOverride => base.env.get(&name).map(|value| (name, value.clone())),
+            Owner::Overlay => None,
+        })
+        .collect();
+    file.env_files
+        .retain(|env_file| owners.owner(Kind::EnvFile, env_file) != Owner::Overlay);
+
+    file.tabs = std::mem::take(&mut file.tabs)
+        .into_iter()
+        .filter_map(|tab| {
+            let base_tab = base.tabs.iter().find(|base_tab| base_tab.id() == tab.id());
+            match (owners.owner(Kind::Tab, tab.id()), tab, base_tab) {
+                (Owner::Overlay, _, _) => None,
+                (_, OttoWorkspaceTab::Canvas(tab), Some(OttoWorkspaceTab::Canvas(base_tab))) => {
+                    Some(OttoWorkspaceTab::Canvas(restore_canvas_tab(
+                        &owners,
+                        tab,
+                        Some(base_tab),
+                    )))
+                }
+                (Owner::Base, OttoWorkspaceTab::Canvas(tab), None) => Some(
+                    OttoWorkspaceTab::Canvas(restore_canvas_tab(&owners, tab, None)),
+                ),
+                (Owner::Base, tab, _) => Some(tab),
+                (Owner::OverlayOverride, _, base_tab) => base_tab.cloned(),
+            }
+        })
+        .collect();
+    if file
+        .workspace
+        .active_tab_id
+        .as_deref()
+        .is_some_and(|tab_id| file.tabs.iter().all(|tab| tab.id() != tab_id))
+    {
+        file.workspace.active_tab_id = file.tabs.first().map(|tab| tab.id().to_string());
+    }
+    Ok(file)
+}
+
+/// Returns what an export of `file` should write to otto.yaml; see
+/// `base_layer_file`.
+#[tauri::command]
+pub fn workspace_file_base_layer(
+    project_path: String,
+    file: OttoWorkspaceFile,
+) -> Result<OttoWorkspaceFile, String> {
+    let trimmed = project_path.trim();
+    if trimmed.is_empty() {
+        return Err("Project path is required".to_string());
+    }
+    base_layer_file(Path::new(trimmed), file)
+}
+
+#[tauri::command]
+pub fn workspace_file_read_merged(project_path: String) -> Result<MergedWorkspaceFile, String> {
+    let trimmed = project_path.trim();
+    if trimmed.is_empty() {
+        return Err("Project path is required".to_string());
+    }
+    merge_workspace_layers(Path::new(trimmed))
+}
@@ -0,0 +1,612 @@
+use crate::workspace_automation::WorkspaceEnsureStep;
+use crate::workspace_services::WorkspaceStartupStep;
+use serde::de::{self, DeserializeSeed, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_yaml::Value;
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+
+pub const OTTO_WORKSPACE_FILE_VERSION: u64 = 1;
+
+pub const OTTO_BLOCK_TYPES: &[&str] = &["terminal", "browser", "otto", "command"];
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OttoWorkspaceFile {
+    pub version: u64,
+    pub workspace: OttoWorkspaceMeta,
+    #[serde(default)]
+    pub ensure: Vec<WorkspaceEnsureStep>,
+    #[serde(default)]
+    pub startup: Vec<WorkspaceStartupStep>,
+    /// Variables for terminals, runtimes and steps; values may use `${...}`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    /// `.env` files loaded before `env`, relative to the environment path.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub env_files: Vec<String>,
+    pub tabs: Vec<OttoWorkspaceTab>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OttoWorkspaceMeta {
+    pub name: String,
+    #[serde(default)]
+    pub active_tab_id: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum OttoWorkspaceTab {
+    Canvas(OttoCanvasTab),
+    Block(OttoBlockTab),
+}
+
+impl OttoWorkspaceTab {
+    pub fn id(&self) -> &str {
+        match self {
+            Self::Canvas(tab) => &tab.id,
+            Self::Block(tab) => &tab.id,
+        }
+    }
+
+    pub fn title(&self) -> &str {
+        match self {
+            Self::Canvas(tab) => &tab.title,
+            Self::Block(tab) => &tab.title,
+        }
+    }
+
+    pub fn blocks(&self) -> &[OttoBlockFile] {
+        match self {
+            Self::Canvas(tab) => &tab.blocks,
+            Self::Block(tab) => std::slice::from_ref(&tab.block),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OttoCanvasTab {
+    pub id: String,
+    pub title: String,
+    #[serde(default)]
+    pub focused_block_id: Option<String>,
+    #[serde(default)]
+    pub layout: Option<OttoLayoutNode>,
+    #[serde(default)]
+    pub blocks: Vec<OttoBlockFile>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OttoBlockTab {
+    pub id: String,
+    pub title: String,
+    pub block: OttoBlockFile,
+}
+
+/// Block types are kept as strings so unknown types are reported with the
+/// position of the offending value rather than the enclosing tab.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OttoBlockFile {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub block_type: String,
+    pub label: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reload_token: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preset: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum OttoSplitDirection {
+    Horizontal,
+    Vertical,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase",
+    tag = "kind"
+)]
+pub enum OttoLayoutNode {
+    Split {
+        id: String,
+        direction: OttoSplitDirection,
+        ratio: f64,
+        first: Box<OttoLayoutNode>,
+        second: Box<OttoLayoutNode>,
+    },
+    Leaf {
+        block_id: String,
+    },
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceFileIssue {
+    pub message: String,
+    /// Dotted path to the offending value, e.g. `tabs[0].blocks[2].type`.
+    pub path: Option<String>,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceFileCheck {
+    pub valid: bool,
+    pub version: Option<u64>,
+    /// Set when the document was written for an older schema and upgraded.
+    pub migrated_from: Option<u64>,
+    pub issues: Vec<WorkspaceFileIssue>,
+}
+
+type Migration = fn(Value) -> Result<Value, String>;
+
+/// `MIGRATIONS[n]` upgrades a document from version `n + 1` to `n + 2`. Bump
+/// `OTTO_WORKSPACE_FILE_VERSION` and append here when the schema changes.
+const MIGRATIONS: &[Migration] = &[];
+
+#[derive(Clone, Debug)]
+enum PathSegment {
+    Key(&'static str),
+    Index(usize),
+}
+
+#[derive(Clone, Debug, Default)]
+struct DocumentPath(Vec<PathSegment>);
+
+impl DocumentPath {
+    fn key(&self, key: &'static str) -> Self {
+        let mut path = self.0.clone();
+        path.push(PathSegment::Key(key));
+        Self(path)
+    }
+
+    fn index(&self, index: usize) -> Self {
+        let mut path = self.0.clone();
+        path.push(PathSegment::Index(index));
+        Self(path)
+    }
+}
+
+impl fmt::Display for DocumentPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (position, segment) in self.0.iter().enumerate() {
+            match segment {
+                PathSegment::Key(key) if position == 0 => write!(f, "{}", key)?,
+                PathSegment::Key(key) => write!(f, ".{}", key)?,
+                PathSegment::Index(index) => write!(f, "[{}]", index)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Walks the document to `path` and fails there, so serde_yaml reports the
+/// line and column of that node.
+struct Locate<'a>(&'a [PathSegment]);
+
+struct FailHere;
+
+impl<'de> Visitor<'de> for FailHere {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("nothing")
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for Locate<'_> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        if self.0.is_empty() {
+            deserializer.deserialize_any(FailHere)
+        } else {
+            deserializer.deserialize_any(self)
+        }
+    }
+}
+
+impl<'de> Visitor<'de> for Locate<'_> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("any value")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        while let Some(key) = map.next_key::<String>()? {
+            match self.0.first() {
+                Some(PathSegment::Key(wanted)) if *wanted == key => {
+                    map.next_value_seed(Locate(&self.0[1..]))?;
+                }
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        let mut index = 0;
+        loop {
+            let found = match self.0.first() {
+                Some(PathSegment::Index(wanted)) if *wanted == index => {
+                    seq.next_element_seed(Locate(&self.0[1..]))?
+                }
+                _ => seq.next_element::<IgnoredAny>()?.map(|_| ()),
+            };
+            if found.is_none() {
+                return Ok(());
+            }
+            index += 1;
+        }
+    }
+
+    fn visit_bool<E: de::Error>(self, _: bool) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_i64<E: de::Error>(self, _: i64) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_u64<E: de::Error>(self, _: u64) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_f64<E: de::Error>(self, _: f64) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_str<E: de::Error>(self, _: &str) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<(), E> {
+        Ok(())
+    }
+}
+
+fn locate(text: &str, path: &DocumentPath) -> Option<(usize, usize)> {
+    let deserializer = serde_yaml::Deserializer::from_str(text);
+    match Locate(&path.0).deserialize(deserializer) {
+        Err(error) => error
+            .location()
+            .map(|location| (location.line(), location.column())),
+        Ok(()) => None,
+    }
+}
+
+fn yaml_issue(error: serde_yaml::Error) -> WorkspaceFileIssue {
+    WorkspaceFileIssue {
+        message: error.to_string(),
+        path: None,
+        line: error.location().map(|location| location.line()),
+        column: error.location().map(|location| location.column()),
+    }
+}
+
+fn plain_issue(message: String) -> WorkspaceFileIssue {
+    WorkspaceFileIssue {
+        message,
+        path: None,
+        line: None,
+        column: None,
+    }
+}
+
+fn document_version(document: &Value) -> Result<u64, String> {
+    if !document.is_mapping() {
+        return Err("otto.yaml did not contain a valid workspace object".to_string());
+    }
+    match document.get("version") {
+        Some(version) => version
+            .as_u64()
+            .filter(|version| *version > 0)
+            .ok_or_else(|| "otto.yaml version must be a positive integer".to_string()),
+        None => Err("otto.yaml is missing version".to_string()),
+    }
+}
+
+/// Upgrades a parsed document to `OTTO_WORKSPACE_FILE_VERSION`, returning
+/// the version it was written for.
+pub fn migrate_workspace_document(document: Value) -> Result<(Value, u64), String> {
+    let original = document_version(&document)?;
+    if original > OTTO_WORKSPACE_FILE_VERSION {
+        return Err(format!(
+            "otto.yaml version {} is newer than the supported version {}",
+            original, OTTO_WORKSPACE_FILE_VERSION
+        ));
+    }
+
+    let mut document = document;
+    for version in original..OTTO_WORKSPACE_FILE_VERSION {
+        let migration = MIGRATIONS
+            .get(version as usize - 1)
+            .ok_or_else(|| format!("No migration from otto.yaml version {}", version))?;
+        document = migration(document)
+            .map_err(|error| format!("Failed to migrate otto.yaml from version {}: {}", version, error))?;
+    }
+    if let Value::Mapping(mapping) = &mut document {
+        mapping.insert(
+            Value::from("version"),
+            Value::from(OTTO_WORKSPACE_FILE_VERSION),
+        );
+    }
+    Ok((document, original))
+}
+
+struct Validator<'a> {
+    text: Option<&'a str>,
+    issues: Vec<WorkspaceFileIssue>,
+}
+
+impl Validator<'_> {
+    fn report(&mut self, path: DocumentPath, message: String) {
+        let (line, column) = match self.text.and_then(|text| locate(text, &path)) {
+            Some((line, column)) => (Some(line), Some(column)),
+            None => (None, None),
+        };
+        self.issues.push(WorkspaceFileIssue {
+            message,
+            path: Some(path.to_string()),
+            line,
+            column,
+        });
+    }
+
+    fn check_unique_ids<'i>(
+        &mut self,
+        kind: &str,
+        ids: impl IntoIterator<Item = (&'i str, DocumentPath)>,
+    ) {
+        let mut seen = HashSet::new();
+        for (id, path) in ids {
+            if id.trim().is_empty() {
+                self.report(path, format!("{} id must not be empty", kind));
+            } else if !seen.insert(id) {
+                self.report(path, format!("Duplicate {} id {}", kind, id));
+            }
+        }
+    }
+
+    fn check_layout(&mut self, node: &OttoLayoutNode, block_ids: &HashSet<&str>, path: DocumentPath) {
+        match node {
+            OttoLayoutNode::Split {
+                ratio,
+                first,
+                second,
+                ..
+            } => {
+                if !(*ratio > 0.0 && *ratio < 1.0) {
+                    self.report(
+                        path.key("ratio"),
+                        format!("Split ratio {} must be between 0 and 1", ratio),
+                    );
+                }
+                self.check_layout(first, block_ids, path.key("first"));
+                self.check_layout(second, block_ids, path.key("second"));
+            }
+            OttoLayoutNode::Leaf { block_id } => {
+                if !block_ids.contains(block_id.as_str()) {
+                    self.report(
+                        path.key("blockId"),
+                        format!("Layout references unknown block {}", block_id),
+                    );
+                }
+            }
+        }
+    }
+
+    fn check_block(&mut self, block: &OttoBlockFile, path: DocumentPath) {
+        if !OTTO_BLOCK_TYPES.contains(&block.block_type.as_str()) {
+            self.report(
+                path.key("type"),
+                format!(
+                    "Unknown block type {}, expected one of {}",
+                    block.block_type,
+                    OTTO_BLOCK_TYPES.join(", ")
+                ),
+            );
+        }
+    }
+
+    fn check_file(&mut self, file: &OttoWorkspaceFile) {
+        let root = DocumentPath::default();
+        self.check_unique_ids(
+            "tab",
+            file.tabs
+                .iter()
+                .enumerate()
+                .map(|(index, tab)| (tab.id(), root.key("tabs").index(index).key("id"))),
+        );
+        self.check_unique_ids(
+            "ensure step",
+            file.ensure
+                .iter()
+                .enumerate()
+                .map(|(index, step)| (step.id.as_str(), root.key("ensure").index(index).key("id"))),
+        );
+        self.check_unique_ids(
+            "startup step",
+            file.startup
+                .iter()
+                .enumerate()
+                .map(|(index, step)| (step.id.as_str(), root.key("startup").index(index).key("id"))),
+        );
+
+        for (index, tab) in file.tabs.iter().enumerate() {
+            let tab_path = root.key("tabs").index(index);
+            match tab {
+                OttoWorkspaceTab::Canvas(tab) => {
+                    let blocks_path = tab_path.key("blocks");
+                    self.check_unique_ids(
+                        "block",
+                        tab.blocks.iter().enumerate().map(|(index, block)| {
+                            (block.id.as_str(), blocks_path.index(index).key("id"))
+                        }),
+                    );
+                    for (index, block) in tab.blocks.iter().enumerate() {
+                        self.check_block(block, blocks_path.index(index));
+                    }
+
+                    let block_ids: HashSet<&str> =
+                        tab.blocks.iter().map(|block| block.id.as_str()).collect();
+                    if let Some(layout) = &tab.layout {
+                        self.check_layout(layout, &block_ids, tab_path.key("layout"));
+                    }
+                    if let Some(focused) = tab.focused_block_id.as_deref() {
+                        if !block_ids.contains(focused) {
+                            self.report(
+                                tab_path.key("focusedBlockId"),
+                                format!("Focused block {} is not in this tab", focused),
+                            );
+                        }
+                    }
+                }
+                OttoWorkspaceTab::Block(tab) => {
+                    self.check_block(&tab.block, tab_path.key("block"));
+                }
+            }
+        }
+
+        if let Some(active) = file.workspace.active_tab_id.as_deref() {
+            if !file.tabs.iter().any(|tab| tab.id() == active) {
+                self.report(
+                    root.key("workspace").key("activeTabId"),
+                    format!("Active tab {} does not exist", active),
+                );
+            }
+        }
+    }
+}
+
+/// Returns semantic problems that serde cannot express: unknown block types,
+/// duplicate or dangling ids and out-of-range layout ratios.
+pub fn validate_workspace_file(
+    file: &OttoWorkspaceFile,
+    text: Option<&str>,
+) -> Vec<WorkspaceFileIssue> {
+    let mut validator = Validator {
+        text,
+        issues: Vec::new(),
+    };
+    validator.check_file(file);
+    validator.issues
+}
+
+/// Parses, migrates and validates otto.yaml text. Positions are reported
+/// against the original text; documents that needed a migration are checked
+/// after upgrading, so their semantic issues carry a path but no position.
+pub fn parse_workspace_file(
+    text: &str,
+) -> Result<(OttoWorkspaceFile, u64), Vec<WorkspaceFileIssue>> {
+    let document: Value = serde_yaml::from_str(text).map_err(|error| vec![yaml_issue(error)])?;
+    let (document, original) =
+        migrate_workspace_document(document).map_err(|error| vec![plain_issue(error)])?;
+
+    let (file, source) = if original == OTTO_WORKSPACE_FILE_VERSION {
+        let file: OttoWorkspaceFile =
+            serde_yaml::from_str(text).map_err(|error| vec![yaml_issue(error)])?;
+        (file, Some(text))
+    } else {
+        let file: OttoWorkspaceFile =
+            serde_yaml::from_value(document).map_err(|error| vec![yaml_issue(error)])?;
+        (file, None)
+    };
+
+    let issues = validate_workspace_file(&file, source);
+    if issues.is_empty() {
+        Ok((file, original))
+    } else {
+        Err(issues)
+    }
+}
+
+pub fn check_workspace_file(text: &str) -> WorkspaceFileCheck {
+    match parse_workspace_file(text) {
+        Ok((file, original)) => WorkspaceFileCheck {
+            valid: true,
+            version: Some(file.version),
+            migrated_from: (original != file.version).then_some(original),
+            issues: Vec::new(),
+        },
+        Err(issues) => WorkspaceFileCheck {
+            valid: false,
+            version: serde_yaml::from_str::<Value>(text)
+                .ok()
+                .and_then(|document| document_version(&document).ok()),
+            migrated_from: None,
+            issues,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DUPLICATE_BLOCK: &str = "version: 1
+workspace:
+  name: demo
+tabs:
+  - id: main
+    type: canvas
+    title: Main
+    blocks:
+      - id: shell
+        type: terminal
+        label: Shell
+      - id: shell
+        type: browser
+        label: Docs
+";
+
+    #[test]
+    fn reports_the_position_of_a_duplicate_block_id() {
+        let issues = parse_workspace_file(DUPLICATE_BLOCK).unwrap_err();
+        assert_eq!(issues.len(), 1, "{issues:?}");
+        let issue = &issues[0];
+        assert!(issue.message.contains("shell"), "{}", issue.message);
+        assert_eq!(issue.path.as_deref(), Some("tabs[0].blocks[1].id"));
+        assert_eq!((issue.line, issue.column), (Some(12), Some(13)));
+    }
+
+    #[test]
+    fn reports_unknown_block_types() {
+        let text = DUPLICATE_BLOCK.replacen(
+            "id: shell\n        type: browser",
+            "id: docs\n        type: spreadsheet",
+            1,
+        );
+        let issues = parse_workspace_file(&text).unwrap_err();
+        assert_eq!(issues.len(), 1, "{issues:?}");
+        assert_eq!(issues[0].path.as_deref(), Some("tabs[0].blocks[1].type"));
+        assert_eq!(issues[0].line, Some(13));
+    }
+
+    #[test]
+    fn reports_yaml_errors_with_a_position() {
+        let issues = parse_workspace_file("version: 1\nworkspace: [\n").unwrap_err();
+        assert!(issues[0].line.is_some(), "{issues:?}");
+    }
+}
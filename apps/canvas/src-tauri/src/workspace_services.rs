@@ -0,0 +1,783 @@
+use crate::runtime::{
+    kill_process_group, kill_process_tree, process_alive, process_group_alive, write_registry,
+    RestartTracker, WorkspaceRestartPolicy,
+};
+use crate::runtime_log::{
+    open_child_log, prepare_service_log_path, rotate_oversized_log, set_log_secrets,
+    RuntimeLogOptions,
+};
+use crate::shell_env::resolve_user_shell_path;
+use crate::workspace_automation::{resolve_step_path, step_path_env};
+use crate::workspace_env::{resolve_env_for, ResolvedEnv, WorkspaceEnvSpec};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread::sleep;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager, State, Wry};
+
+#[cfg(unix)]
+use std::os::unix::process::CommandExt;
+
+const SERVICE_POLL_INTERVAL: Duration = Duration::from_millis(500);
+const SERVICE_STATUS_EVENT: &str = "workspace-service-status";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum WorkspaceStartupPolicy {
+    #[default]
+    Manual,
+    OnOpen,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceStartupStep {
+    pub id: String,
+    pub label: String,
+    pub run: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+    #[serde(default)]
+    pub policy: WorkspaceStartupPolicy,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum WorkspaceServiceStatus {
+    Running,
+    Restarting,
+    Stopped,
+    Failed,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceServiceInfo {
+    pub workspace_id: String,
+    pub environment_id: String,
+    pub service_id: String,
+    pub label: String,
+    pub run: String,
+    pub cwd: String,
+    pub pid: Option<u32>,
+    pub status: WorkspaceServiceStatus,
+    pub restart_policy: WorkspaceRestartPolicy,
+    pub restart_count: u32,
+    pub exit_code: Option<i32>,
+    pub error: Option<String>,
+    pub log_path: String,
+    pub started_at_ms: Option<u64>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct ServiceKey {
+    workspace_id: String,
+    environment_id: String,
+    service_id: String,
+}
+
+impl ServiceKey {
+    fn new(workspace_id: &str, environment_id: &str, service_id: &str) -> Self {
+        Self {
+            workspace_id: workspace_id.to_string(),
+            environment_id: environment_id.to_string(),
+            service_id: service_id.to_string(),
+        }
+    }
+}
+
+#[derive(Clone)]
+struct ServiceLaunch {
+    shell_path: String,
+    run: String,
+    cwd: PathBuf,
+    path_env: String,
+    env: Vec<(String, String)>,
+    secrets: Vec<String>,
+    log_path: PathBuf,
+    log_options: RuntimeLogOptions,
+}
+
+struct ServiceEntry {
+    child: Option<Child>,
+    info: WorkspaceServiceInfo,
+    launch: ServiceLaunch,
+    restarts: RestartTracker,
+}
+
+type ServiceMap = Arc<Mutex<HashMap<ServiceKey, ServiceEntry>>>;
+
+/// Services lead their own session like runtimes, so they outlive a crashed
+/// canvas too. Their process groups are recorded here for the next launch
+/// to reap; `onOpen` steps start them again from the workspace file.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ServiceRegistryEntry {
+    workspace_id: String,
+    environment_id: String,
+    service_id: String,
+    pid: u32,
+    pgid: i32,
+    /// When the leader started, in clock ticks after boot, so a process that
+    /// reused its pid is left alone. Only known on Linux.
+    #[serde(default)]
+    start_ticks: Option<u64>,
+}
+
+static SERVICE_REGISTRY_PATH: OnceLock<PathBuf> = OnceLock::new();
+
+/// Headless processes for otto.yaml startup steps. Each service leads its own
+/// process group and is restarted according to its policy by a supervisor
+/// thread; stopped and failed services stay listed until started again.
+#[derive(Clone, Default)]
+pub struct WorkspaceServiceManager {
+    inner: ServiceMap,
+}
+
+impl WorkspaceServiceManager {
+    pub fn service_count(&self) -> usize {
+        self.inner
+            .lock()
+            .ok()
+            .map(|services| {
+                services
+                    .values()
+                    .filter(|entry| entry.child.is_some())
+                    .count()
+            })
+            .unwrap_or(0)
+    }
+
+    pub fn stop_all(&self) {
+        let children: Vec<(ServiceKey, Child)> = match self.inner.lock() {
+            Ok(mut services) => {
+                let children = services
+                    .drain()
+                    .filter_map(|(key, entry)| entry.child.map(|child| (key, child)))
+                    .collect();
+                persist_registry(&services);
+                children
+            }
+            Err(_) => return,
+        };
+        for (key, mut child) in children {
+            eprintln!(
+                "[canvas] stopping workspace service service={} pid={}",
+                key.service_id,
+                child.id()
+            );
+            kill_process_tree(&mut child);
+        }
+    }
+
+    /// Reaps the process groups of services left running by a previous
+    /// canvas session.
+    pub fn restore(&self, app_handle: &AppHandle<Wry>) {
+        let registry_path = match app_handle.path().app_data_dir() {
+            Ok(dir) => dir.join("service-registry.json"),
+            Err(error) => {
+                eprintln!("[canvas] service registry unavailable: {}", error);
+                return;
+            }
+        };
+        if SERVICE_REGISTRY_PATH.set(registry_path.clone()).is_err() {
+            return;
+        }
+
+        let records: Vec<ServiceRegistryEntry> = fs::read(&registry_path)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default();
+        if records.is_empty() {
+            return;
+        }
+
+        // Each group gets up to three seconds to exit after SIGTERM.
+        let services = self.inner.clone();
+        let spawned = std::thread::Builder::new()
+            .name("otto-service-restore".to_string())
+            .spawn(move || {
+                for record in &records {
+                    reap_stale_service(record);
+                }
+                if let Ok(locked) = services.lock() {
+                    persist_registry(&locked);
+                }
+            });
+        if let Err(error) = spawned {
+            eprintln!("[canvas] failed to spawn service restore: {}", error);
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn process_start_ticks(pid: u32) -> Option<u64> {
+    let stat = fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    // Field 22; the command name before it may contain spaces.
+    let rest = &stat[stat.rfind(')')? + 1..];
+    rest.split_whitespace().nth(19)?.parse().ok()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn process_start_ticks(_pid: u32) -> Option<u64> {
+    None
+}
+
+fn persist_registry(services: &HashMap<ServiceKey, ServiceEntry>) {
+    let Some(registry_path) = SERVICE_REGISTRY_PATH.get() else {
+        return;
+    };
+    let records: Vec<ServiceRegistryEntry> = services
+        .iter()
+        .filter(|(_, entry)| entry.child.is_some())
+        .filter_map(|(key, entry)| {
+            let pid = entry.info.pid?;
+            Some(ServiceRegistryEntry {
+                workspace_id: key.workspace_id.clone(),
+                environment_id: key.environment_id.clone(),
+                service_id: key.service_id.clone(),
+                pid,
+                // Services call setsid(), so each one leads its own group.
+                pgid: pid as i32,
+                start_ticks: process_start_ticks(pid),
+            })
+        })
+        .collect();
+    write_registry(registry_path, &records);
+}
+
+fn reap_stale_service(record: &ServiceRegistryEntry) {
+    // A live leader in another group, or one started at another time, means
+    // the pid was reused.
+    if process_alive(record.pid) {
+        #[cfg(unix)]
+        if unsafe { libc::getpgid(record.pid as i32) } != record.pgid {
+            return;
+        }
+        if record.start_ticks.is_some() && process_start_ticks(record.pid) != record.start_ticks {
+            return;
+        }
+    }
+    if process_group_alive(record.pgid) {
+        eprintln!(
+            "[canvas] reaping stale workspace service service={} pgid={}",
+            record.service_id, record.pgid
+        );
+        kill_process_group(record.pgid as u32);
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn emit_status(app_handle: &AppHandle<Wry>, info: &WorkspaceServiceInfo) {
+    let _ = app_handle.emit(SERVICE_STATUS_EVENT, info.clone());
+}
+
+fn spawn_service_process(launch: &ServiceLaunch) -> Result<Child, String> {
+    set_log_secrets(&launch.log_path, launch.secrets.clone());
+    let (stdout, stderr) =
+        open_child_log(&launch.log_path, launch.log_options).unwrap_or_else(|error| {
+            eprintln!(
+                "[canvas] service log unavailable log={} error={}",
+                launch.log_path.display(),
+                error
+            );
+            (Stdio::null(), Stdio::null())
+        });
+
+    let mut cmd = Command::new(&launch.shell_path);
+    cmd.args(["-l", "-c", &launch.run])
+        .current_dir(&launch.cwd)
+        .env("PATH", &launch.path_env)
+        .envs(launch.env.iter().map(|(key, value)| (key, value)))
+        .stdin(Stdio::null())
+        .stdout(stdout)
+        .stderr(stderr);
+
+    #[cfg(unix)]
+    unsafe {
+        cmd.pre_exec(|| {
+            libc::setsid();
+            Ok(())
+        });
+    }
+
+    cmd.spawn().map_err(|error| {
+        format!(
+            "Failed to start service in {}: {}",
+            launch.cwd.display(),
+            error
+        )
+    })
+}
+
+fn spawn_service_supervisor(
+    app_handle: AppHandle<Wry>,
+    services: &ServiceMap,
+    key: ServiceKey,
+    pid: u32,
+) -> Result<(), String> {
+    let services = services.clone();
+    std::thread::Builder::new()
+        .name(format!("otto-service-{}", key.service_id))
+        .spawn(move || supervise_service(app_handle, services, key, pid))
+        .map(|_| ())
+        .map_err(|error| format!("Failed to spawn service supervisor: {}", error))
+}
+
+fn supervise_service(
+    app_handle: AppHandle<Wry>,
+    services: ServiceMap,
+    key: ServiceKey,
+    mut pid: u32,
+) {
+    loop {
+        sleep(SERVICE_POLL_INTERVAL);
+
+        let Ok(mut locked) = services.lock() else {
+            return;
+        };
+        let Some(entry) = locked
+            .get_mut(&key)
+            .filter(|entry| entry.info.pid == Some(pid))
+        else {
+            return;
+        };
+        let exit = match entry.child.as_mut().map(Child::try_wait) {
+            Some(Ok(Some(status))) => status.code(),
+            Some(Ok(None)) => {
+                let (log_path, log_options) =
+                    (entry.launch.log_path.clone(), entry.launch.log_options);
+                drop(locked);
+                if let Err(error) = rotate_oversized_log(&log_path, log_options) {
+                    eprintln!(
+                        "[canvas] service log rotation failed log={} error={}",
+                        log_path.display(),
+                        error
+                    );
+                }
+                continue;
+            }
+            _ => return,
+        };
+
+        entry.child = None;
+        entry.info.exit_code = exit;
+        let wants_restart = entry.info.restart_policy.should_restart(exit);
+        let delay = if wants_restart {
+            entry.restarts.next_delay()
+        } else {
+            None
+        };
+        let Some(delay) = delay else {
+            entry.info.pid = None;
+            entry.info.status = if exit == Some(0) && !wants_restart {
+                WorkspaceServiceStatus::Stopped
+            } else {
+                WorkspaceServiceStatus::Failed
+            };
+            if wants_restart {
+                entry.info.error = Some("Service is crash looping; not restarting".to_string());
+            }
+            eprintln!(
+                "[canvas] workspace service exited service={} exit_code={:?}",
+                key.service_id, exit
+            );
+            emit_status(&app_handle, &entry.info);
+            persist_registry(&locked);
+            return;
+        };
+
+        entry.info.status = WorkspaceServiceStatus::Restarting;
+        entry.info.restart_count += 1;
+        emit_status(&app_handle, &entry.info);
+        persist_registry(&locked);
+        drop(locked);
+
+        sleep(delay);
+
+        let Ok(mut locked) = services.lock() else {
+            return;
+        };
+        let Some(entry) = locked
+            .get_mut(&key)
+            .filter(|entry| entry.info.pid == Some(pid) && entry.child.is_none())
+        else {
+            return;
+        };
+        match spawn_service_process(&entry.launch) {
+            Ok(child) => {
+                pid = child.id();
+                entry.info.pid = Some(pid);
+                entry.info.status = WorkspaceServiceStatus::Running;
+                entry.info.error = None;
+                entry.info.started_at_ms = Some(now_ms());
+                entry.restarts.ready_since = Some(Instant::now());
+                entry.child = Some(child);
+                eprintln!(
+                    "[canvas] workspace service restarted service={} pid={} restarts={}",
+                    key.service_id, pid, entry.info.restart_count
+                );
+            }
+            Err(error) => {
+                entry.info.pid = None;
+                entry.info.status = WorkspaceServiceStatus::Failed;
+                entry.info.error = Some(error);
+                emit_status(&app_handle, &entry.info);
+                return;
+            }
+        }
+        emit_status(&app_handle, &entry.info);
+        persist_registry(&locked);
+    }
+}
+
+fn start_service(
+    app_handle: &AppHandle<Wry>,
+    services: &ServiceMap,
+    key: ServiceKey,
+    launch: ServiceLaunch,
+    info: WorkspaceServiceInfo,
+) -> Result<WorkspaceServiceInfo, String> {
+    let mut locked = services
+        .lock()
+        .map_err(|_| "Failed to lock service manager".to_string())?;
+    if let Some(entry) = locked.get(&key).filter(|entry| entry.child.is_some()) {
+        return Ok(entry.info.clone());
+    }
+
+    let mut info = info;
+    let (child, restarts) = match spawn_service_process(&launch) {
+        Ok(child) => {
+            info.pid = Some(child.id());
+            info.status = WorkspaceServiceStatus::Running;
+            info.started_at_ms = Some(now_ms());
+            let mut restarts = RestartTracker::default();
+            restarts.ready_since = Some(Instant::now());
+            (Some(child), restarts)
+        }
+        Err(error) => {
+            info.pid = None;
+            info.status = WorkspaceServiceStatus::Failed;
+            info.error = Some(error);
+            (None, RestartTracker::default())
+        }
+    };
+    eprintln!(
+        "[canvas] workspace service started service={} pid={:?} cwd={}",
+        key.service_id,
+        info.pid,
+        launch.cwd.display()
+    );
+
+    locked.insert(
+        key.clone(),
+        ServiceEntry {
+            child,
+            info: info.clone(),
+            launch,
+            restarts,
+        },
+    );
+    persist_registry(&locked);
+    drop(locked);
+
+    emit_status(app_handle, &info);
+    if let Some(pid) = info.pid {
+        spawn_service_supervisor(app_handle.clone(), services, key, pid)?;
+    }
+    Ok(info)
+}
+
+fn stop_service(
+    services: &ServiceMap,
+    key: &ServiceKey,
+) -> Result<Option<WorkspaceServiceInfo>, String> {
+    let (child, info) = {
+        let mut locked = services
+            .lock()
+            .map_err(|_| "Failed to lock service manager".to_string())?;
+        let Some(entry) = locked.get_mut(key) else {
+            return Ok(None);
+        };
+        entry.info.pid = None;
+        entry.info.status = WorkspaceServiceStatus::Stopped;
+        let stopped = (entry.child.take(), entry.info.clone());
+        persist_registry(&locked);
+        stopped
+    };
+    if let Some(mut child) = child {
+        eprintln!(
+            "[canvas] stopping workspace service service={} pid={}",
+            key.service_id,
+            child.id()
+        );
+        kill_process_tree(&mut child);
+    }
+    Ok(Some(info))
+}
+
+fn prepare_service(
+    app_handle: &AppHandle<Wry>,
+    workspace_id: &str,
+    environment_id: &str,
+    environment_path: &Path,
+    step: &WorkspaceStartupStep,
+    restart_policy: WorkspaceRestartPolicy,
+    env: &ResolvedEnv,
+) -> Result<(ServiceKey, ServiceLaunch, WorkspaceServiceInfo), String> {
+    let cwd = match step.cwd.as_deref() {
+        Some(cwd) if !cwd.trim().is_empty() => {
+            resolve_step_path(environment_path, &env.interpolate(cwd)?)
+        }
+        _ => environment_path.to_path_buf(),
+    };
+    let run = env.interpolate(&step.run)?;
+    let log_path = prepare_service_log_path(app_handle, workspace_id, environment_id, &step.id)?;
+    let key = ServiceKey::new(workspace_id, environment_id, &step.id);
+    let info = WorkspaceServiceInfo {
+        workspace_id: workspace_id.to_string(),
+        environment_id: environment_id.to_string(),
+        service_id: step.id.clone(),
+        label: step.label.clone(),
+        run: step.run.clone(),
+        cwd: cwd.to_string_lossy().to_string(),
+        pid: None,
+        status: WorkspaceServiceStatus::Stopped,
+        restart_policy,
+        restart_count: 0,
+        exit_code: None,
+        error: None,
+        log_path: log_path.to_string_lossy().to_string(),
+        started_at_ms: None,
+    };
+    eprintln!(
+        "[canvas] preparing workspace service service={} run={} vars=[{}]",
+        step.id,
+        env.mask(&run),
+        env.describe()
+    );
+    let launch = ServiceLaunch {
+        shell_path: resolve_user_shell_path(),
+        run,
+        cwd,
+        path_env: step_path_env(),
+        env: env.pairs(),
+        secrets: env.secrets(),
+        log_path,
+        log_options: RuntimeLogOptions::default(),
+    };
+    Ok((key, launch, info))
+}
+
+/// Status for a step that never reached `start_service`, e.g. because one of
+/// its variables failed to resolve.
+fn failed_service_info(
+    workspace_id: &str,
+    environment_id: &str,
+    environment_path: &Path,
+    step: &WorkspaceStartupStep,
+    restart_policy: WorkspaceRestartPolicy,
+    error: String,
+) -> WorkspaceServiceInfo {
+    WorkspaceServiceInfo {
+        workspace_id: workspace_id.to_string(),
+        environment_id: environment_id.to_string(),
+        service_id: step.id.clone(),
+        label: step.label.clone(),
+        run: step.run.clone(),
+        cwd: environment_path.to_string_lossy().to_string(),
+        pid: None,
+        status: WorkspaceServiceStatus::Failed,
+        restart_policy,
+        restart_count: 0,
+        exit_code: None,
+        error: Some(error),
+        log_path: String::new(),
+        started_at_ms: None,
+    }
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn workspace_start_service(
+    app_handle: AppHandle<Wry>,
+    manager: State<'_, WorkspaceServiceManager>,
+    workspace_id: String,
+    environment_id: String,
+    environment_path: String,
+    step: WorkspaceStartupStep,
+    restart_policy: Option<WorkspaceRestartPolicy>,
+    env_spec: Option<WorkspaceEnvSpec>,
+) -> Result<WorkspaceServiceInfo, String> {
+    let services = manager.inner.clone();
+    // Resolving the login shell PATH and spawning can block for seconds.
+    tauri::async_runtime::spawn_blocking(move || {
+        let env = resolve_env_for(env_spec, &environment_path)?;
+        let (key, launch, info) = prepare_service(
+            &app_handle,
+            &workspace_id,
+            &environment_id,
+            Path::new(environment_path.trim()),
+            &step,
+            restart_policy.unwrap_or_default(),
+            &env,
+        )?;
+        start_service(&app_handle, &services, key, launch, info)
+    })
+    .await
+    .map_err(|error| format!("Service start did not complete: {}", error))?
+}
+
+/// Starts every `policy: onOpen` step for an environment. Steps that fail to
+/// resolve or spawn are reported with a failed status instead of aborting the
+/// rest.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn workspace_start_services_on_open(
+    app_handle: AppHandle<Wry>,
+    manager: State<'_, WorkspaceServiceManager>,
+    workspace_id: String,
+    environment_id: String,
+    environment_path: String,
+    steps: Vec<WorkspaceStartupStep>,
+    restart_policy: Option<WorkspaceRestartPolicy>,
+    env_spec: Option<WorkspaceEnvSpec>,
+) -> Result<Vec<WorkspaceServiceInfo>, String> {
+    let services = manager.inner.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let env = resolve_env_for(env_spec, &environment_path);
+        let environment_path = PathBuf::from(environment_path.trim());
+        let restart_policy = restart_policy.unwrap_or_default();
+        let mut started = Vec::new();
+        for step in steps
+            .iter()
+            .filter(|step| step.policy == WorkspaceStartupPolicy::OnOpen)
+        {
+            let result = env.as_ref().map_err(Clone::clone).and_then(|env| {
+                let (key, launch, info) = prepare_service(
+                    &app_handle,
+                    &workspace_id,
+                    &environment_id,
+                    &environment_path,
+                    step,
+                    restart_policy,
+                    env,
+                )?;
+                start_service(&app_handle, &services, key, launch, info)
+            });
+            started.push(result.unwrap_or_else(|error| {
+                eprintln!(
+                    "[canvas] workspace service failed service={} error={}",
+                    step.id, error
+                );
+                let info = failed_service_info(
+                    &workspace_id,
+                    &environment_id,
+                    &environment_path,
+                    step,
+                    restart_policy,
+                    error,
+                );
+                emit_status(&app_handle, &info);
+                info
+            }));
+        }
+        Ok(started)
+    })
+    .await
+    .map_err(|error| format!("Service start did not complete: {}", error))?
+}
+
+#[tauri::command]
+pub async fn workspace_stop_service(
+    app_handle: AppHandle<Wry>,
+    manager: State<'_, WorkspaceServiceManager>,
+    workspace_id: String,
+    environment_id: String,
+    service_id: String,
+) -> Result<(), String> {
+    let services = manager.inner.clone();
+    // Stopping waits up to three seconds for the process group to exit.
+    tauri::async_runtime::spawn_blocking(move || {
+        let key = ServiceKey::new(&workspace_id, &environment_id, &service_id);
+        if let Some(info) = stop_service(&services, &key)? {
+            emit_status(&app_handle, &info);
+        }
+        Ok(())
+    })
+    .await
+    .map_err(|error| format!("Service stop did not complete: {}", error))?
+}
+
+#[tauri::command]
+pub async fn workspace_restart_service(
+    app_handle: AppHandle<Wry>,
+    manager: State<'_, WorkspaceServiceManager>,
+    workspace_id: String,
+    environment_id: String,
+    service_id: String,
+) -> Result<WorkspaceServiceInfo, String> {
+    let services = manager.inner.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let key = ServiceKey::new(&workspace_id, &environment_id, &service_id);
+        let info = stop_service(&services, &key)?
+            .ok_or_else(|| format!("Service {} is not known", service_id))?;
+        let launch = services
+            .lock()
+            .map_err(|_| "Failed to lock service manager".to_string())?
+            .get(&key)
+            .map(|entry| entry.launch.clone())
+            .ok_or_else(|| format!("Service {} is not known", service_id))?;
+        let info = WorkspaceServiceInfo {
+            restart_count: info.restart_count + 1,
+            exit_code: None,
+            error: None,
+            ..info
+        };
+        start_service(&app_handle, &services, key, launch, info)
+    })
+    .await
+    .map_err(|error| format!("Service restart did not complete: {}", error))?
+}
+
+#[tauri::command]
+pub fn workspace_list_services(
+    manager: State<'_, WorkspaceServiceManager>,
+    workspace_id: Option<String>,
+    environment_id: Option<String>,
+) -> Result<Vec<WorkspaceServiceInfo>, String> {
+    let services = manager
+        .inner
+        .lock()
+        .map_err(|_| "Failed to lock service manager".to_string())?;
+    let mut infos: Vec<WorkspaceServiceInfo> = services
+        .iter()
+        .filter(|(key, _)| {
+            workspace_id
+                .as_deref()
+                .is_none_or(|id| id == key.workspace_id)
+                && environment_id
+                    .as_deref()
+                    .is_none_or(|id| id == key.environment_id)
+        })
+        .map(|(_, entry)| entry.info.clone())
+        .collect();
+    infos.sort_by(|a, b| {
+        (&a.workspace_id, &a.environment_id, &a.service_id).cmp(&(
+            &b.workspace_id,
+            &b.environment_id,
+            &b.service_id,
+        ))
+    });
+    Ok(infos)
+}
@@ -6,20 +6,29 @@ import { Sidebar } from '../components/Sidebar';
 import { useCanvasKeybinds } from '../hooks/useCanvasKeybinds';
 import { useCanvasNativeBlockManager } from '../hooks/useCanvasNativeBlockManager';
 import { isTauriRuntime } from '../lib/ghostty';
+import type {
+	GhosttyVtOpenEditorEvent,
+	GhosttyVtOpenUrlEvent,
+} from '../lib/ghostty-vt';
 import {
 	buildOttoWorkspaceFile,
 	getOttoWorkspaceFilePath,
-	parseOttoWorkspaceFile,
+	loadOttoWorkspaceFile,
 	stringifyOttoWorkspaceFile,
 } from '../lib/otto-workspace-file';
 import {
-	readWorkspaceFile,
+	getBaseLayerWorkspaceFile,
+	isWorkspaceFileConflict,
+	listenWorkspaceFileChanged,
+	readMergedWorkspaceFile,
+	unwatchWorkspaceFile,
+	watchWorkspaceFile,
 	workspaceFileExists,
 	writeWorkspaceFile,
 } from '../lib/otto-workspace-io';
 import type { WorkspaceSurfaceState } from '../stores/canvas-store';
 import { useCanvasStore } from '../stores/canvas-store';
-import { useWorkspaceRuntimeStore } from '../stores/workspace-runtime-store';
+import { runtimeKey, useWorkspaceRuntimeStore } from '../stores/workspace-runtime-store';
 import { useWorkspaceStore } from '../stores/workspace-store';
 
 function isBlankWorkspaceSurface(workspaceSurface: WorkspaceSurfaceState | null) {
@@ -70,6 +79,9 @@ export function App() {
 	const stopRuntime = useWorkspaceRuntimeStore((s) => s.stopRuntime);
 	const setFocused = useCanvasStore((s) => s.setFocused);
 	const closeBlockSurfaceById = useCanvasStore((s) => s.closeBlockSurfaceById);
+	const addBlock = useCanvasStore((s) => s.addBlock);
+	const setBlockUrl = useCanvasStore((s) => s.setBlockUrl);
+	const setCommandBlockConfig = useCanvasStore((s) => s.setCommandBlockConfig);
 	const canvasActiveWorkspaceId = useCanvasStore((s) => s.activeWorkspaceId);
 	const activeTab = activeTabId ? tabs[activeTabId] ?? null : null;
 	const [workspaceFileExistsState, setWorkspaceFileExistsState] = useState(false);
@@ -80,9 +92,20 @@ export function App() {
 		message: string;
 	} | null>(null);
 	const autoLoadedWorkspaceIdsRef = useRef(new Set<string>());
+	// Token of the otto.yaml content each project path was loaded from or last
+	// exported as, so exports do not overwrite edits made on disk since.
+	const workspaceFileTokensRef = useRef(new Map<string, string>());
+	// Overlay files (otto.local.yaml, .otto/workspace.d) merged into each
+	// loaded workspace; exporting would copy their contents into otto.yaml.
+	const workspaceFileOverlaysRef = useRef(new Map<string, string[]>());
+	// Browser block each terminal session last opened a link in.
+	const linkBrowserBlocksRef = useRef(new Map<string, string>());
 	const activeWorkspaceSurface = activeId ? workspaceStates[activeId] ?? null : null;
 	const shouldAutoLoadWorkspaceFile = isBlankWorkspaceSurface(activeWorkspaceSurface);
-	const activeRuntime = activeId ? runtimes[activeId] ?? null : null;
+	const activeRuntime =
+		activeId && activeEnvironment
+			? runtimes[runtimeKey(activeId, activeEnvironment.id)] ?? null
+			: null;
 	const activeWorkspaceHasOttoBlocks = workspaceSurfaceHasOttoBlocks(activeWorkspaceSurface);
 	const isWorkspaceActivating = Boolean(activeId && canvasActiveWorkspaceId !== activeId);
 	const workspaceRuntimeStarting =
@@ -160,14 +183,21 @@ export function App() {
 					workspaceId,
 					message: 'Loading otto.yaml…',
 				});
-				const content = await readWorkspaceFile(activeEnvironment.path);
+				const merged = await readMergedWorkspaceFile(activeEnvironment.path);
 				if (cancelled) return;
-				const parsed = parseOttoWorkspaceFile(content);
+				const parsed = loadOttoWorkspaceFile(merged.file);
+				const overlays = merged.layers.slice(1);
+				workspaceFileTokensRef.current.set(activeEnvironment.path, merged.token);
+				workspaceFileOverlaysRef.current.set(activeEnvironment.path, overlays);
 				replaceWorkspaceState(workspaceId, parsed.surfaceState);
 				activateWorkspace(workspaceId);
 				setWorkspaceAutomation(workspaceId, parsed.automation);
 				setWorkspaceFileExistsState(true);
-				setWorkspaceFileMessage('Detected and loaded otto.yaml');
+				setWorkspaceFileMessage(
+					overlays.length > 0
+						? `Detected and loaded otto.yaml with ${overlays.length} overlay${overlays.length === 1 ? '' : 's'}`
+						: 'Detected and loaded otto.yaml',
+				);
 				setWorkspaceLoadingState((current) =>
 					current?.workspaceId === workspaceId ? null : current,
 				);
@@ -205,7 +235,43 @@ export function App() {
 				surfaceState: activeWorkspaceSurface,
 				automation: activeWorkspaceAutomation,
 			});
-			await writeWorkspaceFile(activeEnvironment.path, stringifyOttoWorkspaceFile(file));
+			const path = activeEnvironment.path;
+			const knownToken = workspaceFileTokensRef.current.get(path);
+			if (
+				!knownToken &&
+				(await workspaceFileExists(path)) &&
+				!window.confirm(
+					`${getOttoWorkspaceFilePath(path)} was not loaded into this workspace. Overwrite it?`,
+				)
+			) {
+				setWorkspaceFileMessage('Export cancelled');
+				return;
+			}
+			const overlays = workspaceFileOverlaysRef.current.get(path) ?? [];
+			if (
+				overlays.length > 0 &&
+				!window.confirm(
+					`This workspace includes ${overlays.join(', ')}. Their blocks and steps stay in those files, and changes made to them here are not exported. Continue?`,
+				)
+			) {
+				setWorkspaceFileMessage('Export cancelled');
+				return;
+			}
+			const content = stringifyOttoWorkspaceFile(
+				overlays.length > 0 ? await getBaseLayerWorkspaceFile(path, file) : file,
+			);
+			let token: string;
+			try {
+				token = await writeWorkspaceFile(path, content, knownToken);
+			} catch (error) {
+				if (!isWorkspaceFileConflict(error)) throw error;
+				if (!window.confirm(`${String(error)}.\n\nOverwrite it with this workspace?`)) {
+					setWorkspaceFileMessage('otto.yaml changed on disk; export cancelled');
+					return;
+				}
+				token = await writeWorkspaceFile(path, content);
+			}
+			workspaceFileTokensRef.current.set(path, token);
 			setWorkspaceFileExistsState(true);
 			setWorkspaceFileMessage(`Exported ${getOttoWorkspaceFilePath(activeEnvironment.path)}`);
 		} catch (error) {
@@ -222,13 +288,42 @@ export function App() {
 				deleteWorkspaceState(workspaceId);
 			}
 		}
-		for (const workspaceId of Object.keys(runtimes)) {
+		const runtimeWorkspaceIds = new Set(
+			Object.values(runtimes).map((runtime) => runtime.workspaceId),
+		);
+		for (const workspaceId of runtimeWorkspaceIds) {
 			if (!workspaceIds.has(workspaceId)) {
 				void stopRuntime(workspaceId).catch(() => undefined);
 			}
 		}
 	}, [deleteWorkspaceState, runtimes, stopRuntime, workspaceStates, workspaces]);
 
+	useEffect(() => {
+		const path = activeEnvironment?.path;
+		if (!isTauriRuntime() || !path) return;
+
+		let disposed = false;
+		let unlistenChanged: (() => void) | undefined;
+		void listenWorkspaceFileChanged((event) => {
+			if (event.projectPath !== path.trim()) return;
+			if (event.token && event.token === workspaceFileTokensRef.current.get(path)) return;
+			setWorkspaceFileExistsState(event.token !== null);
+			setWorkspaceFileMessage(
+				event.token === null ? 'otto.yaml was deleted on disk' : 'otto.yaml changed on disk',
+			);
+		}).then((dispose) => {
+			if (disposed) dispose();
+			else unlistenChanged = dispose;
+		});
+		void watchWorkspaceFile(path).catch(() => undefined);
+
+		return () => {
+			disposed = true;
+			unlistenChanged?.();
+			void unwatchWorkspaceFile(path).catch(() => undefined);
+		};
+	}, [activeEnvironment?.path]);
+
 	useEffect(() => {
 		if (!isTauriRuntime() || !active || !activeEnvironment) return;
 		void ensureRuntimeStarted({
@@ -243,6 +338,8 @@ export function App() {
 
 		let unlistenClose: (() => void) | undefined;
 		let unlistenFocus: (() => void) | undefined;
+		let unlistenOpenUrl: (() => void) | undefined;
+		let unlistenOpenEditor: (() => void) | undefined;
 
 		void listen<{ blockId: string }>('ghostty-close-block', (event) => {
 			closeBlockSurfaceById(event.payload.blockId);
@@ -259,11 +356,53 @@ export function App() {
 			unlistenFocus = dispose;
 		});
 
+		// Links from a terminal reuse the browser block it opened last, so
+		// following several links does not keep splitting the canvas.
+		void listen<GhosttyVtOpenUrlEvent>('ghostty-vt-open-url', (event) => {
+			const { sessionId, url } = event.payload;
+			const linkedId = linkBrowserBlocksRef.current.get(sessionId);
+			if (linkedId && useCanvasStore.getState().blocks[linkedId]?.type === 'browser') {
+				setBlockUrl(linkedId, url);
+				setFocused(linkedId);
+				return;
+			}
+			setFocused(sessionId);
+			addBlock('browser');
+			const browserId = useCanvasStore.getState().focusedBlockId;
+			if (browserId && browserId !== sessionId) {
+				linkBrowserBlocksRef.current.set(sessionId, browserId);
+				setBlockUrl(browserId, url);
+			}
+		}).then((dispose) => {
+			unlistenOpenUrl = dispose;
+		});
+
+		// Terminal editors need a terminal of their own.
+		void listen<GhosttyVtOpenEditorEvent>('ghostty-vt-open-editor', (event) => {
+			const { sessionId, label, command, cwd } = event.payload;
+			setFocused(sessionId);
+			addBlock('command', label);
+			const editorId = useCanvasStore.getState().focusedBlockId;
+			if (editorId && editorId !== sessionId) {
+				setCommandBlockConfig(editorId, { label, command, cwd });
+			}
+		}).then((dispose) => {
+			unlistenOpenEditor = dispose;
+		});
+
 		return () => {
 			unlistenClose?.();
 			unlistenFocus?.();
+			unlistenOpenUrl?.();
+			unlistenOpenEditor?.();
 		};
-	}, [closeBlockSurfaceById, setFocused]);
+	}, [
+		addBlock,
+		closeBlockSurfaceById,
+		setBlockUrl,
+		setCommandBlockConfig,
+		setFocused,
+	]);
 
 	return (
 		<div
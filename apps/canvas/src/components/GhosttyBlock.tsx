@@ -5,9 +5,9 @@ import {
 	destroyGhosttyVtSession,
 	getGhosttyVtStatus,
 	inputGhosttyVtKey,
+	pasteGhosttyVtText,
 	resizeGhosttyVtSession,
 	scrollGhosttyVtViewport,
-	sendGhosttyVtText,
 	snapshotGhosttyVtSession,
 	type GhosttyVtRgb,
 	type GhosttyVtSnapshot,
@@ -410,16 +410,19 @@ export function GhosttyBlock({ block, isFocused }: GhosttyBlockProps) {
 		hostRef.current?.focus();
 	}, [isFocused, nativeMode]);
 
-	const sendText = useCallback(
-		(text: string) => {
-			if (!text) return;
-			void sendGhosttyVtText(block.id, text)
-				.then(() => {
-					scheduleRefresh(0);
-				})
-				.catch((error) => {
-					setRuntimeError(error instanceof Error ? error.message : String(error));
-				});
+	// Multi-line pastes into a shell without bracketed paste come back with
+	// a warning instead of running each line; paste again once confirmed.
+	const pasteText = useCallback(
+		async (text: string) => {
+			try {
+				let result = await pasteGhosttyVtText(block.id, text);
+				if (result.warning && window.confirm(`${result.warning}\n\nPaste anyway?`)) {
+					result = await pasteGhosttyVtText(block.id, text, true);
+				}
+				if (result.pasted) scheduleRefresh(0);
+			} catch (error) {
+				setRuntimeError(error instanceof Error ? error.message : String(error));
+			}
 		},
 		[block.id, scheduleRefresh],
 	);
@@ -513,7 +516,7 @@ export function GhosttyBlock({ block, isFocused }: GhosttyBlockProps) {
 					if (!text) return;
 					event.preventDefault();
 					event.stopPropagation();
-					sendText(text);
+					void pasteText(text);
 				}}
 				onWheel={(event) => {
 					event.preventDefault();
@@ -13,7 +13,8 @@ import {
 import type { Block } from '../stores/canvas-store';
 import { useCanvasStore } from '../stores/canvas-store';
 import { useTabActivityStore } from '../stores/tab-activity-store';
-import { useWorkspaceRuntimeStore } from '../stores/workspace-runtime-store';
+import { selectRuntime, useWorkspaceRuntimeStore } from '../stores/workspace-runtime-store';
+import { useWorkspaceStore } from '../stores/workspace-store';
 
 interface OttoBlockProps {
 	block: Block;
@@ -46,9 +47,12 @@ function OttoRuntimeState({
 export function OttoBlock({ block, isFocused, workspaceId }: OttoBlockProps) {
 	const [sessionId, setSessionId] = useState<string | null>(block.sessionId ?? null);
 	const queryClient = useQueryClient();
-	const runtime = useWorkspaceRuntimeStore((s) =>
-		workspaceId ? s.runtimes[workspaceId] ?? null : null,
+	const environmentId = useWorkspaceStore(
+		(s) =>
+			s.workspaces.find((workspace) => workspace.id === workspaceId)?.primaryEnvironmentId ??
+			null,
 	);
+	const runtime = useWorkspaceRuntimeStore(selectRuntime(workspaceId, environmentId));
 	const setFocused = useCanvasStore((s) => s.setFocused);
 	const setBlockSessionId = useCanvasStore((s) => s.setBlockSessionId);
 	const containerRef = useRef<HTMLDivElement>(null);
@@ -1,7 +1,8 @@
 import { QueryClient, QueryClientProvider } from '@tanstack/react-query';
 import { useMemo, type ReactNode } from 'react';
 import { configureApiClient } from '@ottocode/web-sdk';
-import { useWorkspaceRuntimeStore } from '../stores/workspace-runtime-store';
+import { selectRuntime, useWorkspaceRuntimeStore } from '../stores/workspace-runtime-store';
+import { useWorkspaceStore } from '../stores/workspace-store';
 
 interface OttoWindow extends Window {
 	OTTO_SERVER_URL?: string;
@@ -16,9 +17,12 @@ export function OttoWorkspaceBoundary({
 	workspaceId: string;
 	isActive: boolean;
 }) {
-	const runtime = useWorkspaceRuntimeStore((state) =>
-		workspaceId ? state.runtimes[workspaceId] ?? null : null,
+	const environmentId = useWorkspaceStore(
+		(state) =>
+			state.workspaces.find((workspace) => workspace.id === workspaceId)
+				?.primaryEnvironmentId ?? null,
 	);
+	const runtime = useWorkspaceRuntimeStore(selectRuntime(workspaceId, environmentId));
 
 	const runtimeUrl = runtime?.status === 'ready' ? runtime.url : undefined;
 
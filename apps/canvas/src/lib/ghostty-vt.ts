@@ -1,4 +1,5 @@
 import { invoke } from '@tauri-apps/api/core';
+import type { WorkspaceEnvSpec } from './workspace-env';
 
 export interface GhosttyVtStatus {
 	available: boolean;
@@ -27,6 +28,7 @@ export interface GhosttyVtCell {
 
 export interface GhosttyVtRow {
 	cells: GhosttyVtCell[];
+	wrapped: boolean;
 }
 
 export interface GhosttyVtCursor {
@@ -37,6 +39,53 @@ export interface GhosttyVtCursor {
 	shape: string;
 }
 
+export type GhosttyVtLinkKind = 'hyperlink' | 'url' | 'file';
+
+/** A link in viewport row `row`; `endCol` is exclusive. */
+export interface GhosttyVtLink {
+	row: number;
+	startCol: number;
+	endCol: number;
+	kind: GhosttyVtLinkKind;
+	/** The URL, or the absolute path for files. */
+	target: string;
+	line?: number | null;
+	column?: number | null;
+}
+
+/** Payload of the `ghostty-vt-open-url` event: open `url` in a browser block. */
+export interface GhosttyVtOpenUrlEvent {
+	sessionId: string;
+	url: string;
+}
+
+/**
+ * Payload of the `ghostty-vt-open-editor` event: run `command`, a terminal
+ * editor opening a file, in a new terminal block next to the session.
+ */
+export interface GhosttyVtOpenEditorEvent {
+	sessionId: string;
+	label: string;
+	command: string;
+	cwd?: string | null;
+}
+
+/** Foreground process and window title of a session. */
+export interface GhosttyVtSessionInfo {
+	title?: string | null;
+	foregroundPid?: number | null;
+	foregroundName?: string | null;
+	foregroundArgv: string[];
+	/** A job other than the shell is running; confirm before closing. */
+	busy: boolean;
+}
+
+/** Payload of the `ghostty-vt-info-changed` event. */
+export interface GhosttyVtInfoEvent {
+	sessionId: string;
+	info: GhosttyVtSessionInfo;
+}
+
 export interface GhosttyVtSnapshot {
 	sessionId: string;
 	cols: number;
@@ -48,6 +97,8 @@ export interface GhosttyVtSnapshot {
 	cursor: GhosttyVtCursor;
 	processAlive: boolean;
 	exitStatus?: number | null;
+	info: GhosttyVtSessionInfo;
+	links: GhosttyVtLink[];
 }
 
 export async function getGhosttyVtStatus() {
@@ -62,6 +113,7 @@ export async function createGhosttyVtSession(
 		command?: string;
 		cols?: number;
 		rows?: number;
+		envSpec?: WorkspaceEnvSpec;
 	},
 ) {
 	return invoke('ghostty_vt_create_session', {
@@ -71,6 +123,7 @@ export async function createGhosttyVtSession(
 		command: payload?.command,
 		cols: payload?.cols,
 		rows: payload?.rows,
+		envSpec: payload?.envSpec,
 	});
 }
 
@@ -93,6 +146,30 @@ export async function sendGhosttyVtText(sessionId: string, text: string) {
 	return invoke('ghostty_vt_send_text', { sessionId, text });
 }
 
+/** Outcome of a paste; `warning` is set when it was held back. */
+export interface GhosttyVtPasteResult {
+	pasted: boolean;
+	bracketed: boolean;
+	strippedControls: number;
+	bytesWritten: number;
+	warning?: string | null;
+}
+
+/** Pastes `text` with bracketed paste when the application enabled it.
+ * Multi-line pastes into a shell without it are refused with a warning
+ * unless `allowMultiline` is set. */
+export async function pasteGhosttyVtText(
+	sessionId: string,
+	text: string,
+	allowMultiline?: boolean,
+) {
+	return invoke<GhosttyVtPasteResult>('ghostty_vt_paste', {
+		sessionId,
+		text,
+		allowMultiline: allowMultiline ?? null,
+	});
+}
+
 export async function inputGhosttyVtKey(
 	sessionId: string,
 	payload: {
@@ -120,6 +197,286 @@ export async function snapshotGhosttyVtSession(sessionId: string) {
 	return invoke<GhosttyVtSnapshot>('ghostty_vt_snapshot_session', { sessionId });
 }
 
+export interface GhosttyVtRowUpdate {
+	index: number;
+	cells: GhosttyVtCell[];
+}
+
+export interface GhosttyVtDelta {
+	sessionId: string;
+	seq: number;
+	full: boolean;
+	cols: number;
+	rows: number;
+	changedRows: GhosttyVtRowUpdate[];
+	/** Links in the changed rows. */
+	links: GhosttyVtLink[];
+	defaultFg: GhosttyVtRgb;
+	defaultBg: GhosttyVtRgb;
+	ansiPalette: GhosttyVtRgb[];
+	cursor: GhosttyVtCursor;
+	processAlive: boolean;
+	exitStatus?: number | null;
+}
+
+export async function snapshotGhosttyVtDelta(
+	sessionId: string,
+	sinceSeq?: number | null,
+) {
+	return invoke<GhosttyVtDelta>('ghostty_vt_snapshot_delta', {
+		sessionId,
+		sinceSeq: sinceSeq ?? null,
+	});
+}
+
+const GHOSTTY_VT_FRAME_MAGIC = 0x46545647; // "GVTF"
+const GHOSTTY_VT_FRAME_HEADER_LEN = 32;
+const GHOSTTY_VT_CURSOR_SHAPES = ['block', 'bar', 'underline', 'hollow-block'];
+
+/** Palette index used by spans that keep the default fg or bg. */
+export const GHOSTTY_VT_FRAME_DEFAULT_COLOR = 0xffff;
+
+/**
+ * A binary frame from the `ghostty-vt` URI scheme, see
+ * src-tauri/src/ghostty_vt_frame.rs for the layout. `spans` holds three
+ * words per span: fg | bg << 16, cells | style << 16, text offset.
+ */
+export interface GhosttyVtFrame {
+	version: number;
+	cols: number;
+	rows: number;
+	processAlive: boolean;
+	exitStatus: number | null;
+	cursor: GhosttyVtCursor;
+	palette: Uint32Array;
+	rowStarts: Uint32Array;
+	spans: Uint32Array;
+	cellLengths: Uint8Array;
+	text: string;
+}
+
+export function ghosttyVtFrameUrl(sessionId: string) {
+	const id = encodeURIComponent(sessionId);
+	// Windows webviews only reach custom schemes through http://<scheme>.localhost.
+	return navigator.userAgent.includes('Windows')
+		? `http://ghostty-vt.localhost/session/${id}`
+		: `ghostty-vt://session/${id}`;
+}
+
+export function decodeGhosttyVtFrame(buffer: ArrayBuffer): GhosttyVtFrame {
+	const view = new DataView(buffer);
+	if (
+		buffer.byteLength < GHOSTTY_VT_FRAME_HEADER_LEN ||
+		view.getUint32(0, true) !== GHOSTTY_VT_FRAME_MAGIC
+	) {
+		throw new Error('Not a ghostty-vt frame');
+	}
+	const version = view.getUint16(4, true);
+	if (version !== 1) {
+		throw new Error(`Unsupported ghostty-vt frame version ${version}`);
+	}
+	const flags = view.getUint16(6, true);
+	const rows = view.getUint16(10, true);
+	const paletteLen = view.getUint16(22, true);
+	const spanCount = view.getUint32(24, true);
+	const textLen = view.getUint32(28, true);
+
+	// Sections are 4-byte aligned, so the word arrays are views, not copies.
+	let offset = GHOSTTY_VT_FRAME_HEADER_LEN;
+	const palette = new Uint32Array(buffer, offset, paletteLen);
+	offset += paletteLen * 4;
+	const rowStarts = new Uint32Array(buffer, offset, rows + 1);
+	offset += (rows + 1) * 4;
+	const spans = new Uint32Array(buffer, offset, spanCount * 3);
+	offset += spanCount * 12;
+	let cellCount = 0;
+	for (let index = 1; index < spans.length; index += 3) {
+		cellCount += spans[index] & 0xffff;
+	}
+	const cellLengths = new Uint8Array(buffer, offset, cellCount);
+	offset += Math.ceil(cellCount / 4) * 4;
+	const text = new TextDecoder().decode(new Uint8Array(buffer, offset, textLen));
+
+	const hasPosition = (flags & 16) !== 0;
+	return {
+		version,
+		cols: view.getUint16(8, true),
+		rows,
+		processAlive: (flags & 1) !== 0,
+		exitStatus: flags & 2 ? view.getInt32(12, true) : null,
+		cursor: {
+			visible: (flags & 4) !== 0,
+			blinking: (flags & 8) !== 0,
+			x: hasPosition ? view.getUint16(16, true) : null,
+			y: hasPosition ? view.getUint16(18, true) : null,
+			shape: GHOSTTY_VT_CURSOR_SHAPES[view.getUint8(20)] ?? 'block',
+		},
+		palette,
+		rowStarts,
+		spans,
+		cellLengths,
+		text,
+	};
+}
+
+export async function fetchGhosttyVtFrame(sessionId: string) {
+	const response = await fetch(ghosttyVtFrameUrl(sessionId));
+	if (!response.ok) {
+		throw new Error(await response.text());
+	}
+	return decodeGhosttyVtFrame(await response.arrayBuffer());
+}
+
 export async function destroyGhosttyVtSession(sessionId: string) {
 	return invoke('ghostty_vt_destroy_session', { sessionId });
 }
+
+export interface GhosttyVtSearchMatch {
+	row: number;
+	startCol: number;
+	endCol: number;
+	text: string;
+}
+
+export interface GhosttyVtSearchResult {
+	matches: GhosttyVtSearchMatch[];
+	firstRow: number;
+	totalRows: number;
+	truncated: boolean;
+}
+
+export interface GhosttyVtSearchPosition {
+	index: number;
+	total: number;
+	match: GhosttyVtSearchMatch;
+}
+
+export async function searchGhosttyVtSession(
+	sessionId: string,
+	query: string,
+	options?: { regex?: boolean; caseSensitive?: boolean },
+) {
+	return invoke<GhosttyVtSearchResult>('ghostty_vt_search', {
+		sessionId,
+		query,
+		regex: options?.regex,
+		caseSensitive: options?.caseSensitive,
+	});
+}
+
+export async function navigateGhosttyVtSearch(
+	sessionId: string,
+	direction: 'next' | 'previous',
+) {
+	return invoke<GhosttyVtSearchPosition | null>('ghostty_vt_search_navigate', {
+		sessionId,
+		direction,
+	});
+}
+
+/** A command run at a shell prompt; rows count from the first line the session printed. */
+export interface GhosttyVtCommandRecord {
+	id: number;
+	command?: string | null;
+	cwd?: string | null;
+	promptRow: number;
+	outputStartRow?: number | null;
+	/** Exclusive. */
+	outputEndRow?: number | null;
+	startedAtMs?: number | null;
+	finishedAtMs?: number | null;
+	exitCode?: number | null;
+}
+
+/** Payload of the `ghostty-vt-command` event. */
+export interface GhosttyVtCommandEvent {
+	sessionId: string;
+	finished: boolean;
+	record: GhosttyVtCommandRecord;
+}
+
+/** Payload of the `ghostty-vt-cwd-changed` event. */
+export interface GhosttyVtCwdEvent {
+	sessionId: string;
+	cwd: string;
+}
+
+export async function listGhosttyVtCommandRecords(sessionId: string) {
+	return invoke<GhosttyVtCommandRecord[]>('ghostty_vt_command_records', {
+		sessionId,
+	});
+}
+
+export async function jumpToGhosttyVtPrompt(
+	sessionId: string,
+	direction: 'next' | 'previous',
+) {
+	return invoke<GhosttyVtCommandRecord | null>('ghostty_vt_jump_to_prompt', {
+		sessionId,
+		direction,
+	});
+}
+
+export async function getGhosttyVtSessionInfo(sessionId: string) {
+	return invoke<GhosttyVtSessionInfo>('ghostty_vt_session_info', { sessionId });
+}
+
+export interface GhosttyVtOpenLinkResult {
+	opened: boolean;
+	warning?: string | null;
+}
+
+/** Opens a link found in a session. Web and mail links open right away and
+ * files only in the editor or the file manager; links another app would
+ * handle are refused with a warning unless `allowExternal` is set after the
+ * user confirmed. */
+export async function openGhosttyVtLink(
+	sessionId: string,
+	link: GhosttyVtLink,
+	allowExternal?: boolean,
+) {
+	return invoke<GhosttyVtOpenLinkResult>('ghostty_vt_open_link', {
+		sessionId,
+		target: link.target,
+		line: link.line ?? null,
+		column: link.column ?? null,
+		allowExternal: allowExternal ?? null,
+	});
+}
+
+export type GhosttyVtExportFormat = 'text' | 'ansi' | 'html';
+
+export interface GhosttyVtExport {
+	rows: number;
+	content?: string | null;
+	path?: string | null;
+}
+
+export async function exportGhosttyVtScrollback(
+	sessionId: string,
+	format: GhosttyVtExportFormat,
+	path?: string,
+) {
+	return invoke<GhosttyVtExport>('ghostty_vt_export_scrollback', { sessionId, format, path });
+}
+
+export async function startGhosttyVtRecording(sessionId: string, path?: string) {
+	return invoke<string>('ghostty_vt_start_recording', { sessionId, path });
+}
+
+export async function stopGhosttyVtRecording(sessionId: string) {
+	return invoke<string | null>('ghostty_vt_stop_recording', { sessionId });
+}
+
+export async function createGhosttyVtReplaySession(
+	sessionId: string,
+	path: string,
+	options?: { speed?: number; maxIdleSecs?: number },
+) {
+	return invoke('ghostty_vt_create_replay_session', {
+		sessionId,
+		path,
+		speed: options?.speed,
+		maxIdleSecs: options?.maxIdleSecs,
+	});
+}
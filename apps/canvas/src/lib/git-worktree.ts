@@ -0,0 +1,35 @@
+import { invoke } from '@tauri-apps/api/core';
+
+export interface GitWorktreeInfo {
+	name?: string | null;
+	path: string;
+	branch?: string | null;
+	isMain: boolean;
+	locked: boolean;
+	prunable: boolean;
+	dirty: boolean;
+	ahead: number;
+	behind: number;
+}
+
+export async function createGitWorktree(input: {
+	repoPath: string;
+	branch: string;
+	createBranch: boolean;
+	base?: string;
+	root?: string;
+}) {
+	return invoke<GitWorktreeInfo>('git_worktree_create', input);
+}
+
+export async function listGitWorktrees(repoPath: string) {
+	return invoke<GitWorktreeInfo[]>('git_worktree_list', { repoPath });
+}
+
+export async function removeGitWorktree(repoPath: string, name: string, force = false) {
+	return invoke('git_worktree_remove', { repoPath, name, force });
+}
+
+export async function pruneGitWorktrees(repoPath: string) {
+	return invoke<string[]>('git_worktree_prune', { repoPath });
+}
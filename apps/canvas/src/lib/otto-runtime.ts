@@ -1,4 +1,14 @@
-import { invoke } from '@tauri-apps/api/core';
+import { Channel, invoke } from '@tauri-apps/api/core';
+import type { WorkspaceEnvSpec } from './workspace-env';
+
+export type WorkspaceRuntimeProcessStatus = 'starting' | 'ready' | 'restarting' | 'failed';
+
+export type WorkspaceRestartPolicy = 'never' | 'onFailure' | 'always';
+
+export interface WorkspaceRuntimeLogOptions {
+	maxBytes?: number;
+	retention?: number;
+}
 
 export interface WorkspaceRuntimeInfo {
 	workspaceId: string;
@@ -8,32 +18,162 @@ export interface WorkspaceRuntimeInfo {
 	port: number;
 	url: string;
 	logPath: string;
+	status: WorkspaceRuntimeProcessStatus;
+	restartPolicy: WorkspaceRestartPolicy;
+	restartCount: number;
 }
 
-export async function startWorkspaceRuntime(input: {
-	workspaceId: string;
-	environmentId: string;
-	projectPath: string;
-}) {
-	return invoke<WorkspaceRuntimeInfo>('workspace_start_runtime', input);
+export type WorkspaceRuntimeEvent =
+	| { event: 'starting'; data: { info: WorkspaceRuntimeInfo } }
+	| { event: 'ready'; data: { info: WorkspaceRuntimeInfo } }
+	| {
+			event: 'restarting';
+			data: {
+				info: WorkspaceRuntimeInfo;
+				exitCode?: number | null;
+				logTail: string;
+				delayMs: number;
+			};
+	  }
+	| {
+			event: 'failed';
+			data: {
+				info: WorkspaceRuntimeInfo;
+				error: string;
+				exitCode?: number | null;
+				logTail: string;
+			};
+	  }
+	| {
+			event: 'exited';
+			data: {
+				info: WorkspaceRuntimeInfo;
+				exitCode?: number | null;
+				logTail: string;
+			};
+	  };
+
+export async function startWorkspaceRuntime(
+	input: {
+		workspaceId: string;
+		environmentId: string;
+		projectPath: string;
+		restartPolicy?: WorkspaceRestartPolicy;
+		logOptions?: WorkspaceRuntimeLogOptions;
+		envSpec?: WorkspaceEnvSpec;
+	},
+	onEvent?: (event: WorkspaceRuntimeEvent) => void,
+) {
+	const channel = new Channel<WorkspaceRuntimeEvent>();
+	if (onEvent) channel.onmessage = onEvent;
+	return invoke<WorkspaceRuntimeInfo>('workspace_start_runtime', {
+		...input,
+		onEvent: channel,
+	});
 }
 
-export async function getWorkspaceRuntime(workspaceId: string) {
+export async function getWorkspaceRuntime(workspaceId: string, environmentId?: string) {
 	return invoke<WorkspaceRuntimeInfo | null>('workspace_get_runtime', {
 		workspaceId,
+		environmentId,
 	});
 }
 
-export async function stopWorkspaceRuntime(workspaceId: string) {
-	return invoke('workspace_stop_runtime', { workspaceId });
+export async function stopWorkspaceRuntime(workspaceId: string, environmentId?: string) {
+	return invoke('workspace_stop_runtime', { workspaceId, environmentId });
 }
 
 export async function readWorkspaceRuntimeLog(logPath: string) {
 	return invoke<string>('workspace_read_runtime_log', { logPath });
 }
 
-export async function listWorkspaceRuntimes() {
-	return invoke<WorkspaceRuntimeInfo[]>('workspace_list_runtimes');
+export interface WorkspaceRuntimeLogLine {
+	offset: number;
+	text: string;
+}
+
+export interface WorkspaceRuntimeLogPage {
+	lines: WorkspaceRuntimeLogLine[];
+	startOffset: number;
+	endOffset: number;
+	hasMore: boolean;
+}
+
+export type WorkspaceRuntimeLogEvent =
+	| {
+			event: 'lines';
+			data: { lines: WorkspaceRuntimeLogLine[]; endOffset: number };
+	  }
+	| { event: 'truncated'; data?: undefined };
+
+export async function readWorkspaceRuntimeLogPage(
+	logPath: string,
+	options?: { beforeOffset?: number; limit?: number },
+) {
+	return invoke<WorkspaceRuntimeLogPage>('workspace_read_runtime_log_page', {
+		logPath,
+		beforeOffset: options?.beforeOffset,
+		limit: options?.limit,
+	});
+}
+
+export async function subscribeWorkspaceRuntimeLog(
+	logPath: string,
+	onEvent: (event: WorkspaceRuntimeLogEvent) => void,
+	fromOffset?: number,
+) {
+	const channel = new Channel<WorkspaceRuntimeLogEvent>();
+	channel.onmessage = onEvent;
+	const subscription = await invoke<{ subscriptionId: number; offset: number }>(
+		'workspace_subscribe_runtime_log',
+		{ logPath, fromOffset, onEvent: channel },
+	);
+	return {
+		offset: subscription.offset,
+		unsubscribe: () =>
+			invoke('workspace_unsubscribe_runtime_log', {
+				subscriptionId: subscription.subscriptionId,
+			}),
+	};
+}
+
+export interface WorkspaceRuntimeLogGeneration {
+	generation: number;
+	path: string;
+	size: number;
+	modifiedMs?: number | null;
+}
+
+export async function listWorkspaceRuntimeLogs(workspaceId: string, environmentId: string) {
+	return invoke<WorkspaceRuntimeLogGeneration[]>('workspace_list_runtime_logs', {
+		workspaceId,
+		environmentId,
+	});
+}
+
+export async function readWorkspaceRuntimeLogGeneration(
+	workspaceId: string,
+	environmentId: string,
+	generation: number,
+	options?: { beforeOffset?: number; limit?: number },
+) {
+	return invoke<WorkspaceRuntimeLogPage>('workspace_read_runtime_log_generation', {
+		workspaceId,
+		environmentId,
+		generation,
+		beforeOffset: options?.beforeOffset,
+		limit: options?.limit,
+	});
+}
+
+export async function listWorkspaceRuntimes(filter?: {
+	workspaceId?: string;
+	environmentId?: string;
+}) {
+	return invoke<WorkspaceRuntimeInfo[]>('workspace_list_runtimes', {
+		workspaceId: filter?.workspaceId,
+		environmentId: filter?.environmentId,
+	});
 }
 
 export async function stopAllWorkspaceRuntimes() {
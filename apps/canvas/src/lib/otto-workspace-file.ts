@@ -24,6 +24,8 @@ export interface OttoWorkspaceFile {
 	};
 	ensure: WorkspaceEnsureStep[];
 	startup: WorkspaceStartupStep[];
+	env?: Record<string, string>;
+	envFiles?: string[];
 	tabs: OttoWorkspaceTab[];
 }
 
@@ -197,6 +199,13 @@ function sanitizeStartup(startup: unknown): WorkspaceStartupStep[] {
 		.filter((item) => typeof item.label === 'string' && typeof item.run === 'string');
 }
 
+function sanitizeEnv(env: unknown): Record<string, string> | undefined {
+	if (!env || typeof env !== 'object' || Array.isArray(env)) return undefined;
+	return Object.fromEntries(
+		Object.entries(env).filter((entry): entry is [string, string] => typeof entry[1] === 'string'),
+	);
+}
+
 export function buildOttoWorkspaceFile(args: {
 	workspace: Workspace;
 	surfaceState: WorkspaceSurfaceState;
@@ -215,12 +224,20 @@ export function buildOttoWorkspaceFile(args: {
 		},
 		ensure: args.automation.ensure,
 		startup: args.automation.startup,
+		...(args.automation.env && Object.keys(args.automation.env).length > 0
+			? { env: args.automation.env }
+			: {}),
+		...(args.automation.envFiles?.length ? { envFiles: args.automation.envFiles } : {}),
 		tabs,
 	};
 }
 
 export function parseOttoWorkspaceFile(text: string): ParsedOttoWorkspace {
-	const parsed = parse(text) as Partial<OttoWorkspaceFile> | null;
+	return loadOttoWorkspaceFile(parse(text));
+}
+
+export function loadOttoWorkspaceFile(value: unknown): ParsedOttoWorkspace {
+	const parsed = value as Partial<OttoWorkspaceFile> | null;
 	if (!parsed || typeof parsed !== 'object') {
 		throw new Error('otto.yaml did not contain a valid workspace object.');
 	}
@@ -275,6 +292,10 @@ export function parseOttoWorkspaceFile(text: string): ParsedOttoWorkspace {
 		automation: {
 			ensure: sanitizeEnsure(parsed.ensure),
 			startup: sanitizeStartup(parsed.startup),
+			env: sanitizeEnv(parsed.env),
+			envFiles: Array.isArray(parsed.envFiles)
+				? parsed.envFiles.filter((item): item is string => typeof item === 'string')
+				: undefined,
 		},
 	};
 }
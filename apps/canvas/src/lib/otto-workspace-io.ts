@@ -1,4 +1,19 @@
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import type { OttoWorkspaceFile } from './otto-workspace-file';
+
+const WORKSPACE_FILE_CONFLICT = 'Workspace file changed on disk';
+
+export interface WorkspaceFileContents {
+	content: string;
+	token: string;
+}
+
+export interface WorkspaceFileChangedEvent {
+	projectPath: string;
+	filePath: string;
+	token: string | null;
+}
 
 export async function workspaceFileExists(projectPath: string) {
 	return invoke<boolean>('workspace_file_exists', { projectPath });
@@ -8,6 +23,150 @@ export async function readWorkspaceFile(projectPath: string) {
 	return invoke<string>('workspace_file_read', { projectPath });
 }
 
-export async function writeWorkspaceFile(projectPath: string, content: string) {
-	return invoke<void>('workspace_file_write', { projectPath, content });
+export async function readWorkspaceFileVersioned(projectPath: string) {
+	return invoke<WorkspaceFileContents>('workspace_file_read_versioned', { projectPath });
+}
+
+export async function writeWorkspaceFile(
+	projectPath: string,
+	content: string,
+	expectedToken?: string,
+) {
+	return invoke<string>('workspace_file_write', { projectPath, content, expectedToken });
+}
+
+export function isWorkspaceFileConflict(error: unknown) {
+	return String(error).startsWith(WORKSPACE_FILE_CONFLICT);
+}
+
+export async function watchWorkspaceFile(projectPath: string) {
+	return invoke<string | null>('workspace_file_watch', { projectPath });
+}
+
+export async function unwatchWorkspaceFile(projectPath: string) {
+	return invoke<void>('workspace_file_unwatch', { projectPath });
+}
+
+export async function listenWorkspaceFileChanged(
+	handler: (event: WorkspaceFileChangedEvent) => void,
+) {
+	return listen<WorkspaceFileChangedEvent>('workspace-file-changed', (event) =>
+		handler(event.payload),
+	);
+}
+
+export interface WorkspaceFileIssue {
+	message: string;
+	path: string | null;
+	line: number | null;
+	column: number | null;
+}
+
+export interface WorkspaceFileCheck {
+	valid: boolean;
+	version: number | null;
+	migratedFrom: number | null;
+	issues: WorkspaceFileIssue[];
+}
+
+export async function validateWorkspaceFile(projectPath: string, content?: string) {
+	return invoke<WorkspaceFileCheck>('workspace_file_validate', { projectPath, content });
+}
+
+export type WorkspaceElementKind =
+	| 'workspace'
+	| 'tab'
+	| 'block'
+	| 'ensure'
+	| 'startup'
+	| 'env'
+	| 'envFile';
+
+export interface WorkspaceElementSource {
+	kind: WorkspaceElementKind;
+	id: string;
+	source: string;
+	overridden: string[];
+}
+
+export interface MergedWorkspaceFile {
+	file: OttoWorkspaceFile;
+	layers: string[];
+	sources: WorkspaceElementSource[];
+	issues: WorkspaceFileIssue[];
+	token: string;
+}
+
+export async function readMergedWorkspaceFile(projectPath: string) {
+	return invoke<MergedWorkspaceFile>('workspace_file_read_merged', { projectPath });
+}
+
+/**
+ * What exporting `file` writes to otto.yaml: blocks, steps and other elements
+ * from fragments or otto.local.yaml stay in their own files.
+ */
+export async function getBaseLayerWorkspaceFile(
+	projectPath: string,
+	file: OttoWorkspaceFile,
+) {
+	return invoke<OttoWorkspaceFile>('workspace_file_base_layer', { projectPath, file });
+}
+
+export interface WorkspaceRevisionItem {
+	id: string;
+	label: string;
+}
+
+export interface WorkspaceRevisionSummary {
+	tabsAdded: WorkspaceRevisionItem[];
+	tabsRemoved: WorkspaceRevisionItem[];
+	blocksAdded: WorkspaceRevisionItem[];
+	blocksRemoved: WorkspaceRevisionItem[];
+	unparsed: boolean;
+}
+
+export interface WorkspaceFileRevision {
+	revisionId: number;
+	createdAtMs: number;
+	token: string;
+	size: number;
+	summary: WorkspaceRevisionSummary;
+}
+
+export interface WorkspaceDiffLine {
+	kind: 'context' | 'added' | 'removed';
+	text: string;
+}
+
+export interface WorkspaceRevisionDiff {
+	summary: WorkspaceRevisionSummary;
+	lines: WorkspaceDiffLine[];
+}
+
+export async function listWorkspaceFileHistory(projectPath: string) {
+	return invoke<WorkspaceFileRevision[]>('workspace_file_history_list', { projectPath });
+}
+
+export async function diffWorkspaceFileRevision(
+	projectPath: string,
+	revisionId: number,
+	against?: number,
+) {
+	return invoke<WorkspaceRevisionDiff>('workspace_file_history_diff', {
+		projectPath,
+		revisionId,
+		against,
+	});
+}
+
+export async function restoreWorkspaceFileRevision(
+	projectPath: string,
+	revisionId: number,
+	expectedToken?: string,
+) {
+	return invoke<string>('workspace_file_history_restore', {
+		projectPath,
+		revisionId,
+		expectedToken,
+	});
 }
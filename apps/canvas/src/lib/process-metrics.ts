@@ -0,0 +1,34 @@
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+
+export type ProcessOwner =
+	| { kind: 'runtime'; workspaceId: string; environmentId: string }
+	| { kind: 'terminal'; sessionId: string };
+
+export type ProcessTreeMetrics = ProcessOwner & {
+	pid: number;
+	cpuPercent: number;
+	rssBytes: number;
+	threads: number;
+	children: number;
+	readBytes: number;
+	writeBytes: number;
+};
+
+export interface CanvasProcessMetrics {
+	supported: boolean;
+	sampledAtMs: number;
+	processes: ProcessTreeMetrics[];
+}
+
+export async function getCanvasProcessMetrics() {
+	return invoke<CanvasProcessMetrics>('canvas_process_metrics');
+}
+
+export async function listenCanvasProcessMetrics(
+	handler: (metrics: CanvasProcessMetrics) => void,
+) {
+	return listen<CanvasProcessMetrics>('canvas-process-metrics', (event) =>
+		handler(event.payload),
+	);
+}
@@ -0,0 +1,37 @@
+import { Channel, invoke } from '@tauri-apps/api/core';
+import type { WorkspaceEnsureStep } from '../stores/workspace-store';
+import type { WorkspaceEnvSpec } from './workspace-env';
+
+export type WorkspaceStepStatus = 'skipped' | 'ok' | 'failed';
+
+export interface WorkspaceStepResult {
+	stepId: string;
+	status: WorkspaceStepStatus;
+	exitCode?: number | null;
+	durationMs: number;
+	reason?: string | null;
+}
+
+export type WorkspaceEnsureEvent =
+	| { event: 'started'; data: { stepId: string; label: string } }
+	| {
+			event: 'output';
+			data: { stepId: string; stream: 'stdout' | 'stderr'; line: string };
+	  }
+	| { event: 'finished'; data: { result: WorkspaceStepResult } };
+
+export async function runWorkspaceEnsureSteps(
+	environmentPath: string,
+	steps: WorkspaceEnsureStep[],
+	onEvent?: (event: WorkspaceEnsureEvent) => void,
+	envSpec?: WorkspaceEnvSpec,
+) {
+	const channel = new Channel<WorkspaceEnsureEvent>();
+	if (onEvent) channel.onmessage = onEvent;
+	return invoke<WorkspaceStepResult[]>('workspace_run_ensure_steps', {
+		environmentPath,
+		steps,
+		envSpec,
+		onEvent: channel,
+	});
+}
@@ -0,0 +1,23 @@
+import { invoke } from '@tauri-apps/api/core';
+
+export interface WorkspaceEnvSpec {
+	workspaceRoot?: string;
+	environmentPath?: string;
+	env?: Record<string, string>;
+	envFiles?: string[];
+}
+
+export interface ResolvedEnvVar {
+	name: string;
+	value: string;
+	secret: boolean;
+	source: string | null;
+}
+
+export async function resolveWorkspaceEnv(spec: WorkspaceEnvSpec, reveal = false) {
+	return invoke<ResolvedEnvVar[]>('workspace_resolve_env', { spec, reveal });
+}
+
+export async function interpolateWorkspaceText(spec: WorkspaceEnvSpec, text: string) {
+	return invoke<string>('workspace_interpolate', { spec, text });
+}
@@ -0,0 +1,82 @@
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import type { WorkspaceRestartPolicy } from './otto-runtime';
+import type { WorkspaceEnvSpec } from './workspace-env';
+import type { WorkspaceStartupStep } from '../stores/workspace-store';
+
+export type WorkspaceServiceStatus = 'running' | 'restarting' | 'stopped' | 'failed';
+
+export interface WorkspaceServiceInfo {
+	workspaceId: string;
+	environmentId: string;
+	serviceId: string;
+	label: string;
+	run: string;
+	cwd: string;
+	pid: number | null;
+	status: WorkspaceServiceStatus;
+	restartPolicy: WorkspaceRestartPolicy;
+	restartCount: number;
+	exitCode: number | null;
+	error: string | null;
+	logPath: string;
+	startedAtMs: number | null;
+}
+
+interface WorkspaceServiceTarget {
+	workspaceId: string;
+	environmentId: string;
+	environmentPath: string;
+	restartPolicy?: WorkspaceRestartPolicy;
+	envSpec?: WorkspaceEnvSpec;
+}
+
+export async function startWorkspaceService(
+	target: WorkspaceServiceTarget & { step: WorkspaceStartupStep },
+) {
+	return invoke<WorkspaceServiceInfo>('workspace_start_service', target);
+}
+
+export async function startWorkspaceServicesOnOpen(
+	target: WorkspaceServiceTarget & { steps: WorkspaceStartupStep[] },
+) {
+	return invoke<WorkspaceServiceInfo[]>('workspace_start_services_on_open', target);
+}
+
+export async function stopWorkspaceService(
+	workspaceId: string,
+	environmentId: string,
+	serviceId: string,
+) {
+	return invoke<void>('workspace_stop_service', { workspaceId, environmentId, serviceId });
+}
+
+export async function restartWorkspaceService(
+	workspaceId: string,
+	environmentId: string,
+	serviceId: string,
+) {
+	return invoke<WorkspaceServiceInfo>('workspace_restart_service', {
+		workspaceId,
+		environmentId,
+		serviceId,
+	});
+}
+
+export async function listWorkspaceServices(filter?: {
+	workspaceId?: string;
+	environmentId?: string;
+}) {
+	return invoke<WorkspaceServiceInfo[]>('workspace_list_services', {
+		workspaceId: filter?.workspaceId,
+		environmentId: filter?.environmentId,
+	});
+}
+
+export async function listenWorkspaceServiceStatus(
+	handler: (info: WorkspaceServiceInfo) => void,
+) {
+	return listen<WorkspaceServiceInfo>('workspace-service-status', (event) =>
+		handler(event.payload),
+	);
+}
@@ -31,13 +31,46 @@ interface EnsureRuntimeInput {
 }
 
 interface WorkspaceRuntimeStore {
+	/** Keyed by {@link runtimeKey}: every environment has its own runtime. */
 	runtimes: Record<string, WorkspaceRuntimeState>;
 	ensureStarted: (input: EnsureRuntimeInput) => Promise<WorkspaceRuntimeState>;
-	refreshRuntime: (workspaceId: string) => Promise<WorkspaceRuntimeState | null>;
-	stopRuntime: (workspaceId: string) => Promise<void>;
+	refreshRuntime: (
+		workspaceId: string,
+		environmentId: string,
+	) => Promise<WorkspaceRuntimeState | null>;
+	/** Stops one environment's runtime, or all of the workspace's without one. */
+	stopRuntime: (workspaceId: string, environmentId?: string) => Promise<void>;
 	stopAll: () => Promise<void>;
 }
 
+export function runtimeKey(workspaceId: string, environmentId: string) {
+	return `${workspaceId}/${environmentId}`;
+}
+
+export function selectRuntime(
+	workspaceId: string | null | undefined,
+	environmentId: string | null | undefined,
+) {
+	return (state: WorkspaceRuntimeStore) =>
+		workspaceId && environmentId
+			? state.runtimes[runtimeKey(workspaceId, environmentId)] ?? null
+			: null;
+}
+
+function stoppedRuntime(
+	workspaceId: string,
+	environmentId: string,
+	previous?: WorkspaceRuntimeState,
+): WorkspaceRuntimeState {
+	return {
+		workspaceId,
+		environmentId,
+		projectPath: previous?.projectPath ?? '',
+		status: 'stopped',
+		error: null,
+	};
+}
+
 const pendingStarts = new Map<string, Promise<WorkspaceRuntimeState>>();
 
 async function ensureRuntimeReachable(info: WorkspaceRuntimeInfo) {
@@ -70,7 +103,8 @@ export const useWorkspaceRuntimeStore = create<WorkspaceRuntimeStore>((set, get)
 	runtimes: {},
 
 	ensureStarted: async (input) => {
-		const existing = get().runtimes[input.workspaceId];
+		const key = runtimeKey(input.workspaceId, input.environmentId);
+		const existing = get().runtimes[key];
 		if (existing?.status === 'ready' && existing.url) {
 			try {
 				await waitForWorkspaceRuntime(existing.url, {
@@ -82,7 +116,7 @@ export const useWorkspaceRuntimeStore = create<WorkspaceRuntimeStore>((set, get)
 				set((state) => ({
 					runtimes: {
 						...state.runtimes,
-						[input.workspaceId]: {
+						[key]: {
 							...existing,
 							status: 'starting',
 							error: null,
@@ -92,7 +126,7 @@ export const useWorkspaceRuntimeStore = create<WorkspaceRuntimeStore>((set, get)
 			}
 		}
 		if (existing?.status === 'starting') {
-			const pending = pendingStarts.get(input.workspaceId);
+			const pending = pendingStarts.get(key);
 			if (pending) return pending;
 		}
 
@@ -100,7 +134,7 @@ export const useWorkspaceRuntimeStore = create<WorkspaceRuntimeStore>((set, get)
 			set((state) => ({
 				runtimes: {
 					...state.runtimes,
-					[input.workspaceId]: {
+					[key]: {
 						workspaceId: input.workspaceId,
 						environmentId: input.environmentId,
 						projectPath: input.projectPath,
@@ -111,28 +145,58 @@ export const useWorkspaceRuntimeStore = create<WorkspaceRuntimeStore>((set, get)
 			}));
 
 			try {
-				let info = await getWorkspaceRuntime(input.workspaceId);
+				let info = await getWorkspaceRuntime(input.workspaceId, input.environmentId);
 				if (info) {
 					try {
 						await ensureRuntimeReachable(info);
 					} catch {
-						await stopWorkspaceRuntime(input.workspaceId).catch(() => undefined);
+						await stopWorkspaceRuntime(input.workspaceId, input.environmentId).catch(
+							() => undefined,
+						);
 						info = null;
 					}
 				}
-				info ??= await startWorkspaceRuntime(input);
+				info ??= await startWorkspaceRuntime(input, (event) => {
+					const current = get().runtimes[key];
+					if (!current || current.status === 'stopped') return;
+					let patch: Partial<WorkspaceRuntimeState> | null = null;
+					if (event.event === 'restarting') {
+						patch = { status: 'starting', error: null };
+					} else if (event.event === 'ready' && current.status !== 'ready') {
+						patch = fromRuntimeInfo(event.data.info, 'ready');
+					} else if (event.event === 'failed' && current.pid === event.data.info.pid) {
+						patch = {
+							status: 'error',
+							error: `${event.data.error}\n\nLast runtime log lines:\n${event.data.logTail}`,
+						};
+					} else if (event.event === 'exited' && current.pid === event.data.info.pid) {
+						patch = {
+							status: event.data.exitCode ? 'error' : 'stopped',
+							error: event.data.exitCode
+								? `Otto runtime exited with code ${event.data.exitCode}\n\nLast runtime log lines:\n${event.data.logTail}`
+								: null,
+						};
+					}
+					if (!patch) return;
+					set((state) => ({
+						runtimes: {
+							...state.runtimes,
+							[key]: { ...current, ...patch },
+						},
+					}));
+				});
 				await ensureRuntimeReachable(info);
 				const next = fromRuntimeInfo(info, 'ready');
 				set((state) => ({
 					runtimes: {
 						...state.runtimes,
-						[input.workspaceId]: next,
+						[key]: next,
 					},
 				}));
 				return next;
 			} catch (error) {
 				const message = error instanceof Error ? error.message : String(error);
-				const info = await getWorkspaceRuntime(input.workspaceId);
+				const info = await getWorkspaceRuntime(input.workspaceId, input.environmentId);
 				let logSnippet = '';
 				if (info?.logPath) {
 					try {
@@ -155,32 +219,27 @@ export const useWorkspaceRuntimeStore = create<WorkspaceRuntimeStore>((set, get)
 				set((state) => ({
 					runtimes: {
 						...state.runtimes,
-						[input.workspaceId]: next,
+						[key]: next,
 					},
 				}));
 				throw error;
 			} finally {
-				pendingStarts.delete(input.workspaceId);
+				pendingStarts.delete(key);
 			}
 		})();
 
-		pendingStarts.set(input.workspaceId, pending);
+		pendingStarts.set(key, pending);
 		return pending;
 	},
 
-	refreshRuntime: async (workspaceId) => {
-		const info = await getWorkspaceRuntime(workspaceId);
+	refreshRuntime: async (workspaceId, environmentId) => {
+		const key = runtimeKey(workspaceId, environmentId);
+		const info = await getWorkspaceRuntime(workspaceId, environmentId);
 		if (!info) {
 			set((state) => ({
 				runtimes: {
 					...state.runtimes,
-					[workspaceId]: {
-						workspaceId,
-						environmentId: state.runtimes[workspaceId]?.environmentId ?? '',
-						projectPath: state.runtimes[workspaceId]?.projectPath ?? '',
-						status: 'stopped',
-						error: null,
-					},
+					[key]: stoppedRuntime(workspaceId, environmentId, state.runtimes[key]),
 				},
 			}));
 			return null;
@@ -190,26 +249,26 @@ export const useWorkspaceRuntimeStore = create<WorkspaceRuntimeStore>((set, get)
 		set((state) => ({
 			runtimes: {
 				...state.runtimes,
-				[workspaceId]: next,
+				[key]: next,
 			},
 		}));
 		return next;
 	},
 
-	stopRuntime: async (workspaceId) => {
-		await stopWorkspaceRuntime(workspaceId);
-		set((state) => ({
-			runtimes: {
-				...state.runtimes,
-				[workspaceId]: {
-					workspaceId,
-					environmentId: state.runtimes[workspaceId]?.environmentId ?? '',
-					projectPath: state.runtimes[workspaceId]?.projectPath ?? '',
-					status: 'stopped',
-					error: null,
-				},
-			},
-		}));
+	stopRuntime: async (workspaceId, environmentId) => {
+		await stopWorkspaceRuntime(workspaceId, environmentId);
+		set((state) => {
+			const runtimes = { ...state.runtimes };
+			for (const [key, runtime] of Object.entries(state.runtimes)) {
+				if (
+					runtime.workspaceId === workspaceId &&
+					(!environmentId || runtime.environmentId === environmentId)
+				) {
+					runtimes[key] = stoppedRuntime(workspaceId, runtime.environmentId, runtime);
+				}
+			}
+			return { runtimes };
+		});
 	},
 
 	stopAll: async () => {
@@ -48,6 +48,8 @@ export interface WorkspaceStartupStep {
 export interface WorkspaceAutomationConfig {
 	ensure: WorkspaceEnsureStep[];
 	startup: WorkspaceStartupStep[];
+	env?: Record<string, string>;
+	envFiles?: string[];
 }
 
 interface CreateWorkspaceInput {
@@ -55,6 +57,13 @@ interface CreateWorkspaceInput {
 	path: string;
 }
 
+interface AddEnvironmentInput {
+	workspaceId: string;
+	kind: EnvironmentKind;
+	path: string;
+	label: string;
+}
+
 interface WorkspaceState {
 	workspaces: Workspace[];
 	environments: Record<string, Environment>;
@@ -64,6 +73,8 @@ interface WorkspaceState {
 	setActive: (id: string) => void;
 	addWorkspace: (input: CreateWorkspaceInput) => string;
 	removeWorkspace: (id: string) => void;
+	addEnvironment: (input: AddEnvironmentInput) => string;
+	removeEnvironment: (id: string) => void;
 	setWorkspaceAutomation: (workspaceId: string, config: WorkspaceAutomationConfig) => void;
 	toggleSidebar: () => void;
 	getActiveWorkspace: () => Workspace | null;
@@ -181,6 +192,40 @@ export const useWorkspaceStore = create<WorkspaceState>()(
 				});
 			},
 
+			addEnvironment: ({ workspaceId, kind, path, label }) => {
+				const normalizedPath = path.trim();
+				if (!normalizedPath) {
+					throw new Error('Environment path is required');
+				}
+
+				const now = Date.now();
+				const id = generateId();
+				set((state) => ({
+					environments: {
+						...state.environments,
+						[id]: {
+							id,
+							workspaceId,
+							kind,
+							path: normalizedPath,
+							label: label.trim() || getNameFromPath(normalizedPath),
+							isPrimary: false,
+							createdAt: now,
+							updatedAt: now,
+						},
+					},
+				}));
+				return id;
+			},
+
+			removeEnvironment: (id) =>
+				set((state) => {
+					if (!state.environments[id] || state.environments[id].isPrimary) return state;
+					const nextEnvironments = { ...state.environments };
+					delete nextEnvironments[id];
+					return { environments: nextEnvironments };
+				}),
+
 			setWorkspaceAutomation: (workspaceId, config) =>
 				set((state) => ({
 					workspaceAutomation: {